@@ -0,0 +1,50 @@
+/*
+What --version tells about the build: the commit it was built from, the day and the rustc that built it. The target
+comes from cargo already (TARGET is only handed to build scripts, so it's passed along too). Builds outside a git
+checkout, from a release tarball say, just say unknown, and SOURCE_DATE_EPOCH is honoured so a reproducible build
+gets the same date every time...
+*/
+use std::env;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let commit = output("git", &["rev-parse", "--short=12", "HEAD"]).unwrap_or_else(|| String::from("unknown"));
+    let rustc = output(&env::var("RUSTC").unwrap_or_else(|_| String::from("rustc")), &["--version"]).unwrap_or_else(|| String::from("unknown"));
+    let seconds = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0));
+
+    println!("cargo:rustc-env=WC_CLONE_COMMIT={}", commit);
+    println!("cargo:rustc-env=WC_CLONE_BUILD_DATE={}", date(seconds));
+    println!("cargo:rustc-env=WC_CLONE_TARGET={}", env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=WC_CLONE_RUSTC={}", rustc);
+
+    // a new commit moves HEAD or the branch it points at
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    if let Some(branch) = std::fs::read_to_string(".git/HEAD").ok().and_then(|head| head.strip_prefix("ref: ").map(|r| r.trim().to_string())) {
+        println!("cargo:rerun-if-changed=.git/{}", branch);
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let text = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !text.trim().is_empty()).then(|| text.trim().to_string())
+}
+
+// YYYY-MM-DD in UTC, from the days since 1970 (Howard Hinnant's civil_from_days)
+fn date(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
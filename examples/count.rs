@@ -0,0 +1,35 @@
+/*
+Counting with wc_clone as a library, through everything the prelude promises to keep working. Counts stdin, or the
+text given as arguments:
+
+    cargo run --example count -- "some text to count"
+    cargo run --example count < sherlock.txt
+*/
+use std::io;
+
+use wc_clone::prelude::*;
+
+fn main() -> Result<(), String> {
+    let text: Vec<String> = std::env::args().skip(1).collect();
+    let options = CountOptions::new().chars(true).max_line_length(true);
+
+    let stats: FileStats = match text.is_empty() {
+        true => count_reader(io::stdin(), &options).map_err(|err| err.to_string())?,
+        false => count_str(&text.join(" "), &options),
+    };
+    println!("lines {} words {} chars {} bytes {} longest {}", stats.lines(), stats.words(), stats.chars(), stats.bytes(), stats.max_line_length());
+
+    // the binary's options work too, anything but files
+    let strict = CountOptions::parse(["-m", "--strict"])?;
+    if let Err(err) = count_reader(&b"caf\xe9"[..], &strict) {
+        println!("--strict: {}", err);
+    }
+    let first_line = CountOptions::parse(["-l", "--max-lines=1"])?.words(true);
+    let stats = count_str("one two\nthree\n", &first_line);
+    println!("first line: {} line, {} words", stats.lines(), stats.words());
+
+    // and the totals add up like the binary's
+    let total: FileStats = ["one two\n", "three\n"].iter().map(|text| count_str(text, &CountOptions::new())).sum();
+    println!("total: {}", total);
+    Ok(())
+}
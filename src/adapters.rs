@@ -0,0 +1,87 @@
+/*
+Input adapters for document formats where the bytes on disk aren't the text we want to count.
+Each adapter sits behind its own cargo feature so the plain wc build doesn't pull in the parsers, except HTML's, which
+is only a tag scanner and only used by --auto...
+*/
+mod html;
+#[cfg(feature = "office")]
+mod office;
+#[cfg(feature = "pdf")]
+mod pdf;
+
+use std::path::Path;
+
+use crate::ReadResult;
+
+/*
+Returns the adapter that extracts the text from paths like this one, or None so the caller reads the file as usual.
+Only looks at the name, nothing gets opened until the adapter is called.
+*/
+pub fn adapter_for(path: &str) -> Option<fn(&str) -> ReadResult> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+
+    match extension.as_str() {
+        #[cfg(feature = "pdf")]
+        "pdf" => Some(pdf::read_pdf),
+        #[cfg(feature = "office")]
+        "docx" => Some(office::read_docx),
+        #[cfg(feature = "office")]
+        "odt" => Some(office::read_odt),
+        _ => None,
+    }
+}
+
+// for --auto, which goes by what the content turned out to be (see mime.rs) instead of the name
+pub fn adapter_for_type(mime: &str) -> Option<fn(&str) -> ReadResult> {
+    match mime {
+        "text/html" => Some(html::read_html),
+        #[cfg(feature = "pdf")]
+        "application/pdf" => Some(pdf::read_pdf),
+        #[cfg(feature = "office")]
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some(office::read_docx),
+        #[cfg(feature = "office")]
+        "application/vnd.oasis.opendocument.text" => Some(office::read_odt),
+        _ => None,
+    }
+}
+
+// the entities of xml and html text, &amp; and the like and the numeric ones
+pub fn push_unescaped(text: &mut String, raw: &str) {
+    let mut rest = raw;
+
+    while let Some(amp) = rest.find('&') {
+        text.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let Some(semi) = rest.find(';') else { break };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'), // html's, xml only has the five above
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+
+        match decoded {
+            Some(c) => {
+                text.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                // not an entity we know, keep the ampersand as is
+                text.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    text.push_str(rest);
+}
@@ -0,0 +1,57 @@
+use std::fs;
+
+use super::push_unescaped;
+use crate::ReadResult;
+
+pub fn read_html(path: &str) -> ReadResult {
+    match fs::read(path) {
+        Ok(bytes) => ReadResult::Utf8(html_to_text(&String::from_utf8_lossy(&bytes))),
+        Err(err) => ReadResult::ReadError(Box::new(err)),
+    }
+}
+
+/*
+The text a browser would show, more or less: tags, comments and whatever is inside <script> and <style> are left out,
+entities decoded, and the elements that start a line of their own (paragraphs, headings, list items, table rows, <br>)
+end one here too so lines still mean something. Whitespace is kept the way the page has it...
+*/
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        push_unescaped(&mut text, &rest[..open]);
+        rest = &rest[open..];
+
+        let close = if rest.starts_with("<!--") {
+            rest.find("-->").map(|i| i + 2)
+        } else {
+            rest.find('>')
+        };
+        let Some(close) = close else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        let is_end = tag.starts_with('/');
+        let name = tag.trim_start_matches('/').split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("").to_ascii_lowercase();
+        match name.as_str() {
+            // their content is code, up to the end tag
+            "script" | "style" if !is_end && !tag.ends_with('/') => {
+                let end_tag = format!("</{}", name);
+                let end = rest.to_ascii_lowercase().find(&end_tag).unwrap_or(rest.len());
+                rest = &rest[end..];
+            },
+            "br" => text.push('\n'),
+            "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "pre" | "blockquote" | "dt" | "dd"
+            | "section" | "article" | "header" | "footer" | "title" if is_end => text.push('\n'),
+            "td" | "th" if is_end => text.push('\t'),
+            _ => {},
+        }
+    }
+
+    push_unescaped(&mut text, rest);
+    text
+}
@@ -0,0 +1,117 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+
+use super::push_unescaped;
+use crate::ReadResult;
+
+enum Flavor {
+    Word,         // .docx, Office Open XML
+    OpenDocument, // .odt
+}
+
+pub fn read_docx(path: &str) -> ReadResult {
+    read_office(path, Flavor::Word)
+}
+
+pub fn read_odt(path: &str) -> ReadResult {
+    read_office(path, Flavor::OpenDocument)
+}
+
+/*
+Unzips the document and pulls the text out of its main xml part, one line per paragraph.
+*/
+fn read_office(path: &str, flavor: Flavor) -> ReadResult {
+    match extract_text(path, &flavor) {
+        Ok(text) => ReadResult::Utf8(text),
+        Err(err) => ReadResult::ReadError(err),
+    }
+}
+
+fn extract_text(path: &str, flavor: &Flavor) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let member = match flavor {
+        Flavor::Word => "word/document.xml",
+        Flavor::OpenDocument => "content.xml",
+    };
+
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let mut xml = String::new();
+    archive.by_name(member)?.read_to_string(&mut xml)?;
+
+    Ok(xml_to_text(&xml, flavor))
+}
+
+/*
+Not a real xml parser, just enough of a tag scanner for the markup word processors write. Text is only kept inside
+the elements that hold document content (w:t for word, text:p/text:h for odt) so styles and metadata don't get counted...
+*/
+fn xml_to_text(xml: &str, flavor: &Flavor) -> String {
+    let mut text = String::new();
+    let mut depth = 0; // how many content elements we're nested in
+    let mut rest = xml;
+
+    while let Some(open) = rest.find('<') {
+        if depth > 0 {
+            push_unescaped(&mut text, &rest[..open]);
+        }
+        rest = &rest[open..];
+
+        // comments and processing instructions can contain '>' so skip them whole
+        let close = if rest.starts_with("<!--") {
+            rest.find("-->").map(|i| i + 2)
+        } else {
+            rest.find('>')
+        };
+        let Some(close) = close else { break };
+
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        let is_end = tag.starts_with('/');
+        let is_empty = tag.ends_with('/');
+        let name = tag.trim_start_matches('/').trim_end_matches('/');
+        let name = name.split_whitespace().next().unwrap_or("");
+        let local_name = name.rsplit(':').next().unwrap_or(name);
+
+        match (flavor, local_name) {
+            (Flavor::Word, "t") | (Flavor::OpenDocument, "p") | (Flavor::OpenDocument, "h") if !is_empty => {
+                if is_end {
+                    depth -= 1;
+                } else {
+                    depth += 1;
+                }
+            }
+            _ => {}
+        }
+
+        if is_end {
+            match (flavor, local_name) {
+                (Flavor::Word, "p") | (Flavor::OpenDocument, "p") | (Flavor::OpenDocument, "h") => text.push('\n'),
+                _ => {}
+            }
+        } else {
+            match (flavor, local_name) {
+                (_, "tab") => text.push('\t'),
+                (Flavor::Word, "br") | (Flavor::Word, "cr") | (Flavor::OpenDocument, "line-break") => text.push('\n'),
+                (Flavor::OpenDocument, "s") => {
+                    // <text:s text:c="3"/> is a run of spaces
+                    let spaces = attribute(tag, "text:c").and_then(|c| c.parse().ok()).unwrap_or(1);
+                    text.push_str(&" ".repeat(spaces));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    text
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!("{}=\"", name))? + name.len() + 2;
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
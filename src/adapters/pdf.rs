@@ -0,0 +1,11 @@
+use crate::ReadResult;
+
+/*
+Extracts the text of every page so we can count each one separately, the caller sums them up for the file row.
+*/
+pub fn read_pdf(path: &str) -> ReadResult {
+    match pdf_extract::extract_text_by_pages(path) {
+        Ok(pages) => ReadResult::Pages(pages),
+        Err(err) => ReadResult::ReadError(Box::new(err)),
+    }
+}
@@ -0,0 +1,137 @@
+/*
+.zip inputs (with the archives feature) are counted member by member instead of as the bytes of the archive: every file
+in it is decompressed as it's read, gets a row of its own named ARCHIVE:MEMBER, and the archive's row after them adds
+them up. Directories don't get one. Tar archives are counted the same way, but only with --auto, which is what
+recognizes them (and the ones inside a gzip, zstd or xz file), and need no feature since reading one is just 512 byte
+headers and the files between them. The limits (--max-lines, --max-bytes...) are for each member, they're files of
+their own. Like documents an archive is only opened up when it's a regular file read without a byte range, --posix,
+--compat or --no-decompress, otherwise its bytes are counted like any file's...
+
+    wc_clone -l exports.zip
+*/
+use std::io::{self, Read};
+
+use crate::i18n::tr;
+use crate::stream;
+use crate::utf8::Utf8Mode;
+use crate::{CommandOptions, FileStats, ReadResult};
+
+const TAR_BLOCK: usize = 512;
+
+#[cfg(feature = "archives")]
+pub fn is_archive(path: &str) -> bool {
+    std::path::Path::new(path).extension().and_then(|extension| extension.to_str()).is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+#[cfg(not(feature = "archives"))]
+pub fn is_archive(_path: &str) -> bool {
+    false
+}
+
+#[cfg(feature = "archives")]
+pub fn read_zip(path: &str, command_options: &CommandOptions) -> ReadResult {
+    match count_members(path, command_options) {
+        Ok(members) => ReadResult::Members(members),
+        Err(err) => crate::read_error(err),
+    }
+}
+
+// is_archive is always false then, so nothing gets here
+#[cfg(not(feature = "archives"))]
+pub fn read_zip(_path: &str, _command_options: &CommandOptions) -> ReadResult {
+    ReadResult::ReadError(Box::new(io::Error::new(io::ErrorKind::Unsupported, "wc_clone was built without the archives feature")))
+}
+
+#[cfg(feature = "archives")]
+fn count_members(path: &str, command_options: &CommandOptions) -> io::Result<Vec<(String, FileStats)>> {
+    use std::fs::File;
+
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let mut members = Vec::new();
+    for i in 0..archive.len() {
+        let member = archive.by_index(i)?;
+        if member.is_dir() {
+            continue;
+        }
+        let name = member.name()?.to_string();
+        let stats = count_member(member, &name, command_options)?;
+        members.push((name, stats));
+    }
+    Ok(members)
+}
+
+// every regular file in a tar archive, reader being at its start
+pub fn read_tar(reader: impl Read + Send, command_options: &CommandOptions) -> ReadResult {
+    match count_tar_members(reader, command_options) {
+        Ok(members) => ReadResult::Members(members),
+        Err(err) => crate::read_error(err),
+    }
+}
+
+fn count_tar_members(mut reader: impl Read + Send, command_options: &CommandOptions) -> io::Result<Vec<(String, FileStats)>> {
+    let mut members = Vec::new();
+    let mut long_name = None; // from a GNU ././@LongLink entry, for the member after it
+    let mut header = [0; TAR_BLOCK];
+    loop {
+        if !read_block(&mut reader, &mut header)? || header.iter().all(|byte| *byte == 0) {
+            return Ok(members); // the end, two zero blocks or just the end of the file
+        }
+        let size = octal(&header[124..136]).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("not a tar header")))?;
+        let padding = (TAR_BLOCK as u64 - size % TAR_BLOCK as u64) % TAR_BLOCK as u64;
+        let mut data = (&mut reader).take(size + padding);
+
+        match header[156] {
+            b'L' => {
+                let mut name = Vec::new();
+                (&mut data).take(size).read_to_end(&mut name)?;
+                long_name = Some(String::from_utf8_lossy(&name).trim_end_matches('\0').to_string());
+            },
+            // a regular file, the others (directories, links, pax headers...) have nothing to count
+            b'0' | b'\0' | b'7' => {
+                let name = long_name.take().unwrap_or_else(|| header_name(&header));
+                let stats = count_member((&mut data).take(size), &name, command_options)?;
+                members.push((name, stats));
+            },
+            _ => long_name = None,
+        }
+        // whatever the limits didn't read of the member, and the padding after it
+        io::copy(&mut data, &mut io::sink())?;
+    }
+}
+
+// false at the end of the input
+fn read_block(reader: &mut impl Read, block: &mut [u8; TAR_BLOCK]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < TAR_BLOCK {
+        match reader.read(&mut block[filled..])? {
+            0 if filled == 0 => return Ok(false),
+            0 => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, tr!("the tar archive is cut off"))),
+            read => filled += read,
+        }
+    }
+    Ok(true)
+}
+
+// ustar keeps the directories of a long name apart, in the prefix field
+fn header_name(header: &[u8; TAR_BLOCK]) -> String {
+    let field = |bytes: &[u8]| String::from_utf8_lossy(bytes.split(|byte| *byte == 0).next().unwrap_or_default()).into_owned();
+    let (name, prefix) = (field(&header[0..100]), field(&header[345..500]));
+    if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) }
+}
+
+// sizes are octal digits padded with spaces or NULs
+fn octal(field: &[u8]) -> Option<u64> {
+    let digits = std::str::from_utf8(field).ok()?.trim_matches(|c: char| c == '\0' || c == ' ');
+    u64::from_str_radix(digits, 8).ok()
+}
+
+// a member of either kind of archive, counted like a file of its own
+fn count_member(member: impl Read + Send, name: &str, command_options: &CommandOptions) -> io::Result<FileStats> {
+    let in_member = |err: io::Error| io::Error::new(err.kind(), format!("{}: {}", name, err));
+    match stream::count_reader(member, command_options, name).map_err(in_member)? {
+        (_, Some(offset)) if command_options.utf8_mode == Utf8Mode::Strict => {
+            Err(io::Error::new(io::ErrorKind::InvalidData, tr!("{}: invalid UTF-8 at byte {}", name, offset)))
+        },
+        (stats, _) => Ok(stats),
+    }
+}
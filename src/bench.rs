@@ -0,0 +1,154 @@
+/*
+The bench subcommand times each way wc_clone can read an input, so the flags can be picked for the machine at hand
+instead of guessed: plain reads, reads through a bigger buffer, mmap, what --io=auto picks, and -j over one copy of
+the input per core. Every strategy counts the input once to warm the page cache and then RUNS more times, the best
+run is what's printed, and the counts have to come out the same or the row says so. Options after the input (-m, -L,
+--hash...) are counted with, since they change what each byte costs. --generate=SIZE benchmarks that much of what
+the generate subcommand writes instead of a file...
+
+    wc_clone bench big.log -m
+    wc_clone bench --generate=256M
+*/
+use std::env;
+use std::fs;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::generate::Generator;
+use crate::human::{self, Units};
+use crate::i18n::tr;
+use crate::{pipeline, CommandOptions};
+
+const RUNS: usize = 5;
+
+struct Strategy {
+    name: String,
+    args: Vec<String>,
+    copies: usize, // of the input, for -j to have something to spread out
+}
+
+fn strategies() -> Vec<Strategy> {
+    let strategy = |name: &str, args: &[&str]| Strategy { name: name.to_string(), args: args.iter().map(|arg| arg.to_string()).collect(), copies: 1 };
+    let mut strategies = vec![strategy("read", &["--io=read"]), strategy("read, 1M buffer", &["--io=read", "--buffer-size=1M"])];
+    if cfg!(feature = "mmap") {
+        strategies.push(strategy("mmap", &["--io=mmap"]));
+    }
+    strategies.push(strategy("auto", &[]));
+
+    let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+    if cores > 1 {
+        strategies.push(Strategy { name: format!("parallel, -j{}", cores), args: vec![format!("-j{}", cores)], copies: cores });
+    }
+    strategies
+}
+
+// argv here is whatever came after "bench"
+pub fn run(argv: impl Iterator<Item = String>) {
+    let mut input: Option<String> = None;
+    let mut generate: Option<usize> = None;
+    let mut counting_args = Vec::new();
+    for arg in argv {
+        if let Some(size) = arg.strip_prefix("--generate=") {
+            match crate::parse_size("--generate", size) {
+                Ok(size) => generate = Some(size),
+                Err(err) => {
+                    println!("{}", err);
+                    return;
+                },
+            }
+        } else if input.is_none() && !arg.starts_with('-') {
+            input = Some(arg);
+        } else {
+            counting_args.push(arg);
+        }
+    }
+
+    let (path, generated) = match (input, generate) {
+        (Some(path), None) => (path, false),
+        (None, Some(size)) => match write_generated(size) {
+            Ok(path) => (path, true),
+            Err(err) => {
+                eprintln!("wc_clone: can't write the generated input: {}", err);
+                process::exit(1);
+            },
+        },
+        (None, None) => {
+            println!("bench needs a FILE or --generate=SIZE");
+            return;
+        },
+        (Some(_), Some(_)) => {
+            println!("bench takes a FILE or --generate=SIZE, not both");
+            return;
+        },
+    };
+
+    let size = match fs::metadata(&path) {
+        Ok(metadata) => metadata.len(),
+        Err(err) => {
+            println!("{}", tr!("Encounted error reading file {}: {}", path, err));
+            return;
+        },
+    };
+    let counting = if counting_args.is_empty() { String::from("-lwc") } else { counting_args.join(" ") };
+    println!("bench: {} ({}), counting {}, best of {}", path, human::size(size, Units::Binary), counting, RUNS);
+    println!("{:<20} {:>10} {:>14}", "strategy", "time", "throughput");
+
+    let mut first_counts: Option<Vec<String>> = None;
+    for strategy in strategies() {
+        match measure(&strategy, &path, &counting_args) {
+            Ok((best, counts)) => {
+                let bytes = size * strategy.copies as u64;
+                let throughput = (bytes as f64 / best.as_secs_f64().max(1e-9)) as u64;
+                let agrees = first_counts.get_or_insert_with(|| counts.clone()) == &counts;
+                println!(
+                    "{:<20} {:>10} {:>14}{}",
+                    strategy.name,
+                    format!("{:.2}ms", best.as_secs_f64() * 1000.0),
+                    format!("{}/s", human::size(throughput, Units::Binary)),
+                    if agrees { "" } else { "  (counts differ!)" }
+                );
+            },
+            Err(err) => println!("{:<20} {}", strategy.name, err),
+        }
+    }
+
+    if generated {
+        let _ = fs::remove_file(&path);
+    }
+}
+
+// the best of RUNS timed runs, and the rows they counted
+fn measure(strategy: &Strategy, path: &str, counting_args: &[String]) -> Result<(Duration, Vec<String>), String> {
+    let files = vec![path.to_string(); strategy.copies];
+    let mut args = vec![String::from("wc_clone")];
+    args.extend(strategy.args.iter().cloned());
+    args.extend(counting_args.iter().cloned());
+    args.push(String::from("--"));
+    args.extend(files.iter().cloned());
+    let command_options = CommandOptions::build(args.into_iter())?;
+
+    let mut best = Duration::MAX;
+    let mut counts = Vec::new();
+    for run in 0..=RUNS {
+        let started = Instant::now();
+        let results = pipeline::count_all(&files, &command_options, command_options.jobs);
+        let elapsed = started.elapsed();
+
+        counts = results
+            .into_iter()
+            .map(|result| crate::counted_stats(result, path, &command_options).map(|stats| crate::format_run_results(&command_options, &stats, "")))
+            .collect::<Result<_, _>>()?;
+        if run > 0 {
+            best = best.min(elapsed); // the first one only warms the cache
+        }
+    }
+    Ok((best, counts))
+}
+
+// generate's text, the same every time
+fn write_generated(size: usize) -> std::io::Result<String> {
+    let path = env::temp_dir().join(format!("wc_clone-bench-{}.txt", process::id()));
+    fs::write(&path, Generator::new(0, 60, false).text(size))?;
+    Ok(path.to_string_lossy().into_owned())
+}
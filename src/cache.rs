@@ -0,0 +1,110 @@
+/*
+--no-cache-pollution: tells the kernel we read each file once, front to back, and that it can forget the pages once
+we're past them. Without it a one-off `wc_clone -r /data` fills the page cache with data nobody needs and evicts
+whatever the other services on the box were relying on. The catch is that pages which were already cached before we
+came along get dropped too, so it's opt-in...
+*/
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::throttle;
+
+// how far a reader gets past the last advice before it advises again, small enough to keep the cache footprint flat
+const DROP_INTERVAL: u64 = 8 * 1024 * 1024;
+
+/*
+A file opened for counting. With avoid_pollution on it drops the pages behind it as it reads and everything it read
+when it's dropped, and with a --throttle rate each read waits its turn, otherwise it's just the file.
+*/
+pub struct FileReader {
+    file: File,
+    avoid_pollution: bool,
+    throttle: Option<u64>, // bytes a second
+    position: u64,
+    dropped_up_to: u64,
+}
+
+pub fn open(path: &str, avoid_pollution: bool) -> io::Result<FileReader> {
+    Ok(reader(File::open(path)?, avoid_pollution))
+}
+
+// for a file that's already open, --timeout opens them its own way
+pub fn reader(file: File, avoid_pollution: bool) -> FileReader {
+    if avoid_pollution {
+        advise_sequential(&file);
+    }
+
+    FileReader { file, avoid_pollution, throttle: None, position: 0, dropped_up_to: 0 }
+}
+
+impl FileReader {
+    pub fn throttled(mut self, throttle: Option<u64>) -> Self {
+        self.throttle = throttle;
+        self
+    }
+}
+
+impl Read for FileReader {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = self.file.read(buffer)?;
+        self.position += read as u64;
+        if let Some(bytes_per_second) = self.throttle {
+            throttle::pace(read, bytes_per_second);
+        }
+
+        if self.avoid_pollution && self.position - self.dropped_up_to >= DROP_INTERVAL {
+            drop_cached(&self.file, self.dropped_up_to, self.position - self.dropped_up_to);
+            self.dropped_up_to = self.position;
+        }
+        Ok(read)
+    }
+}
+
+// only ever to the --offset, before any reading, so there's nothing behind us to drop yet
+impl Seek for FileReader {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.position = self.file.seek(position)?;
+        self.dropped_up_to = self.position;
+        Ok(self.position)
+    }
+}
+
+impl Drop for FileReader {
+    fn drop(&mut self) {
+        if self.avoid_pollution && self.position > self.dropped_up_to {
+            drop_cached(&self.file, self.dropped_up_to, self.position - self.dropped_up_to);
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd"))]
+pub fn advise_sequential(file: &File) {
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: just a hint on a file descriptor we own, failures are harmless and ignored
+    unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_SEQUENTIAL) };
+}
+
+// a len of 0 means everything from offset to the end of the file
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd"))]
+pub fn drop_cached(file: &File, offset: u64, len: u64) {
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: as above
+    unsafe { libc::posix_fadvise(file.as_raw_fd(), offset as libc::off_t, len as libc::off_t, libc::POSIX_FADV_DONTNEED) };
+}
+
+// macOS has no fadvise, F_NOCACHE keeps our reads out of the cache in the first place
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn advise_sequential(file: &File) {
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: as above
+    unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) };
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn drop_cached(_file: &File, _offset: u64, _len: u64) {}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "macos", target_os = "ios")))]
+pub fn advise_sequential(_file: &File) {}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "macos", target_os = "ios")))]
+pub fn drop_cached(_file: &File, _offset: u64, _len: u64) {}
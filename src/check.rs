@@ -0,0 +1,155 @@
+/*
+--check, sha256sum -c for counts: the operands are the output of an earlier run and every file listed in them is counted
+again with the same options and compared. Each file gets an OK or CHANGED line (with what changed), and anything that
+changed or can't be read anymore makes the exit status 1, and so does a line that isn't a row of counts and a name the
+way these options print them, or a manifest with no file in it to check...
+
+    wc_clone -lw --hash=sha256 -r docs > manifest.txt
+    wc_clone -lw --hash=sha256 --check manifest.txt
+*/
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::process;
+
+use crate::{interrupt, pipeline, CommandOptions};
+
+// one file the manifest has counts for
+struct Entry {
+    counts: Vec<String>,
+    file: String,
+}
+
+enum Line {
+    Entry(Entry),
+    Nothing,      // blank, or the total
+    Misformatted, // not the columns of the options given and a name
+}
+
+pub fn run(command_options: CommandOptions) {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut misformatted = 0;
+
+    let manifests = if command_options.files.is_empty() { vec![String::from("-")] } else { command_options.files.clone() };
+    for manifest in &manifests {
+        let contents = match read_manifest(manifest) {
+            Ok(contents) => contents,
+            Err(err) => {
+                println!("Encounted error reading file {}: {}", manifest, err);
+                process::exit(1);
+            }
+        };
+
+        for (number, line) in contents.lines().enumerate() {
+            match parse_line(line, command_options.counters()) {
+                Line::Entry(entry) => entries.push(entry),
+                Line::Nothing => {},
+                Line::Misformatted => {
+                    eprintln!("wc_clone: {}:{}: not formatted like wc_clone output with these options", manifest, number + 1);
+                    misformatted += 1;
+                },
+            }
+        }
+    }
+
+    let files: Vec<String> = entries.iter().map(|entry| entry.file.clone()).collect();
+    let results = pipeline::count_all(&files, &command_options, command_options.jobs);
+    let names = crate::column_names(&command_options);
+    let mut changed = 0;
+    let mut failed = 0;
+
+    for (entry, result) in entries.iter().zip(results) {
+        let stats = match crate::counted_stats(result, &entry.file, &command_options) {
+            Ok(stats) => stats,
+            Err(err) => {
+                println!("{}: FAILED ({})", entry.file, err);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let current = crate::selected_counts(&command_options, &stats);
+        let differences: Vec<String> = names
+            .iter()
+            .zip(entry.counts.iter().zip(&current))
+            .filter(|(_, (recorded, current))| recorded != current)
+            .map(|(name, (recorded, current))| format!("{} {} -> {}", name, recorded, current))
+            .collect();
+
+        if differences.is_empty() {
+            println!("{}: OK", entry.file);
+        } else {
+            println!("{}: CHANGED ({})", entry.file, differences.join(", "));
+            changed += 1;
+        }
+    }
+
+    if interrupt::is_interrupted() {
+        eprintln!("wc_clone: interrupted before every file was checked");
+        process::exit(interrupt::EXIT_CODE);
+    }
+    if misformatted > 0 {
+        eprintln!("wc_clone: WARNING: {} line{} not formatted like wc_clone output", misformatted, plural(misformatted));
+    }
+    if entries.is_empty() {
+        eprintln!("wc_clone: no file to check in {}", manifests.join(", "));
+        process::exit(1);
+    }
+    if changed > 0 {
+        eprintln!("wc_clone: WARNING: {} of {} listed file{} changed", changed, entries.len(), plural(entries.len()));
+    }
+    if failed > 0 {
+        eprintln!("wc_clone: WARNING: {} listed file{} could not be read", failed, plural(failed));
+    }
+    if changed > 0 || failed > 0 || misformatted > 0 {
+        process::exit(1);
+    }
+}
+
+fn read_manifest(manifest: &str) -> io::Result<String> {
+    if manifest != "-" {
+        return fs::read_to_string(manifest);
+    }
+    if io::stdin().lock().is_terminal() {
+        return Err(io::Error::other("no manifest given and stdin is a terminal"));
+    }
+
+    let mut contents = String::new();
+    io::stdin().read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/*
+A row is the counts (however they were padded) and then the name after a single separator, so names with spaces in
+them survive. The total has nothing to check and is left out, and so would stdin, but a row without a name can just as
+well be one with a column missing, so those are misformatted like any row with too few columns.
+*/
+fn parse_line(line: &str, counters: usize) -> Line {
+    let mut rest = line.trim_end_matches('\r');
+    if rest.trim().is_empty() {
+        return Line::Nothing;
+    }
+
+    let mut counts = Vec::new();
+    for _ in 0..counters {
+        rest = rest.trim_start();
+        match rest.find(char::is_whitespace) {
+            Some(end) => {
+                counts.push(rest[..end].to_string());
+                rest = &rest[end..];
+            },
+            None => return Line::Misformatted,
+        }
+    }
+
+    // the separator can be any whitespace, a no-break space is two bytes
+    let separator = rest.chars().next().map_or(0, char::len_utf8);
+    match &rest[separator..] {
+        "total" => Line::Nothing,
+        "" => Line::Misformatted,
+        file => Line::Entry(Entry { counts, file: file.to_string() }),
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
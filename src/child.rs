@@ -0,0 +1,90 @@
+/*
+Inputs that are another program's output: curl for https, the aws and gcloud CLIs for s3:// and gs://, ssh for
+--remote. Reading the input is reading the pipe, and once it runs dry the exit status says whether that was all of it
+or the program gave up, in which case what it printed to stderr is the error. The pipe can be polled, so --timeout
+works on it the same as on a FIFO, and whatever stops reading early (a line limit, a timeout) kills the program on the
+way out...
+
+filter is the same with an input of ours fed to the program's stdin from a thread, zstd -dc and xz -dc for compressed
+streams. An error reading that input is the error, rather than whatever the program makes of its input being cut off.
+*/
+use std::io::{self, Read};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::thread::{self, JoinHandle};
+
+use crate::timeout::{self, Deadline};
+use crate::CommandOptions;
+
+pub struct ChildOutput {
+    child: Child,
+    stdout: ChildStdout,
+    feeder: Option<JoinHandle<io::Result<()>>>, // the thread writing to its stdin, for filter
+    stderr: Option<JoinHandle<String>>,
+}
+
+pub fn spawn(mut command: Command, command_options: &CommandOptions) -> io::Result<Deadline<ChildOutput>> {
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    let stdout = child.stdout.take().ok_or_else(|| io::Error::other("the child has no stdout"))?;
+    let stderr = drain_stderr(&mut child);
+    Ok(timeout::pipe(ChildOutput { child, stdout, feeder: None, stderr }, command_options))
+}
+
+pub fn filter(mut command: Command, mut input: Box<dyn Read + Send>, command_options: &CommandOptions) -> io::Result<Deadline<ChildOutput>> {
+    command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    let stdout = child.stdout.take().ok_or_else(|| io::Error::other("the child has no stdout"))?;
+    let mut stdin = child.stdin.take().ok_or_else(|| io::Error::other("the child has no stdin"))?;
+    let feeder = thread::spawn(move || match io::copy(&mut input, &mut stdin) {
+        // the program stopped reading, its exit status says why
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.map(|_| ()),
+    });
+    let stderr = drain_stderr(&mut child);
+    Ok(timeout::pipe(ChildOutput { child, stdout, feeder: Some(feeder), stderr }, command_options))
+}
+
+/*
+Read from a thread as it comes rather than once stdout is done: a program with more than a pipe buffer of warnings or
+progress to write would otherwise block on its stderr and never finish its stdout.
+*/
+fn drain_stderr(child: &mut Child) -> Option<JoinHandle<String>> {
+    let mut stderr = child.stderr.take()?;
+    Some(thread::spawn(move || {
+        let mut message = String::new();
+        let _ = stderr.read_to_string(&mut message);
+        message
+    }))
+}
+
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for ChildOutput {
+    fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+        self.stdout.as_raw_fd()
+    }
+}
+
+impl Read for ChildOutput {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = self.stdout.read(buffer)?;
+        if read == 0 && !buffer.is_empty() {
+            if let Some(feeder) = self.feeder.take() {
+                feeder.join().map_err(|_| io::Error::other("the thread feeding the child panicked"))??;
+            }
+            if !self.child.wait()?.success() {
+                let message = self.stderr.take().and_then(|stderr| stderr.join().ok()).unwrap_or_default();
+                return Err(io::Error::other(message.trim().to_string()));
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl Drop for ChildOutput {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
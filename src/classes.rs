@@ -0,0 +1,69 @@
+/*
+--char-classes splits the characters of each input into letters, digits, punctuation, whitespace and everything else,
+for sanity checking data files: an ID column that should only hold digits, a CSV that picked up tabs, that kind of
+thing. Letters and digits are the Unicode alphabetic and numeric ones, so "é" and "٣" count too. Punctuation is the
+ascii set plus the Latin-1, general and CJK punctuation blocks; symbols, emoji, control characters and invalid utf8
+(one U+FFFD per invalid sequence) are other...
+*/
+use crate::utf8::Utf8Decoder;
+
+// the order the columns are printed in
+pub const LETTERS: usize = 0;
+pub const DIGITS: usize = 1;
+pub const PUNCTUATION: usize = 2;
+pub const WHITESPACE: usize = 3;
+pub const OTHER: usize = 4;
+
+pub struct CharClasses {
+    decoder: Utf8Decoder,
+    counts: [u64; 5],
+}
+
+impl CharClasses {
+    pub fn new() -> Self {
+        Self { decoder: Utf8Decoder::new(), counts: [0; 5] }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let counts = &mut self.counts;
+        self.decoder.decode(bytes, |c| counts[class_of(c.unwrap_or(char::REPLACEMENT_CHARACTER))] += 1);
+    }
+
+    pub fn feed_char(&mut self, c: char) {
+        self.counts[class_of(c)] += 1;
+    }
+
+    // see StreamCounter::drop_partial_char
+    pub fn drop_partial_char(&mut self) {
+        self.decoder.drop_partial_char();
+    }
+
+    pub fn finish(mut self) -> [u64; 5] {
+        let counts = &mut self.counts;
+        self.decoder.finish(|c| counts[class_of(c.unwrap_or(char::REPLACEMENT_CHARACTER))] += 1);
+        self.counts
+    }
+}
+
+fn class_of(c: char) -> usize {
+    if c.is_alphabetic() {
+        LETTERS
+    } else if c.is_numeric() {
+        DIGITS
+    } else if c.is_whitespace() {
+        WHITESPACE
+    } else if is_punctuation(c) {
+        PUNCTUATION
+    } else {
+        OTHER
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    match c {
+        '¡' | '§' | '«' | '¶' | '·' | '»' | '¿' => true,
+        '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205E}' => true, // general punctuation, minus the spaces and controls
+        '\u{3001}'..='\u{3003}' | '\u{3008}'..='\u{3011}' | '\u{3014}'..='\u{301F}' => true, // CJK
+        _ => c.is_ascii_punctuation(),
+    }
+}
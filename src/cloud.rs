@@ -0,0 +1,40 @@
+/*
+s3://bucket/key and gs://bucket/object operands (with the cloud feature), for checking exports where they landed
+instead of copying them down first. The objects are streamed through the aws and gcloud CLIs (aws s3 cp URI - and
+gcloud storage cat URI), so credentials, profiles, regions and endpoints come from wherever those already look:
+AWS_PROFILE, AWS_ACCESS_KEY_ID, AWS_ENDPOINT_URL and ~/.aws for S3, GOOGLE_APPLICATION_CREDENTIALS and gcloud auth for
+GCS, instance metadata for both. A URI names one object, prefixes aren't listed...
+
+    wc_clone -l s3://exports/2026-10-14/orders.csv gs://warehouse/events.jsonl
+*/
+use std::io;
+
+use crate::{CommandOptions, ReadResult};
+
+pub fn is_uri(operand: &str) -> bool {
+    operand.starts_with("s3://") || operand.starts_with("gs://")
+}
+
+#[cfg(feature = "cloud")]
+pub fn read_uri(uri: &str, command_options: &CommandOptions) -> ReadResult {
+    use std::process::Command;
+
+    use crate::i18n::tr;
+    use crate::{child, decompress, stream};
+
+    let (program, args) = match uri.starts_with("s3://") {
+        true => ("aws", vec!["s3", "cp", "--quiet", uri, "-"]),
+        false => ("gcloud", vec!["storage", "cat", uri]),
+    };
+    let mut command = Command::new(program);
+    command.args(args);
+
+    let body = child::spawn(command, command_options).map_err(|err| io::Error::new(err.kind(), tr!("couldn't run the {} CLI: {}", program, err)));
+    let body = body.and_then(|body| decompress::wrap(body, command_options));
+    crate::read_result(body.and_then(|body| stream::count_reader(body, command_options, uri)))
+}
+
+#[cfg(not(feature = "cloud"))]
+pub fn read_uri(_uri: &str, _command_options: &CommandOptions) -> ReadResult {
+    ReadResult::ReadError(Box::new(io::Error::new(io::ErrorKind::Unsupported, "wc_clone was built without the cloud feature")))
+}
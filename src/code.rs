@@ -0,0 +1,172 @@
+/*
+--code splits lines into blank, comment and code, picking the comment syntax from the file extension the way tokei and
+cloc do. A line with any code on it is code even if it ends in a comment. It doesn't know about strings, so a "//"
+inside a string literal starts a comment as far as it's concerned, close enough for a quick breakdown...
+*/
+use std::path::Path;
+
+pub struct Syntax {
+    line: &'static [&'static str],
+    block: &'static [(&'static str, &'static str)],
+}
+
+const C_LIKE: Syntax = Syntax { line: &["//"], block: &[("/*", "*/")] };
+const HASH: Syntax = Syntax { line: &["#"], block: &[] };
+const DASHES: Syntax = Syntax { line: &["--"], block: &[] };
+const LUA: Syntax = Syntax { line: &["--"], block: &[("--[[", "]]")] };
+const HASKELL: Syntax = Syntax { line: &["--"], block: &[("{-", "-}")] };
+const MARKUP: Syntax = Syntax { line: &[], block: &[("<!--", "-->")] };
+const CSS: Syntax = Syntax { line: &[], block: &[("/*", "*/")] };
+const SEMICOLON: Syntax = Syntax { line: &[";"], block: &[] };
+const PERCENT: Syntax = Syntax { line: &["%"], block: &[] };
+const INI: Syntax = Syntax { line: &[";", "#"], block: &[] };
+const FORTRAN: Syntax = Syntax { line: &["!"], block: &[] };
+const VIM: Syntax = Syntax { line: &["\""], block: &[] };
+pub const PLAIN_TEXT: Syntax = Syntax { line: &[], block: &[] }; // every non-blank line is code
+
+pub fn syntax_for(path: &str) -> &'static Syntax {
+    let name = Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or("");
+    if matches!(name, "Makefile" | "makefile" | "GNUmakefile" | "Dockerfile" | "CMakeLists.txt") {
+        return &HASH;
+    }
+
+    let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).unwrap_or("");
+    match extension.to_ascii_lowercase().as_str() {
+        "rs" | "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "java" | "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx"
+        | "go" | "swift" | "kt" | "kts" | "scala" | "cs" | "dart" | "zig" | "proto" | "php" | "scss" | "less" => &C_LIKE,
+        "py" | "sh" | "bash" | "zsh" | "fish" | "rb" | "pl" | "pm" | "r" | "yaml" | "yml" | "toml" | "mk" | "cmake"
+        | "nix" | "ps1" | "tf" | "jl" | "ex" | "exs" => &HASH,
+        "sql" | "ada" | "elm" => &DASHES,
+        "lua" => &LUA,
+        "hs" => &HASKELL,
+        "html" | "htm" | "xml" | "xhtml" | "svg" | "vue" => &MARKUP,
+        "css" => &CSS,
+        "lisp" | "clj" | "cljs" | "el" | "scm" | "asm" | "s" => &SEMICOLON,
+        "tex" | "erl" | "m" => &PERCENT,
+        "ini" | "cfg" | "conf" => &INI,
+        "f90" | "f95" | "f03" => &FORTRAN,
+        "vim" => &VIM,
+        _ => &PLAIN_TEXT,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Blank,
+    Comment,
+    Code,
+}
+
+/*
+Fed the input chunk by chunk, a line cut off at the end of one chunk waits for the rest of it. Block comments carry over
+from line to line.
+*/
+pub struct LineClassifier {
+    syntax: &'static Syntax,
+    line: Vec<u8>,
+    block_end: Option<&'static str>, // set while inside a block comment
+    blank: u64,
+    comment: u64,
+    code: u64,
+}
+
+impl LineClassifier {
+    pub fn new(syntax: &'static Syntax) -> Self {
+        Self {
+            syntax,
+            line: Vec::new(),
+            block_end: None,
+            blank: 0,
+            comment: 0,
+            code: 0,
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut rest = bytes;
+        while let Some(newline) = rest.iter().position(|byte| *byte == b'\n') {
+            self.line.extend_from_slice(&rest[..newline]);
+            self.end_line();
+            rest = &rest[newline + 1..];
+        }
+        self.line.extend_from_slice(rest);
+    }
+
+    // a last line without a newline still counts
+    pub fn finish(mut self) -> (u64, u64, u64) {
+        if !self.line.is_empty() {
+            self.end_line();
+        }
+        (self.blank, self.comment, self.code)
+    }
+
+    fn end_line(&mut self) {
+        let line = String::from_utf8_lossy(&self.line).into_owned();
+        match self.classify(&line) {
+            Kind::Blank => self.blank += 1,
+            Kind::Comment => self.comment += 1,
+            Kind::Code => self.code += 1,
+        }
+        self.line.clear();
+    }
+
+    fn classify(&mut self, line: &str) -> Kind {
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            return Kind::Blank;
+        }
+
+        let mut has_code = false;
+        let mut has_comment = false;
+        loop {
+            if let Some(end) = self.block_end {
+                has_comment = true;
+                match rest.find(end) {
+                    Some(i) => {
+                        rest = &rest[i + end.len()..];
+                        self.block_end = None;
+                    },
+                    None => break,
+                }
+            }
+
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            // block starts first, "--[[" would otherwise look like a "--" line comment
+            if let Some((start, end)) = self.syntax.block.iter().find(|(start, _)| rest.starts_with(start)) {
+                has_comment = true;
+                rest = &rest[start.len()..];
+                self.block_end = Some(end);
+                continue;
+            }
+            if self.syntax.line.iter().any(|start| rest.starts_with(start)) {
+                has_comment = true;
+                break;
+            }
+
+            // code up to wherever the next comment starts, if one does
+            has_code = true;
+            let next_comment = self
+                .syntax
+                .line
+                .iter()
+                .chain(self.syntax.block.iter().map(|(start, _)| start))
+                .filter_map(|start| rest.find(start))
+                .min();
+            match next_comment {
+                Some(i) => rest = &rest[i..],
+                None => break,
+            }
+        }
+
+        if has_code {
+            Kind::Code
+        } else if has_comment {
+            Kind::Comment
+        } else {
+            Kind::Blank
+        }
+    }
+}
@@ -0,0 +1,100 @@
+/*
+--output-format=parquet and --output-format=arrow (Arrow IPC, the .arrow file format) with the arrow feature, for
+loading the counts of a lot of files straight into DuckDB, Polars or pandas. There's a row for each row of the text
+output, a file column (null for stdin) and then a column for each count under the names snapshots use. Counts are
+64 bit integers, --entropy and --compress-ratio are doubles and anything that isn't a number (a hash, an estimate
+with its margin) is a string. They're binary, so they only go to --output...
+
+    wc_clone -r --output-format=parquet --output=counts.parquet src/
+    duckdb -c "SELECT file, lines FROM 'counts.parquet' ORDER BY lines DESC LIMIT 10"
+*/
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+
+use crate::{snapshot, CommandOptions, FileStats, Reporter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Columnar {
+    Parquet,
+    Ipc,
+}
+
+// everything has to be in before the columns can be built, so the rows are kept as the text would have them
+#[derive(Debug, Clone)]
+pub struct ColumnarReporter {
+    format: Columnar,
+    columns: Vec<String>,
+    rows: Vec<(Option<String>, Vec<String>)>,
+}
+
+impl ColumnarReporter {
+    pub fn new(format: Columnar) -> Self {
+        Self { format, columns: Vec::new(), rows: Vec::new() }
+    }
+
+    fn batch(&self) -> Result<RecordBatch, arrow_schema::ArrowError> {
+        let files: StringArray = self.rows.iter().map(|(file, _)| file.as_deref()).collect();
+        let mut fields = vec![Field::new("file", DataType::Utf8, true)];
+        let mut arrays: Vec<ArrayRef> = vec![Arc::new(files)];
+
+        for (index, name) in self.columns.iter().enumerate() {
+            let values: Vec<&str> = self.rows.iter().map(|(_, counts)| counts[index].as_str()).collect();
+            let (data_type, array) = column(&values);
+            fields.push(Field::new(name, data_type, true));
+            arrays.push(array);
+        }
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+    }
+}
+
+impl Reporter for ColumnarReporter {
+    fn row(&mut self, _out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        if self.columns.is_empty() {
+            self.columns = snapshot::column_keys(options);
+        }
+        let file = (!topic.is_empty()).then(|| topic.to_string());
+        self.rows.push((file, crate::selected_counts(options, stats)));
+        Ok(())
+    }
+
+    fn message(&mut self, _out: &mut impl Write, text: &str) -> io::Result<()> {
+        writeln!(io::stderr(), "{}", text)
+    }
+
+    fn finish(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let batch = self.batch().map_err(io::Error::other)?;
+        match self.format {
+            Columnar::Parquet => {
+                // the parquet writer wants to own something Send, the whole file is small next to what was counted
+                let mut buffer = Vec::new();
+                let mut writer = parquet::arrow::ArrowWriter::try_new(&mut buffer, batch.schema(), None).map_err(io::Error::other)?;
+                writer.write(&batch).map_err(io::Error::other)?;
+                writer.close().map_err(io::Error::other)?;
+                out.write_all(&buffer)
+            },
+            Columnar::Ipc => {
+                let mut writer = arrow_ipc::writer::FileWriter::try_new(out, &batch.schema()).map_err(io::Error::other)?;
+                writer.write(&batch).map_err(io::Error::other)?;
+                writer.finish().map_err(io::Error::other)
+            },
+        }
+    }
+}
+
+// the narrowest type every value of the column fits, "-" (nothing to show) being null
+fn column(values: &[&str]) -> (DataType, ArrayRef) {
+    let present = || values.iter().filter(|value| **value != "-");
+    if present().all(|value| value.parse::<i64>().is_ok()) {
+        let array: Int64Array = values.iter().map(|value| value.parse().ok()).collect();
+        (DataType::Int64, Arc::new(array))
+    } else if present().all(|value| value.parse::<f64>().is_ok()) {
+        let array: Float64Array = values.iter().map(|value| value.parse().ok()).collect();
+        (DataType::Float64, Arc::new(array))
+    } else {
+        let array: StringArray = values.iter().map(|value| (*value != "-").then_some(*value)).collect();
+        (DataType::Utf8, Arc::new(array))
+    }
+}
@@ -0,0 +1,145 @@
+/*
+--compat=gnu|bsd makes the output byte for byte what that wc prints, so wc_clone can be dropped into test suites that
+compare against the platform wc: column widths, when a total line shows up and what errors look like...
+*/
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+
+use crate::stream::Separators;
+use crate::utf8::Utf8Decoder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compat {
+    Native,
+    Gnu, // coreutils
+    Bsd, // FreeBSD and macOS
+}
+
+impl Compat {
+    pub fn parse(value: &str) -> Result<Compat, String> {
+        match value {
+            "gnu" => Ok(Compat::Gnu),
+            "bsd" => Ok(Compat::Bsd),
+            _ => Err(format!("invalid value for '--compat': '{}' (expected gnu or bsd)", value)),
+        }
+    }
+}
+
+/*
+GNU pads every column to the digits of the combined size of the inputs, or at least 7 when one of them isn't a regular
+file since there's no size to go by. A single number for a single input isn't padded at all. Inputs that can't be
+stat'ed don't count towards the width, and if the first one can't GNU gives up on padding altogether. "-" is stdin.
+*/
+pub fn gnu_width(inputs: &[&str], counters: usize) -> usize {
+    if (inputs.len() == 1 && counters == 1) || inputs.first().is_some_and(|first| metadata(first).is_err()) {
+        return 1;
+    }
+
+    let mut minimum_width = 1;
+    let mut regular_total: u64 = 0;
+    for input in inputs {
+        match metadata(input) {
+            Ok(metadata) if metadata.is_file() => regular_total += metadata.len(),
+            Ok(_) => minimum_width = 7,
+            Err(_) => {},
+        }
+    }
+
+    regular_total.to_string().len().max(minimum_width)
+}
+
+fn metadata(input: &str) -> io::Result<fs::Metadata> {
+    fs::metadata(if input == "-" { "/dev/stdin" } else { input })
+}
+
+pub fn format_gnu(counts: &[String], topic: &str, width: usize) -> String {
+    let mut results: Vec<String> = counts.iter().map(|count| format!("{:>width$}", count, width = width)).collect();
+    if !topic.is_empty() {
+        results.push(topic.to_string());
+    }
+    results.join(" ")
+}
+
+// BSD just gives every count eight columns including the space in front
+pub fn format_bsd(counts: &[String], topic: &str) -> String {
+    let mut results = String::new();
+    for count in counts {
+        results.push_str(&format!(" {:>7}", count));
+    }
+    if !topic.is_empty() {
+        results.push_str(&format!(" {}", topic));
+    }
+    results
+}
+
+// both print "wc: FILE: reason" with the bare strerror text, io::Error adds " (os error N)" that has to go
+pub fn error_message(path: &str, err: &(dyn Error + Send + Sync)) -> String {
+    let reason = err.to_string();
+    let reason = match reason.find(" (os error ") {
+        Some(end) => &reason[..end],
+        None => &reason,
+    };
+    format!("wc: {}: {}", path, reason)
+}
+
+// GNU still prints a row of zeros for a directory after complaining about it
+pub fn is_directory_error(err: &(dyn Error + Send + Sync + 'static)) -> bool {
+    err.downcast_ref::<io::Error>().is_some_and(|err| err.kind() == io::ErrorKind::IsADirectory)
+}
+
+/*
+The words and characters GNU wc counts in a UTF-8 locale. It decodes with mbrtowc() and skips a byte that doesn't
+decode, as neither a character nor anything that starts or ends a word: "caf\xe9 \xff ok" is 2 words of 7 characters
+to it, where the native count is 3 words of 9. The same goes for what iswprint() says isn't printable, control
+characters and the line and paragraph separators, and words also end at the characters iswspace() has beyond ascii
+and, unless POSIXLY_CORRECT is set, at the no-break spaces. Lines are the \n bytes either way.
+
+What's still different: glibc decodes the old 5 and 6 byte forms and code points past U+10FFFF, which are invalid
+here, and code points Unicode hasn't assigned yet aren't printable to it but start words here...
+*/
+pub struct GnuCounter {
+    decoder: Utf8Decoder,
+    separators: &'static Separators,
+    no_break_spaces: bool, // whether they end words
+    in_word: bool,
+    words: u64,
+    chars: u64,
+}
+
+impl GnuCounter {
+    pub fn new(separators: &'static Separators) -> Self {
+        let no_break_spaces = env::var_os("POSIXLY_CORRECT").is_none();
+        Self { decoder: Utf8Decoder::new(), separators, no_break_spaces, in_word: false, words: 0, chars: 0 }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        let Self { decoder, separators, no_break_spaces, in_word, words, chars } = self;
+        decoder.decode(chunk, |c| count(c, separators, *no_break_spaces, in_word, words, chars));
+    }
+
+    // the words and the characters
+    pub fn finish(mut self) -> (u64, u64) {
+        let Self { decoder, separators, no_break_spaces, in_word, words, chars } = &mut self;
+        decoder.finish(|c| count(c, separators, *no_break_spaces, in_word, words, chars));
+        (self.words + u64::from(self.in_word), self.chars)
+    }
+}
+
+// a character at a time, None being a sequence that didn't decode
+fn count(c: Option<char>, separators: &Separators, no_break_spaces: bool, in_word: &mut bool, words: &mut u64, chars: &mut u64) {
+    let Some(c) = c else { return };
+    *chars += 1;
+    let space = match c {
+        '\u{1680}' | '\u{2000}'..='\u{2006}' | '\u{2008}'..='\u{200a}' | '\u{205f}' | '\u{3000}' => true,
+        '\u{a0}' | '\u{2007}' | '\u{202f}' | '\u{2060}' => no_break_spaces,
+        _ => separators.ends_word(c),
+    };
+    if space {
+        *words += u64::from(*in_word);
+        *in_word = false;
+    } else if !c.is_control() && !matches!(c, '\u{2028}' | '\u{2029}') {
+        *in_word = true;
+    }
+}
@@ -0,0 +1,88 @@
+/*
+Counters from outside the crate. A Counter is fed every chunk of an input (the whole text, for documents), gets
+merged into the total with the other inputs' and reports its column. They're registered on the CommandOptions with a
+name for the column and a function making a fresh one for each input, and come after the built in columns:
+
+    options.register_counter("semicolons", || Box::new(Semicolons::default()));
+
+Chunks are whatever size the reader read, so anything spanning two of them (a line, a word, a utf8 character) is up
+to the counter to carry over...
+*/
+use std::any::Any;
+use std::fmt;
+
+pub trait Counter: CloneCounter + fmt::Debug + Send + Any {
+    fn feed(&mut self, chunk: &[u8]);
+
+    // adds another input's counts for the total. other is always the same type as self, and
+    // (other as &dyn Any).downcast_ref::<Self>() gets it back as one
+    fn merge(&mut self, other: &dyn Counter);
+
+    // the column's value for this input (or the total)
+    fn report(&self) -> String;
+}
+
+// FileStats are Clone, so the counters in them have to be. It comes for free with #[derive(Clone)]
+pub trait CloneCounter {
+    fn clone_counter(&self) -> Box<dyn Counter>;
+}
+
+impl<T: Counter + Clone> CloneCounter for T {
+    fn clone_counter(&self) -> Box<dyn Counter> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Counter> {
+    fn clone(&self) -> Self {
+        self.clone_counter()
+    }
+}
+
+type Factory = Box<dyn Fn() -> Box<dyn Counter> + Send + Sync>;
+
+// the registered counters, in column order
+#[derive(Default)]
+pub struct Registry {
+    counters: Vec<(String, Factory)>,
+}
+
+impl Registry {
+    pub fn register(&mut self, name: String, factory: Factory) {
+        self.counters.push((name, factory));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.counters.iter().map(|(name, _)| name.as_str())
+    }
+
+    // one of each for a new input
+    pub fn start(&self) -> Vec<Box<dyn Counter>> {
+        self.counters.iter().map(|(_, factory)| factory()).collect()
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+// the total's counters start out as copies of the first input's
+pub fn merge_all(total: &mut Vec<Box<dyn Counter>>, other: &[Box<dyn Counter>]) {
+    if total.is_empty() {
+        total.extend(other.iter().cloned());
+        return;
+    }
+    for (counter, other_counter) in total.iter_mut().zip(other) {
+        counter.merge(other_counter.as_ref());
+    }
+}
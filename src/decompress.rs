@@ -0,0 +1,141 @@
+/*
+Compressed streams are counted as what they decompress to, so cat logs.gz | wc_clone -l counts the log's lines and not
+the newlines that happen to be in the gzip data. The first bytes of stdin, of FIFOs and devices and of remote inputs
+(URLs, s3://, gs://, ssh) are checked for the gzip, zstd and xz magic numbers and put back in front of the rest. gzip is
+decoded here with the compression feature, zstd and xz (and gzip without that feature) go through the zstd, xz and
+gzip programs, which then have to be installed.
+
+A stream can be several of them back to back, the way pigz, pzstd and cat a.gz b.gz write them, and every member or
+frame is counted, not just the first. After the last gzip member only zeros (the padding tapes and some tools add) are
+allowed, anything else is an error instead of quietly left out. pzstd starts with a skippable frame, which counts as
+zstd's magic too.
+
+Regular files are counted as the bytes they are, like every wc does, and so is everything with --no-decompress,
+--posix or --compat, which promise the counts of the bytes that were read...
+*/
+use std::io::{self, Cursor, Read};
+#[cfg(feature = "compression")]
+use std::io::{BufRead, BufReader};
+use std::process::Command;
+
+use crate::compat::Compat;
+use crate::i18n::tr;
+use crate::{child, stream, CommandOptions};
+
+// long enough for the longest of the magic numbers, xz's
+const SNIFF_LEN: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Format {
+    fn sniff(start: &[u8]) -> Option<Format> {
+        match start {
+            [0x1f, 0x8b, ..] => Some(Format::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] | [0x50..=0x5f, 0x2a, 0x4d, 0x18, ..] => Some(Format::Zstd), // a frame, or a skippable one
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Format::Xz),
+            _ => None,
+        }
+    }
+
+    fn program(self) -> &'static str {
+        match self {
+            Format::Gzip => "gzip",
+            Format::Zstd => "zstd",
+            Format::Xz => "xz",
+        }
+    }
+}
+
+// for --auto, which looks at the start of regular files too
+pub fn is_compressed(start: &[u8]) -> bool {
+    Format::sniff(start).is_some()
+}
+
+// the stream to count in place of reader, decompressed if it turns out to be compressed
+pub fn wrap(mut reader: impl Read + Send + 'static, command_options: &CommandOptions) -> io::Result<Box<dyn Read + Send>> {
+    if command_options.no_decompress || command_options.posix || command_options.compat != Compat::Native {
+        return Ok(Box::new(reader));
+    }
+
+    let start = stream::read_start(&mut reader, SNIFF_LEN)?;
+    let format = Format::sniff(&start);
+    let stream: Box<dyn Read + Send> = Box::new(Cursor::new(start).chain(reader));
+    match format {
+        None => Ok(stream),
+        Some(format) => decoder(format, stream, command_options),
+    }
+}
+
+#[cfg(feature = "compression")]
+fn decoder(format: Format, stream: Box<dyn Read + Send>, command_options: &CommandOptions) -> io::Result<Box<dyn Read + Send>> {
+    match format {
+        Format::Gzip => Ok(Box::new(GzipMembers { decoder: Some(flate2::bufread::GzDecoder::new(BufReader::new(stream))) })),
+        _ => program(format, stream, command_options),
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+fn decoder(format: Format, stream: Box<dyn Read + Send>, command_options: &CommandOptions) -> io::Result<Box<dyn Read + Send>> {
+    program(format, stream, command_options)
+}
+
+fn program(format: Format, stream: Box<dyn Read + Send>, command_options: &CommandOptions) -> io::Result<Box<dyn Read + Send>> {
+    let mut command = Command::new(format.program());
+    command.args(["-d", "-c"]);
+    match child::filter(command, stream, command_options) {
+        Ok(output) => Ok(Box::new(output)),
+        Err(err) => Err(io::Error::new(
+            err.kind(),
+            tr!("the input is {} compressed and {} couldn't be run to decompress it (--no-decompress counts it as it is): {}", format.program(), format.program(), err),
+        )),
+    }
+}
+
+// every member of a gzip stream, one after the other
+#[cfg(feature = "compression")]
+struct GzipMembers<R> {
+    decoder: Option<flate2::bufread::GzDecoder<R>>, // None once the last one is done
+}
+
+#[cfg(feature = "compression")]
+impl<R: BufRead> Read for GzipMembers<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let Some(decoder) = &mut self.decoder else { return Ok(0) };
+            let read = decoder.read(buffer)?;
+            if read > 0 || buffer.is_empty() {
+                return Ok(read);
+            }
+
+            // the member ended, what comes after it is left in the BufReader
+            let Some(mut rest) = self.decoder.take().map(|decoder| decoder.into_inner()) else { return Ok(0) };
+            if next_member(&mut rest)? {
+                self.decoder = Some(flate2::bufread::GzDecoder::new(rest));
+            }
+        }
+    }
+}
+
+// whether another member follows, rather than the end (or zeros up to it)
+#[cfg(feature = "compression")]
+fn next_member(rest: &mut impl BufRead) -> io::Result<bool> {
+    let mut padding = false;
+    loop {
+        let available = rest.fill_buf()?;
+        match available.iter().position(|&byte| byte != 0) {
+            _ if available.is_empty() => return Ok(false),
+            Some(0) if !padding && available[0] == 0x1f => return Ok(true),
+            Some(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("trailing garbage after the gzip data"))),
+            None => {
+                let len = available.len();
+                rest.consume(len);
+                padding = true;
+            },
+        }
+    }
+}
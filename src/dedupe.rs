@@ -0,0 +1,95 @@
+/*
+--dedupe=inode|hash, for trees where the same file shows up more than once: snapshot and backup directories are full of
+hard links, and copies of the same file under different names are everywhere. Every input still gets its row but only
+the first of each set goes into the totals. inode only catches hard links (and the same file named twice), hash reads
+the files and also catches copies, only hashing files that share their size with another one (and needs the hashes
+feature)...
+*/
+#[cfg(feature = "hashes")]
+use std::collections::HashMap;
+use std::fs;
+
+#[cfg(feature = "hashes")]
+use crate::hash;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Inode,
+    #[cfg(feature = "hashes")]
+    Hash,
+}
+
+impl Mode {
+    pub fn parse(value: &str) -> Result<Mode, String> {
+        match value {
+            "inode" => Ok(Mode::Inode),
+            #[cfg(feature = "hashes")]
+            "hash" => Ok(Mode::Hash),
+            #[cfg(not(feature = "hashes"))]
+            "hash" => Err(String::from("can't use '--dedupe=hash': wc_clone was built without the hashes feature")),
+            _ => Err(format!("invalid value for '--dedupe': '{}' (expected inode or hash)", value)),
+        }
+    }
+
+    // what a duplicate row says about the input it duplicates
+    pub fn annotation(&self, original: &str) -> String {
+        match self {
+            Mode::Inode => format!("(same file as {})", original), // a hard link, or just named twice
+            #[cfg(feature = "hashes")]
+            Mode::Hash => format!("(same content as {})", original),
+        }
+    }
+}
+
+// inputs with the same key are the same file, or the same bytes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    #[cfg(unix)]
+    Inode(u64, u64),
+    #[cfg(not(unix))]
+    Path(std::path::PathBuf),
+    #[cfg(feature = "hashes")]
+    Content(u64, String), // the size and a sha256 of the content
+}
+
+/*
+A key for every input that could have a duplicate. Only regular files get one: reading a pipe here would leave nothing
+for the count, and anything we can't stat or read is left for the normal read to report.
+*/
+pub fn keys(files: &[String], mode: Mode) -> Vec<Option<Key>> {
+    let sizes: Vec<Option<u64>> = files
+        .iter()
+        .map(|file| fs::metadata(file).ok().filter(|metadata| metadata.is_file()).map(|metadata| metadata.len()))
+        .collect();
+
+    match mode {
+        Mode::Inode => files.iter().zip(&sizes).map(|(file, size)| size.and_then(|_| inode_key(file))).collect(),
+        #[cfg(feature = "hashes")]
+        Mode::Hash => {
+            let mut same_size: HashMap<u64, usize> = HashMap::new();
+            for size in sizes.iter().flatten() {
+                *same_size.entry(*size).or_insert(0) += 1;
+            }
+            files
+                .iter()
+                .zip(&sizes)
+                .map(|(file, size)| match size {
+                    Some(size) if same_size[size] > 1 => hash::content_digest(file).ok().map(|hash| Key::Content(*size, hash)),
+                    _ => None,
+                })
+                .collect()
+        },
+    }
+}
+
+#[cfg(unix)]
+fn inode_key(file: &str) -> Option<Key> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(file).ok().map(|metadata| Key::Inode(metadata.dev(), metadata.ino()))
+}
+
+// no inodes to go by, so only the same file named twice is caught
+#[cfg(not(unix))]
+fn inode_key(file: &str) -> Option<Key> {
+    fs::canonicalize(file).ok().map(Key::Path)
+}
@@ -0,0 +1,163 @@
+/*
+--diff-input reads a unified diff (git diff, diff -u) instead of plain text and counts what it changes: lines and words
+added and removed per file, plus how many files it touches, a diffstat with word counts. Hunk headers are followed so
+a removed line that happens to start with "--" isn't taken for the next file's header...
+
+    git diff main | wc_clone --diff-input
+*/
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+
+use crate::i18n::tr;
+use crate::stream::Separators;
+use crate::CommandOptions;
+
+#[derive(Debug, Default)]
+struct FileDiff {
+    file: String,
+    added_lines: u64,
+    removed_lines: u64,
+    added_words: u64,
+    removed_words: u64,
+}
+
+impl FileDiff {
+    fn new(file: &str) -> Self {
+        FileDiff { file: file.to_string(), ..FileDiff::default() }
+    }
+
+    fn add(&mut self, other: &FileDiff) {
+        self.added_lines += other.added_lines;
+        self.removed_lines += other.removed_lines;
+        self.added_words += other.added_words;
+        self.removed_words += other.removed_words;
+    }
+}
+
+// the operands are diffs, stdin when there aren't any
+pub fn run(command_options: CommandOptions) {
+    let mut files: Vec<FileDiff> = Vec::new();
+
+    if command_options.files.is_empty() {
+        if io::stdin().lock().is_terminal() {
+            println!("{}", tr!("No files spcified..."));
+            return;
+        }
+        let mut diff = Vec::new();
+        match io::stdin().read_to_end(&mut diff) {
+            Ok(_) => files.append(&mut parse(&diff, command_options.separators())),
+            Err(err) => println!("{}", tr!("Encounted error reading stdin: {}", err)),
+        }
+    }
+    for path in &command_options.files {
+        match fs::read(path) {
+            Ok(diff) => files.append(&mut parse(&diff, command_options.separators())),
+            Err(err) => println!("{}", tr!("Encounted error reading file {}: {}", path, err)),
+        }
+    }
+
+    let mut total = FileDiff::new("total");
+    for file in &files {
+        total.add(file);
+        print_row(file, &file.file);
+    }
+    let touched = format!("total ({} file{})", files.len(), if files.len() == 1 { "" } else { "s" });
+    print_row(&total, &touched);
+}
+
+fn print_row(diff: &FileDiff, topic: &str) {
+    println!(
+        " +{} -{} +{} -{} {}",
+        diff.added_lines, diff.removed_lines, diff.added_words, diff.removed_words, topic
+    );
+}
+
+fn parse(diff: &[u8], separators: &Separators) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut old_name = String::new();
+    let mut has_header = false; // a "diff --git" line started the last file and its ---/+++ haven't come yet
+    let mut old_left = 0; // lines of the current hunk still to come on each side
+    let mut new_left = 0;
+
+    for line in diff.split(|byte| *byte == b'\n') {
+        if old_left > 0 || new_left > 0 {
+            let Some(current) = files.last_mut() else { break };
+            match line.first() {
+                Some(b'+') => {
+                    current.added_lines += 1;
+                    current.added_words += count_words(&line[1..], separators);
+                    new_left -= 1;
+                    continue;
+                },
+                Some(b'-') => {
+                    current.removed_lines += 1;
+                    current.removed_words += count_words(&line[1..], separators);
+                    old_left -= 1;
+                    continue;
+                },
+                // context, some tools drop the space from empty context lines
+                Some(b' ') | None => {
+                    old_left -= 1;
+                    new_left -= 1;
+                    continue;
+                },
+                Some(b'\\') => continue, // "\ No newline at end of file"
+                Some(_) => {
+                    // the hunk header promised more than came, carry on as if it had ended
+                    old_left = 0;
+                    new_left = 0;
+                },
+            }
+        }
+
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches('\r');
+        if let Some(names) = line.strip_prefix("diff --git ") {
+            // a/old b/new, the new name is good enough until +++ says otherwise
+            let name = names.rsplit_once(" b/").map_or(names, |(_, new)| new);
+            files.push(FileDiff::new(name));
+            has_header = true;
+        } else if let Some(name) = line.strip_prefix("--- ") {
+            old_name = diff_name(name);
+        } else if let Some(name) = line.strip_prefix("+++ ") {
+            let name = match diff_name(name) {
+                name if name == "/dev/null" => old_name.clone(), // deleted
+                name => name,
+            };
+            match files.last_mut() {
+                Some(current) if has_header => current.file = name,
+                _ => files.push(FileDiff::new(&name)),
+            }
+            has_header = false;
+        } else if let Some(ranges) = line.strip_prefix("@@ ") {
+            if files.is_empty() {
+                files.push(FileDiff::new("(unknown)"));
+            }
+            let mut ranges = ranges.split_whitespace();
+            old_left = ranges.next().and_then(|range| range_len(range, '-')).unwrap_or(0);
+            new_left = ranges.next().and_then(|range| range_len(range, '+')).unwrap_or(0);
+        }
+    }
+
+    files
+}
+
+// "a/src/lib.rs\t2024-01-01 ..." -> "src/lib.rs"
+fn diff_name(name: &str) -> String {
+    let name = name.split('\t').next().unwrap_or(name);
+    let name = name.strip_prefix("a/").or_else(|| name.strip_prefix("b/")).unwrap_or(name);
+    name.to_string()
+}
+
+// "-12,5" is 5 lines, "-12" just 1
+fn range_len(range: &str, side: char) -> Option<u64> {
+    let range = range.strip_prefix(side)?;
+    match range.split_once(',') {
+        Some((_, len)) => len.parse().ok(),
+        None => Some(1),
+    }
+}
+
+fn count_words(line: &[u8], separators: &Separators) -> u64 {
+    line.split(|byte| separators.contains(*byte)).map(|word| separators.split_marks(word).len() as u64).sum()
+}
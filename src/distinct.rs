@@ -0,0 +1,307 @@
+/*
+--unique-lines, --duplicate-lines and --unique-words, what `sort -u | wc -l` and `sort | uniq -d | wc -l` give without
+the sorting or reading the input three times. Lines are compared byte for byte (a trailing \r makes a line different,
+same as uniq), words are split the same way the word count splits them.
+
+Every distinct item is kept in a hash set until they take up more than MAX_EXACT_BYTES, past that only a 64 bit hash
+of each is kept so huge inputs stay within memory. That's approximate in theory, two different lines would need to hash
+the same, and with 64 bits that doesn't happen below billions of lines. --approx swaps the set for a HyperLogLog sketch:
+16K of memory whatever the input, around 0.8% off, and sketches can be merged so the totals are distinct counts across
+all the inputs instead of a sum. Duplicates can't be told from a sketch, so --duplicate-lines keeps the set either way.
+
+With --max-memory the set gets a share of it, and an input with more distinct items than fit (even as hashes) has its
+set turned into a sketch with a warning, so it's --approx from there on. --duplicate-lines can't do that and only
+warns that it's going over...
+*/
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::i18n::tr;
+use crate::stream::Separators;
+
+// how much content is kept before switching over to hashes
+const MAX_EXACT_BYTES: usize = 64 * 1024 * 1024;
+
+// roughly what an entry costs on top of the item itself (the Vec, the flag and the table's slot), and a hashed one
+const ITEM_OVERHEAD: usize = 48;
+const HASHED_ENTRY: usize = 24;
+
+// 2^14 registers of a byte each, the standard error is 1.04 / sqrt(2^14)
+const PRECISION: u32 = 14;
+const REGISTERS: usize = 1 << PRECISION;
+
+// what one input came to
+pub struct Counts {
+    pub unique: u64,
+    pub duplicate: u64,
+    pub sketch: Option<Box<HyperLogLog>>, // with --approx, then unique is its estimate
+}
+
+pub struct Distinct {
+    separators: Option<&'static Separators>, // splits words when set, lines otherwise
+    exact: Option<ExactSet>,
+    sketch: Option<Box<HyperLogLog>>,
+    pending: Vec<u8>, // an item cut off at the end of a chunk
+    max_memory: Option<MaxMemory>,
+}
+
+struct MaxMemory {
+    bytes: usize,
+    duplicates: bool, // whether the set is there for --duplicate-lines, which a sketch can't stand in for
+    input: String,    // for the warning
+    warned: bool,
+}
+
+impl Distinct {
+    // exact is whether the set is needed (no --approx, or duplicates are counted), approx whether the sketch is
+    pub fn new(separators: Option<&'static Separators>, exact: bool, approx: bool) -> Self {
+        Self {
+            separators,
+            exact: exact.then(ExactSet::new),
+            sketch: approx.then(|| Box::new(HyperLogLog::new())),
+            pending: Vec::new(),
+            max_memory: None,
+        }
+    }
+
+    // input is what the warning calls the input once the set goes over bytes
+    pub fn with_max_memory(mut self, bytes: usize, duplicates: bool, input: &str) -> Self {
+        if let Some(exact) = self.exact.as_mut() {
+            exact.max_items_bytes = exact.max_items_bytes.min(bytes);
+        }
+        self.max_memory = Some(MaxMemory { bytes, duplicates, input: input.to_string(), warned: false });
+        self
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut rest = bytes;
+        while let Some(end) = rest.iter().position(|byte| self.ends_item(*byte)) {
+            if self.pending.is_empty() {
+                self.insert(&rest[..end]);
+            } else {
+                self.pending.extend_from_slice(&rest[..end]);
+                let item = std::mem::take(&mut self.pending);
+                self.insert(&item);
+            }
+            rest = &rest[end + 1..];
+        }
+        self.pending.extend_from_slice(rest);
+    }
+
+    // a last line without a newline still counts
+    pub fn finish(mut self) -> Counts {
+        if !self.pending.is_empty() {
+            let item = std::mem::take(&mut self.pending);
+            self.insert(&item);
+        }
+
+        let (unique, duplicate) = self.exact.map_or((0, 0), |exact| exact.counts());
+        match self.sketch {
+            Some(sketch) => Counts { unique: sketch.estimate(), duplicate, sketch: Some(sketch) },
+            None => Counts { unique, duplicate, sketch: None },
+        }
+    }
+
+    fn ends_item(&self, byte: u8) -> bool {
+        match self.separators {
+            Some(separators) => separators.contains(byte),
+            None => byte == b'\n',
+        }
+    }
+
+    fn insert(&mut self, item: &[u8]) {
+        if self.separators.is_some() && item.is_empty() {
+            return; // separators in a row, empty lines are still lines though
+        }
+        if let Some(separators) = self.separators.filter(|separators| separators.has_marks()) {
+            for word in separators.split_marks(item) {
+                self.insert_item(word);
+            }
+            return;
+        }
+        self.insert_item(item);
+    }
+
+    fn insert_item(&mut self, item: &[u8]) {
+        if let Some(exact) = self.exact.as_mut() {
+            exact.insert(item);
+        }
+        if let Some(sketch) = self.sketch.as_mut() {
+            sketch.insert(hash_item(item));
+        }
+        if let (Some(exact), Some(max_memory)) = (&self.exact, &self.max_memory) {
+            if exact.bytes() > max_memory.bytes && !max_memory.warned {
+                self.over_max_memory();
+            }
+        }
+    }
+
+    fn over_max_memory(&mut self) {
+        let option = if self.separators.is_some() { "--unique-words" } else { "--unique-lines" };
+        let Some(max_memory) = self.max_memory.as_mut() else {
+            return;
+        };
+        max_memory.warned = true;
+        if max_memory.duplicates {
+            eprintln!("{}", tr!("wc_clone: {}: --duplicate-lines can't be estimated, it's going over --max-memory", max_memory.input));
+            return;
+        }
+
+        eprintln!("{}", tr!("wc_clone: {}: {} went over --max-memory, it's estimated from here on", max_memory.input, option));
+        if let Some(exact) = self.exact.take() {
+            let sketch = self.sketch.get_or_insert_with(|| Box::new(HyperLogLog::new()));
+            exact.into_hashes(|hash| sketch.insert(hash));
+        }
+    }
+}
+
+// item -> whether it was seen more than once
+struct ExactSet {
+    items: Option<HashMap<Vec<u8>, bool>>,
+    items_bytes: usize,
+    max_items_bytes: usize,
+    hashed: HashMap<u64, bool>, // once the items themselves take too much room
+}
+
+impl ExactSet {
+    fn new() -> Self {
+        Self { items: Some(HashMap::new()), items_bytes: 0, max_items_bytes: MAX_EXACT_BYTES, hashed: HashMap::new() }
+    }
+
+    fn insert(&mut self, item: &[u8]) {
+        if let Some(items) = self.items.as_mut() {
+            match items.get_mut(item) {
+                Some(repeated) => *repeated = true,
+                None => {
+                    items.insert(item.to_vec(), false);
+                    self.items_bytes += item.len() + ITEM_OVERHEAD;
+                    if self.items_bytes > self.max_items_bytes {
+                        self.switch_to_hashes();
+                    }
+                },
+            }
+            return;
+        }
+
+        match self.hashed.entry(hash_item(item)) {
+            Entry::Occupied(mut seen) => *seen.get_mut() = true,
+            Entry::Vacant(new) => {
+                new.insert(false);
+            },
+        }
+    }
+
+    fn switch_to_hashes(&mut self) {
+        if let Some(items) = self.items.take() {
+            self.hashed = items.into_iter().map(|(item, repeated)| (hash_item(&item), repeated)).collect();
+        }
+    }
+
+    // about how much memory the set takes
+    fn bytes(&self) -> usize {
+        match &self.items {
+            Some(_) => self.items_bytes,
+            None => self.hashed.len() * HASHED_ENTRY,
+        }
+    }
+
+    fn into_hashes(self, mut each: impl FnMut(u64)) {
+        match self.items {
+            Some(items) => items.keys().for_each(|item| each(hash_item(item))),
+            None => self.hashed.into_keys().for_each(each),
+        }
+    }
+
+    // (distinct items, distinct items seen more than once)
+    fn counts(&self) -> (u64, u64) {
+        match &self.items {
+            Some(items) => (items.len() as u64, items.values().filter(|repeated| **repeated).count() as u64),
+            None => (self.hashed.len() as u64, self.hashed.values().filter(|repeated| **repeated).count() as u64),
+        }
+    }
+}
+
+fn hash_item(item: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/*
+The top PRECISION bits of a hash pick a register, and the register remembers the longest run of leading zeros seen in
+the rest. Long runs are rare, so the longer the runs the more distinct hashes there must have been.
+*/
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    // two standard errors, the estimate is within this fraction of the real count about 95% of the time
+    pub const ERROR: f64 = 2.0 * 1.04 / 128.0; // sqrt(REGISTERS) is 128
+
+    fn new() -> Self {
+        Self { registers: vec![0; REGISTERS] }
+    }
+
+    fn insert(&mut self, hash: u64) {
+        let register = (hash >> (64 - PRECISION)) as usize;
+        // the guard bit stops the run at the end of the remaining bits
+        let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let run = rest.leading_zeros() as u8 + 1;
+        self.registers[register] = self.registers[register].max(run);
+    }
+
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (register, other_register) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other_register);
+        }
+    }
+
+    // small counts leave most registers empty, and counting how many are empty is more accurate there
+    pub fn estimate(&self) -> u64 {
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|register| 2f64.powi(-(*register as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        let empty = self.registers.iter().filter(|register| **register == 0).count();
+        let estimate = if raw <= 2.5 * m && empty > 0 { m * (m / empty as f64).ln() } else { raw };
+        estimate.round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::DEFAULT_SEPARATORS;
+
+    fn unique_words(chunks: &[&[u8]], approx: bool) -> u64 {
+        let mut distinct = Distinct::new(Some(&DEFAULT_SEPARATORS), !approx, approx);
+        for chunk in chunks {
+            distinct.feed(chunk);
+        }
+        distinct.finish().unique
+    }
+
+    #[test]
+    fn unique_words_are_counted_exactly() {
+        assert_eq!(unique_words(&[b"the cat and the dog\n\tand  the cat"], false), 4);
+        // a word cut in two by the chunks is still one word
+        assert_eq!(unique_words(&[b"the cat and th", b"e dog\nca", b"t"], false), 4);
+        assert_eq!(unique_words(&[b" \n\t "], false), 0);
+    }
+
+    #[test]
+    fn the_approx_estimate_is_within_its_error() {
+        for vocabulary in [3_000, 60_000] {
+            // every word three times over, in an order that isn't the one they were made in
+            let text = (0..vocabulary * 3).map(|i| format!("word{} ", (i * 7919) % vocabulary)).collect::<String>();
+            let estimate = unique_words(&[text.as_bytes()], true) as f64;
+            let off = (estimate - vocabulary as f64).abs() / vocabulary as f64;
+            assert!(off <= HyperLogLog::ERROR, "{} words estimated as {}", vocabulary, estimate);
+        }
+    }
+}
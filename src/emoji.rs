@@ -0,0 +1,74 @@
+/*
+--non-ascii and --emoji, for localization and content teams that want to know how much of a text isn't plain ascii
+and how much of it is emoji. Non-ascii is every character above U+007F, including the U+FFFD an invalid byte sequence
+decodes to. Emoji are counted per pictograph: the skin tone modifiers, variation selectors and zero width joiners that
+dress one up don't count on their own, so 👍🏽 is one, but a ZWJ sequence like a family counts each person in it, and a
+flag (two regional indicators) counts once...
+*/
+use crate::utf8::Utf8Decoder;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Counts {
+    pub non_ascii: u64,
+    pub emoji: u64,
+}
+
+pub struct EmojiCounter {
+    decoder: Utf8Decoder,
+    counts: Counts,
+    regional_indicators: u64, // a flag is two of them
+}
+
+impl EmojiCounter {
+    pub fn new() -> Self {
+        Self { decoder: Utf8Decoder::new(), counts: Counts::default(), regional_indicators: 0 }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let (counts, regional_indicators) = (&mut self.counts, &mut self.regional_indicators);
+        self.decoder.decode(bytes, |c| count(c.unwrap_or(char::REPLACEMENT_CHARACTER), counts, regional_indicators));
+    }
+
+    pub fn feed_char(&mut self, c: char) {
+        count(c, &mut self.counts, &mut self.regional_indicators);
+    }
+
+    // see StreamCounter::drop_partial_char
+    pub fn drop_partial_char(&mut self) {
+        self.decoder.drop_partial_char();
+    }
+
+    pub fn finish(mut self) -> Counts {
+        let (counts, regional_indicators) = (&mut self.counts, &mut self.regional_indicators);
+        self.decoder.finish(|c| count(c.unwrap_or(char::REPLACEMENT_CHARACTER), counts, regional_indicators));
+        self.counts.emoji += self.regional_indicators.div_ceil(2);
+        self.counts
+    }
+}
+
+fn count(c: char, counts: &mut Counts, regional_indicators: &mut u64) {
+    if c.is_ascii() {
+        return;
+    }
+    counts.non_ascii += 1;
+    if matches!(c, '\u{1F1E6}'..='\u{1F1FF}') {
+        *regional_indicators += 1;
+    } else if is_pictograph(c) {
+        counts.emoji += 1;
+    }
+}
+
+// the blocks emoji come from, minus the modifiers that only change the one before them
+fn is_pictograph(c: char) -> bool {
+    match c {
+        '\u{1F3FB}'..='\u{1F3FF}' => false, // skin tones
+        '\u{1F000}'..='\u{1FAFF}' => true,  // mahjong tiles through symbols and pictographs extended-A
+        '\u{2600}'..='\u{27BF}' => true,    // miscellaneous symbols and dingbats
+        '\u{2B00}'..='\u{2BFF}' => matches!(c, '\u{2B05}'..='\u{2B07}' | '\u{2B1B}' | '\u{2B1C}' | '\u{2B50}' | '\u{2B55}'),
+        '\u{2190}'..='\u{21FF}' => matches!(c, '\u{2194}'..='\u{2199}' | '\u{21A9}' | '\u{21AA}'),
+        '\u{231A}' | '\u{231B}' | '\u{2328}' | '\u{23CF}' | '\u{23E9}'..='\u{23F3}' | '\u{23F8}'..='\u{23FA}' => true,
+        '\u{00A9}' | '\u{00AE}' | '\u{203C}' | '\u{2049}' | '\u{2122}' | '\u{2139}' | '\u{3030}' | '\u{303D}' => true,
+        '\u{3297}' | '\u{3299}' => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,108 @@
+/*
+--entropy and --compress-ratio, for spotting inputs that are already compressed or encrypted: both come out close to 8
+bits per byte and don't shrink at all when deflated, where text sits around 4-5 bits and deflates to a third or so.
+--byte-histogram prints the byte counts the entropy is worked out from...
+*/
+#[cfg(feature = "compression")]
+use std::io::Write;
+
+#[cfg(feature = "compression")]
+use flate2::write::DeflateEncoder;
+#[cfg(feature = "compression")]
+use flate2::Compression;
+
+// how much of each input --compress-ratio deflates, enough to be representative and still quick
+pub const SAMPLE_LEN: usize = 1024 * 1024;
+
+// bits per byte, 0 when every byte is the same and 8 when all 256 values are equally likely
+pub fn shannon(histogram: &[u64; 256]) -> f64 {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+
+    histogram
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// size of the sample after a fast deflate
+#[cfg(feature = "compression")]
+pub fn compressed_len(sample: &[u8]) -> u64 {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    // writing into a Vec can't fail
+    let _ = encoder.write_all(sample);
+    encoder.finish().map_or(sample.len(), |compressed| compressed.len()) as u64
+}
+
+// --compress-ratio is refused without the compression feature, so nothing asks for this then
+#[cfg(not(feature = "compression"))]
+pub fn compressed_len(sample: &[u8]) -> u64 {
+    sample.len() as u64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistogramFormat {
+    Text,
+    Json,
+}
+
+impl HistogramFormat {
+    pub fn parse(value: &str) -> Result<HistogramFormat, String> {
+        match value {
+            "text" => Ok(HistogramFormat::Text),
+            "json" => Ok(HistogramFormat::Json),
+            _ => Err(format!("invalid value for '--byte-histogram': '{}' (expected text or json)", value)),
+        }
+    }
+}
+
+/*
+Text is a line per byte value that occurs, with the character for printable ascii, like a hexdump column. JSON is one
+line with all 256 counts so it can be picked out of the rest of the output and loaded as is.
+*/
+pub fn format_histogram(histogram: &[u64; 256], topic: &str, format: HistogramFormat) -> String {
+    match format {
+        HistogramFormat::Text => {
+            let mut lines: Vec<String> = Vec::new();
+            for (byte, count) in histogram.iter().enumerate().filter(|(_, count)| **count > 0) {
+                let shown = if (0x21..0x7f).contains(&byte) { format!(" {}", byte as u8 as char) } else { String::new() };
+                lines.push(format!("{:>10} 0x{:02x}{}", count, byte, shown));
+            }
+            lines.join("\n")
+        },
+        HistogramFormat::Json => {
+            let counts: Vec<String> = histogram.iter().map(|count| count.to_string()).collect();
+            let topic = serde_json::Value::from(topic).to_string(); // quoted and escaped
+            format!("{{\"file\":{},\"histogram\":[{}]}}", topic, counts.join(","))
+        },
+    }
+}
+
+// serde only does arrays up to 32 long, so the histogram goes through a Vec
+#[cfg(feature = "serde")]
+pub mod histogram_serde {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(histogram: &Option<Box<[u64; 256]>>, serializer: S) -> Result<S::Ok, S::Error> {
+        histogram.as_deref().map(|histogram| histogram.as_slice()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Box<[u64; 256]>>, D::Error> {
+        match Option::<Vec<u64>>::deserialize(deserializer)? {
+            Some(counts) => {
+                let histogram: Box<[u64; 256]> = counts
+                    .into_boxed_slice()
+                    .try_into()
+                    .map_err(|counts: Box<[u64]>| de::Error::invalid_length(counts.len(), &"256 byte counts"))?;
+                Ok(Some(histogram))
+            },
+            None => Ok(None),
+        }
+    }
+}
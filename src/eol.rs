@@ -0,0 +1,64 @@
+/*
+--eol, what ends a line for -l. Words are the same whatever the convention since \r is a separator anyway, it's the
+line count that isn't: a file from a classic Mac has nothing but lone \r in it and comes out as 0 lines, and a CRLF
+file with a \n or two inside its fields (CSV exports do this) has more lines than its rows.
+
+- lf, the default and what every wc counts: \n, with \r\n counting through its \n
+- crlf: \r\n only, a \n on its own is part of the line
+- any: \n, \r\n and a lone \r are one line break each
+
+Only the line count goes by it. -L, --max-lines and the other columns reading lines still end them at \n...
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eol {
+    Lf,
+    Crlf,
+    Any,
+}
+
+impl Eol {
+    pub fn parse(value: &str) -> Result<Eol, String> {
+        match value {
+            "lf" => Ok(Eol::Lf),
+            "crlf" => Ok(Eol::Crlf),
+            "any" => Ok(Eol::Any),
+            _ => Err(format!("invalid value for '--eol': '{}' (expected lf, crlf or any)", value)),
+        }
+    }
+}
+
+// the line count with crlf or any, lf is what the counters count anyway
+pub struct LineEnds {
+    eol: Eol,
+    after_cr: bool, // whether the last chunk ended with a \r, the \n may be the first byte of the next
+    lf: u64,
+    cr: u64,
+    crlf: u64,
+}
+
+impl LineEnds {
+    pub fn new(eol: Eol) -> Self {
+        Self { eol, after_cr: false, lf: 0, cr: 0, crlf: 0 }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        for byte in chunk {
+            match byte {
+                b'\n' if self.after_cr => self.crlf += 1,
+                b'\n' => self.lf += 1,
+                b'\r' => self.cr += 1,
+                _ => {},
+            }
+            self.after_cr = *byte == b'\r';
+        }
+    }
+
+    pub fn finish(self) -> u64 {
+        match self.eol {
+            Eol::Lf => self.lf + self.crlf,
+            Eol::Crlf => self.crlf,
+            Eol::Any => self.lf + self.cr, // every \r\n was counted at its \r
+        }
+    }
+}
@@ -0,0 +1,212 @@
+/*
+--detect-type, two columns saying what each input looks like, for making sense of counts that seem off: a "text" file
+with no lines that's really UTF-16, or a word count that's mostly the noise of a binary. The type comes from the first
+8K of the content:
+
+- ascii, utf8: valid UTF-8, with nothing past 7 bits or with something, a UTF-8 BOM being utf8 whatever follows it
+- utf16le, utf16be: a BOM, or every other byte 0 the way ascii text in UTF-16 has it
+- text: not UTF-8 but no NULs and hardly any control characters either, Latin-1 or another 8 bit encoding
+- binary: anything else
+- empty
+
+and the language from the name (the extension, or Makefile and the like) or failing that a #! line or an <?xml or
+<!DOCTYPE html at the start, as a guess rather than anything a parser would agree with. "-" when there's no telling.
+Documents and archives get the type of the file itself, even though what's counted is the text inside them...
+*/
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+const SNIFF_LEN: usize = 8 * 1024;
+
+// fed the start of an input as it's read
+pub struct Sniffer {
+    start: Vec<u8>,
+    language: Option<&'static str>,
+}
+
+impl Sniffer {
+    // the path is empty for stdin
+    pub fn new(path: &str) -> Self {
+        Self { start: Vec::new(), language: language_for(path) }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        let wanted = (SNIFF_LEN - self.start.len()).min(chunk.len());
+        self.start.extend_from_slice(&chunk[..wanted]);
+    }
+
+    // the type and the language, if there's one
+    pub fn finish(self) -> (String, Option<String>) {
+        let file_type = content_type(&self.start);
+        let language = match file_type {
+            "binary" | "empty" => self.language,
+            _ => self.language.or_else(|| language_in(&self.start)),
+        };
+        (file_type.to_string(), language.map(String::from))
+    }
+}
+
+// for documents, which are counted from their text but whose type is the file's
+pub fn detect_file(path: &str) -> (String, Option<String>) {
+    let mut sniffer = Sniffer::new(path);
+    let mut start = Vec::new();
+    if let Ok(file) = File::open(path) {
+        let _ = file.take(SNIFF_LEN as u64).read_to_end(&mut start);
+    }
+    sniffer.feed(&start);
+    sniffer.finish()
+}
+
+fn content_type(start: &[u8]) -> &'static str {
+    if start.is_empty() {
+        return "empty";
+    }
+    if start.starts_with(b"\xef\xbb\xbf") {
+        return "utf8";
+    }
+    if start.starts_with(b"\xff\xfe") {
+        return "utf16le";
+    }
+    if start.starts_with(b"\xfe\xff") {
+        return "utf16be";
+    }
+    if let Some(utf16) = utf16_without_bom(start) {
+        return utf16;
+    }
+    if start.contains(&0) {
+        return "binary";
+    }
+
+    // the 8K can end halfway through a character
+    let valid_utf8 = match std::str::from_utf8(start) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    };
+    if valid_utf8 {
+        return if start.is_ascii() { "ascii" } else { "utf8" };
+    }
+    let controls = start.iter().filter(|byte| byte.is_ascii_control() && !matches!(byte, b'\t' | b'\n' | b'\r' | b'\x0c' | b'\x1b')).count();
+    if controls * 100 < start.len() { "text" } else { "binary" }
+}
+
+// most of the high (or low) bytes 0 and none of the others, what ascii in UTF-16 comes out as
+fn utf16_without_bom(start: &[u8]) -> Option<&'static str> {
+    if start.len() < 16 {
+        return None;
+    }
+    let zeros_at = |offset: usize| start.iter().skip(offset).step_by(2).filter(|byte| **byte == 0).count();
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    let half = start.len() / 2;
+    if odd * 10 >= half * 9 && even == 0 {
+        Some("utf16le")
+    } else if even * 10 >= half * 9 && odd == 0 {
+        Some("utf16be")
+    } else {
+        None
+    }
+}
+
+fn language_for(path: &str) -> Option<&'static str> {
+    let name = Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or("");
+    match name {
+        "Makefile" | "makefile" | "GNUmakefile" => return Some("make"),
+        "Dockerfile" | "Containerfile" => return Some("dockerfile"),
+        "CMakeLists.txt" => return Some("cmake"),
+        _ => {},
+    }
+
+    let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).unwrap_or("");
+    let language = match extension.to_ascii_lowercase().as_str() {
+        "rs" => "rust",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "c++",
+        "cs" => "c#",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "scala" => "scala",
+        "swift" => "swift",
+        "go" => "go",
+        "zig" => "zig",
+        "dart" => "dart",
+        "js" | "mjs" | "cjs" | "jsx" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "php" => "php",
+        "py" => "python",
+        "rb" => "ruby",
+        "pl" | "pm" => "perl",
+        "lua" => "lua",
+        "r" => "r",
+        "jl" => "julia",
+        "ex" | "exs" => "elixir",
+        "erl" => "erlang",
+        "hs" => "haskell",
+        "elm" => "elm",
+        "clj" | "cljs" => "clojure",
+        "lisp" | "el" | "scm" => "lisp",
+        "sh" | "bash" | "zsh" | "fish" => "shell",
+        "ps1" => "powershell",
+        "sql" => "sql",
+        "html" | "htm" | "xhtml" => "html",
+        "xml" | "svg" => "xml",
+        "css" | "scss" | "less" => "css",
+        "vue" => "vue",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "ini" | "cfg" | "conf" => "ini",
+        "md" | "markdown" => "markdown",
+        "rst" => "restructuredtext",
+        "tex" => "tex",
+        "proto" => "protobuf",
+        "tf" => "terraform",
+        "nix" => "nix",
+        "mk" => "make",
+        "cmake" => "cmake",
+        "asm" | "s" => "assembly",
+        "f90" | "f95" | "f03" => "fortran",
+        "vim" => "vim",
+        "csv" => "csv",
+        "tsv" => "tsv",
+        "txt" | "log" => "plaintext",
+        _ => return None,
+    };
+    Some(language)
+}
+
+// what the content gives away when the name doesn't
+fn language_in(start: &[u8]) -> Option<&'static str> {
+    let first_line = start.split(|byte| *byte == b'\n').next().unwrap_or_default();
+    let first_line = String::from_utf8_lossy(first_line);
+    if let Some(command) = first_line.strip_prefix("#!") {
+        // #!/usr/bin/env python3 or #!/bin/bash -e
+        let mut words = command.split_whitespace();
+        let program = words.next().map(|program| program.rsplit('/').next().unwrap_or(program));
+        let interpreter = match program {
+            Some("env") => words.find(|word| !word.starts_with('-')),
+            program => program,
+        }?;
+        return match interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+            "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish" => Some("shell"),
+            "python" => Some("python"),
+            "ruby" => Some("ruby"),
+            "perl" => Some("perl"),
+            "node" | "deno" | "bun" => Some("javascript"),
+            "php" => Some("php"),
+            "lua" => Some("lua"),
+            "Rscript" => Some("r"),
+            "pwsh" => Some("powershell"),
+            "awk" | "gawk" => Some("awk"),
+            _ => None,
+        };
+    }
+
+    let head = first_line.trim_start().to_ascii_lowercase();
+    if head.starts_with("<?xml") {
+        Some("xml")
+    } else if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        Some("html")
+    } else {
+        None
+    }
+}
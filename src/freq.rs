@@ -0,0 +1,254 @@
+/*
+The freq subcommand: how often each word shows up across the inputs, most frequent first. Words are split the same way
+the counter splits them, then trimmed of the punctuation around them so "end." and "end" are the same word. With
+--ngrams=N it's runs of N words in a row instead, those never run from the end of one input into the next...
+
+    wc_clone freq [--top=N] [--format=text|json|csv] [--ignore-case] [--stopwords=FILE] [--min-count=N] [--ngrams=N] [FILE]...
+*/
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+
+use crate::i18n::tr;
+use crate::{stream, suggest};
+
+// --stopwords=english, the usual function words that drown out everything else in a frequency list
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "am", "an", "and", "any", "are", "as", "at", "be",
+    "because", "been", "before", "being", "below", "between", "both", "but", "by", "can", "could", "did", "do", "does",
+    "doing", "down", "during", "each", "few", "for", "from", "further", "had", "has", "have", "having", "he", "her",
+    "here", "hers", "herself", "him", "himself", "his", "how", "i", "if", "in", "into", "is", "it", "its", "itself",
+    "just", "me", "more", "most", "my", "myself", "no", "nor", "not", "now", "of", "off", "on", "once", "only", "or",
+    "other", "our", "ours", "ourselves", "out", "over", "own", "same", "she", "should", "so", "some", "such", "than",
+    "that", "the", "their", "theirs", "them", "themselves", "then", "there", "these", "they", "this", "those",
+    "through", "to", "too", "under", "until", "up", "very", "was", "we", "were", "what", "when", "where", "which",
+    "while", "who", "whom", "why", "will", "with", "would", "you", "your", "yours", "yourself", "yourselves",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Debug)]
+struct FreqOptions {
+    files: Vec<String>,
+    top: Option<usize>,
+    format: Format,
+    ignore_case: bool,
+    stopwords: HashSet<String>, // always lowercase, matched whatever the case of the word
+    min_count: u64,
+    ngrams: usize,
+}
+
+impl FreqOptions {
+    fn build(mut argv: impl Iterator<Item = String>) -> Result<FreqOptions, String> {
+        let mut options = FreqOptions {
+            files: Vec::new(),
+            top: None,
+            format: Format::Text,
+            ignore_case: false,
+            stopwords: HashSet::new(),
+            min_count: 1,
+            ngrams: 1,
+        };
+
+        while let Some(arg) = argv.next() {
+            if !arg.starts_with("--") {
+                options.files.push(arg);
+                continue;
+            }
+
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+
+            if flag == "--ignore-case" {
+                options.ignore_case = true;
+                continue;
+            }
+            if !matches!(flag.as_str(), "--top" | "--min-count" | "--format" | "--ngrams" | "--stopwords") {
+                return Err(suggest::unsupported(&arg, &["--top", "--min-count", "--format", "--ngrams", "--stopwords", "--ignore-case"]));
+            }
+
+            let value = match inline_value.or_else(|| argv.next()) {
+                Some(value) => value,
+                None => return Err(tr!("option '{}' requires an argument", flag)),
+            };
+            match flag.as_str() {
+                "--top" => options.top = Some(parse_number(&flag, &value)?),
+                "--min-count" => options.min_count = parse_number(&flag, &value)? as u64,
+                "--format" => options.format = parse_format(&value)?,
+                "--ngrams" => options.ngrams = parse_number(&flag, &value)?.max(1),
+                _ => options.stopwords.extend(read_stopwords(&value)?),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("invalid number for '{}': '{}'", flag, value))
+}
+
+fn parse_format(value: &str) -> Result<Format, String> {
+    match value {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        "csv" => Ok(Format::Csv),
+        _ => Err(format!("invalid value for '--format': '{}' (expected text, json or csv)", value)),
+    }
+}
+
+/*
+"english" is the built-in list, anything else is a file of stopwords separated by whitespace (./english for a file
+that happens to be called that).
+*/
+fn read_stopwords(source: &str) -> Result<Vec<String>, String> {
+    if source == "english" {
+        return Ok(ENGLISH_STOPWORDS.iter().map(|word| word.to_string()).collect());
+    }
+
+    match fs::read(source) {
+        Ok(contents) => Ok(String::from_utf8_lossy(&contents).split_whitespace().map(|word| word.to_lowercase()).collect()),
+        Err(err) => Err(format!("cannot open '{}' for reading: {}", source, err)),
+    }
+}
+
+// argv here is whatever came after "freq"
+pub fn run(argv: impl Iterator<Item = String>) {
+    let options = match FreqOptions::build(argv) {
+        Ok(options) => options,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        }
+    };
+
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    if options.files.is_empty() {
+        if io::stdin().lock().is_terminal() {
+            println!("{}", tr!("No files spcified..."));
+            return;
+        }
+        if let Err(err) = count_words(io::stdin(), &options, &mut counts) {
+            println!("{}", tr!("Encounted error reading stdin: {}", err));
+        }
+    }
+    for file in &options.files {
+        if let Err(err) = fs::File::open(file).and_then(|reader| count_words(reader, &options, &mut counts)) {
+            println!("{}", tr!("Encounted error reading file {}: {}", file, err));
+        }
+    }
+
+    let mut sorted: Vec<(String, u64)> = counts.into_iter().filter(|(_, count)| *count >= options.min_count).collect();
+    // most frequent first, ties alphabetically so the output doesn't change from run to run
+    sorted.sort_by(|(word_a, count_a), (word_b, count_b)| count_b.cmp(count_a).then_with(|| word_a.cmp(word_b)));
+    sorted.truncate(options.top.unwrap_or(usize::MAX));
+
+    print_counts(&sorted, options.format);
+}
+
+// reads in chunks like the counter does, a word cut in two at the end of a chunk is kept for the next one
+fn count_words(mut reader: impl Read, options: &FreqOptions, counts: &mut HashMap<String, u64>) -> io::Result<()> {
+    let mut buffer = vec![0; stream::CHUNK_SIZE];
+    let mut word: Vec<u8> = Vec::new();
+    let mut window: VecDeque<String> = VecDeque::with_capacity(options.ngrams); // the last ngrams words
+
+    loop {
+        let read = stream::read_chunk(&mut reader, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+
+        for byte in &buffer[..read] {
+            if stream::DEFAULT_SEPARATORS.contains(*byte) {
+                add_word(&word, options, &mut window, counts);
+                word.clear();
+            } else {
+                word.push(*byte);
+            }
+        }
+    }
+
+    add_word(&word, options, &mut window, counts);
+    Ok(())
+}
+
+// stopwords are dropped before the n-grams are put together, so they never show up in one either
+fn add_word(raw: &[u8], options: &FreqOptions, window: &mut VecDeque<String>, counts: &mut HashMap<String, u64>) {
+    let raw = String::from_utf8_lossy(raw);
+    let word = raw.trim_matches(|c: char| !c.is_alphanumeric());
+    if word.is_empty() {
+        return;
+    }
+
+    let lowercase = word.to_lowercase();
+    if options.stopwords.contains(&lowercase) {
+        return;
+    }
+
+    let word = if options.ignore_case { lowercase } else { word.to_string() };
+    if options.ngrams == 1 {
+        *counts.entry(word).or_insert(0) += 1;
+        return;
+    }
+
+    if window.len() == options.ngrams {
+        window.pop_front();
+    }
+    window.push_back(word);
+    if window.len() == options.ngrams {
+        let ngram = window.iter().map(|word| word.as_str()).collect::<Vec<&str>>().join(" ");
+        *counts.entry(ngram).or_insert(0) += 1;
+    }
+}
+
+fn print_counts(sorted: &[(String, u64)], format: Format) {
+    match format {
+        Format::Text => {
+            for (word, count) in sorted {
+                println!("{:>7} {}", count, word);
+            }
+        },
+        Format::Json => {
+            let entries: Vec<String> = sorted
+                .iter()
+                .map(|(word, count)| format!("{{\"word\":{},\"count\":{}}}", json_string(word), count))
+                .collect();
+            println!("[{}]", entries.join(","));
+        },
+        Format::Csv => {
+            println!("word,count");
+            for (word, count) in sorted {
+                println!("{},{}", csv_field(word), count);
+            }
+        },
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
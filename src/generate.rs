@@ -0,0 +1,135 @@
+/*
+The generate subcommand writes made up text for testing and benchmarking: lines of random lowercase words, about
+--avg-line-len characters long (from half that to half as much again), and with --unicode every so often an accented
+letter, Greek or Cyrillic, CJK, an emoji or a letter with a combining accent, for exercising -m, -L and the word
+splitting. The same --seed always gives the same bytes, so a corpus can be made again instead of kept around...
+
+    wc_clone generate --lines 100000 --avg-line-len 80 --unicode -o corpus.txt
+*/
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::i18n::tr;
+
+const UNICODE: &[&str] = &["é", "ü", "ß", "ñ", "ø", "α", "λ", "ω", "ж", "я", "中", "文", "字", "日", "本", "😀", "🎉", "👍", "e\u{301}", "a\u{308}"];
+
+pub struct Generator {
+    state: u64,
+    avg_line_len: usize,
+    unicode: bool,
+}
+
+impl Generator {
+    pub fn new(seed: u64, avg_line_len: usize, unicode: bool) -> Self {
+        Self { state: seed, avg_line_len: avg_line_len.max(1), unicode }
+    }
+
+    // splitmix64, small and good enough for text nobody reads
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    // one line and its newline
+    pub fn line(&mut self, out: &mut String) {
+        let half = self.avg_line_len / 2;
+        let target = half + self.below(self.avg_line_len + 1);
+        let mut len = 0;
+        while len < target {
+            if len > 0 {
+                out.push(' ');
+                len += 1;
+            }
+            let word_len = 1 + self.below(9);
+            for _ in 0..word_len.min(target.saturating_sub(len).max(1)) {
+                if self.unicode && self.below(8) == 0 {
+                    out.push_str(UNICODE[self.below(UNICODE.len())]);
+                } else {
+                    out.push((b'a' + self.below(26) as u8) as char);
+                }
+                len += 1;
+            }
+        }
+        out.push('\n');
+    }
+
+    // about size bytes of lines, cut to exactly that (which can cut a character in two)
+    pub fn text(&mut self, size: usize) -> Vec<u8> {
+        let mut text = String::with_capacity(size + self.avg_line_len * 4);
+        while text.len() < size {
+            self.line(&mut text);
+        }
+        let mut bytes = text.into_bytes();
+        bytes.truncate(size);
+        bytes
+    }
+}
+
+// argv here is whatever came after "generate"
+pub fn run(mut argv: impl Iterator<Item = String>) {
+    let (mut lines, mut avg_line_len, mut unicode, mut seed, mut output) = (1000, 60, false, 0, None);
+    while let Some(arg) = argv.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        if flag == "--unicode" {
+            unicode = true;
+            continue;
+        }
+        if !matches!(flag.as_str(), "--lines" | "--avg-line-len" | "--seed" | "-o" | "--output") {
+            println!("{}", crate::suggest::unsupported(&arg, &["--lines", "--avg-line-len", "--unicode", "--seed", "--output"]));
+            return;
+        }
+        let Some(value) = inline.or_else(|| argv.next()) else {
+            println!("{}", tr!("option '{}' requires an argument", flag));
+            return;
+        };
+        let number = || value.parse::<u64>().map_err(|_| format!("invalid number for '{}': '{}'", flag, value));
+        let parsed = match flag.as_str() {
+            "--lines" => number().map(|number| lines = number),
+            "--avg-line-len" => number().map(|number| avg_line_len = number as usize),
+            "--seed" => number().map(|number| seed = number),
+            _ => {
+                output = Some(value.clone());
+                Ok(())
+            },
+        };
+        if let Err(err) = parsed {
+            println!("{}", err);
+            return;
+        }
+    }
+
+    let result = match &output {
+        Some(path) => File::create(path).and_then(|file| write(BufWriter::new(file), lines, Generator::new(seed, avg_line_len, unicode))),
+        None => write(BufWriter::new(io::stdout().lock()), lines, Generator::new(seed, avg_line_len, unicode)),
+    };
+    if let Err(err) = result {
+        if err.kind() == io::ErrorKind::BrokenPipe {
+            std::process::exit(1); // | head, same as counting
+        }
+        match output {
+            Some(path) => eprintln!("{}", tr!("wc_clone: can't write {}: {}", path, err)),
+            None => eprintln!("{}", tr!("wc_clone: write error: {}", err)),
+        }
+        std::process::exit(1);
+    }
+}
+
+fn write(mut out: impl Write, lines: u64, mut generator: Generator) -> io::Result<()> {
+    let mut line = String::new();
+    for _ in 0..lines {
+        line.clear();
+        generator.line(&mut line);
+        out.write_all(line.as_bytes())?;
+    }
+    out.flush()
+}
@@ -0,0 +1,55 @@
+/*
+--git asks git which files to count instead of taking them from the command line: everything tracked, what's staged,
+what's been modified, or what differs from a revision ("how big are the files this branch touches" is then
+`wc_clone -l --git=diff main`). Operands narrow it down like pathspecs do. Paths come back relative to the current
+directory and only from below it, the way git ls-files lists them, and deleted files are left out since there's
+nothing left to count...
+*/
+use std::process::Command;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    Tracked,
+    Staged,
+    Modified,     // changed in the working tree and not staged yet
+    Diff(String), // the working tree against a revision
+}
+
+impl Selection {
+    // diff takes the revision from the argument after it
+    pub fn parse(value: &str, next_arg: impl FnOnce() -> Option<String>) -> Result<Selection, String> {
+        match value {
+            "tracked" => Ok(Selection::Tracked),
+            "staged" => Ok(Selection::Staged),
+            "modified" => Ok(Selection::Modified),
+            "diff" => match next_arg() {
+                Some(rev) => Ok(Selection::Diff(rev)),
+                None => Err(String::from("'--git=diff' requires a revision, e.g. --git=diff main")),
+            },
+            _ => Err(format!("invalid value for '--git': '{}' (expected tracked, staged, modified or diff REV)", value)),
+        }
+    }
+}
+
+pub fn list_files(selection: &Selection, pathspecs: &[String]) -> Result<Vec<String>, String> {
+    let mut command = Command::new("git");
+    match selection {
+        Selection::Tracked => command.args(["ls-files", "-z"]),
+        Selection::Staged => command.args(["diff", "--cached", "--relative", "--name-only", "-z", "--diff-filter=d"]),
+        Selection::Modified => command.args(["diff", "--relative", "--name-only", "-z", "--diff-filter=d"]),
+        Selection::Diff(rev) => command.args(["diff", "--relative", "--name-only", "-z", "--diff-filter=d", rev.as_str()]),
+    };
+    command.arg("--").args(pathspecs);
+
+    let output = command.output().map_err(|err| format!("couldn't run git: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("git failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(output
+        .stdout
+        .split(|byte| *byte == b'\0')
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect())
+}
@@ -0,0 +1,122 @@
+/*
+--hash=sha256|blake3 checksums each input as it's counted, from the same chunks the counters see, so one pass over an
+archive gives both the numbers and a manifest to check it against later. sha256 is what sha256sum prints and what most
+manifests use, blake3 is a lot faster on big inputs.
+
+Without the hashes feature there are no algorithms at all, --hash is refused when it's parsed and nothing below can
+be reached...
+*/
+#[cfg(feature = "hashes")]
+use std::fs;
+use std::io;
+
+#[cfg(feature = "hashes")]
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "hashes")]
+use crate::stream;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    #[cfg(feature = "hashes")]
+    Sha256,
+    #[cfg(feature = "hashes")]
+    Blake3,
+}
+
+impl Algorithm {
+    #[cfg(feature = "hashes")]
+    pub fn parse(value: &str) -> Result<Algorithm, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "sha256" => Ok(Algorithm::Sha256),
+            "blake3" => Ok(Algorithm::Blake3),
+            _ => Err(format!("invalid value for '--hash': '{}' (expected sha256 or blake3)", value)),
+        }
+    }
+
+    #[cfg(not(feature = "hashes"))]
+    pub fn parse(value: &str) -> Result<Algorithm, String> {
+        Err(format!("can't use '--hash={}': wc_clone was built without the hashes feature", value))
+    }
+}
+
+#[cfg(feature = "hashes")]
+pub enum Hasher {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>), // boxed, its state is a couple of kilobytes
+}
+
+#[cfg(not(feature = "hashes"))]
+pub enum Hasher {}
+
+#[cfg(not(feature = "hashes"))]
+impl Hasher {
+    pub fn new(algorithm: Algorithm) -> Self {
+        match algorithm {}
+    }
+
+    pub fn update(&mut self, _bytes: &[u8]) {
+        match *self {}
+    }
+
+    pub fn finish(self) -> String {
+        match self {}
+    }
+}
+
+#[cfg(feature = "hashes")]
+impl Hasher {
+    pub fn new(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            Algorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(bytes),
+            Hasher::Blake3(hasher) => {
+                hasher.update(bytes);
+            },
+        }
+    }
+
+    // lowercase hex, the way the sum tools print it
+    pub fn finish(self) -> String {
+        let digest: Vec<u8> = match self {
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+        };
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+// the whole file, for inputs that aren't counted from their bytes (documents) and for --dedupe=hash
+#[cfg(feature = "hashes")]
+pub fn file_digest(path: &str, algorithm: Algorithm) -> io::Result<String> {
+    let mut reader = fs::File::open(path)?;
+    let mut buffer = vec![0; stream::CHUNK_SIZE];
+    let mut hasher = Hasher::new(algorithm);
+
+    loop {
+        let read = stream::read_chunk(&mut reader, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finish())
+}
+
+#[cfg(not(feature = "hashes"))]
+pub fn file_digest(_path: &str, algorithm: Algorithm) -> io::Result<String> {
+    match algorithm {}
+}
+
+// what --dedupe=hash compares inputs of the same size by
+#[cfg(feature = "hashes")]
+pub fn content_digest(path: &str) -> io::Result<String> {
+    file_digest(path, Algorithm::Sha256)
+}
@@ -0,0 +1,129 @@
+/*
+--output-format=html, a page that stands on its own (no scripts or styles from anywhere else) for sharing a report with
+people who'd rather not read a terminal: a table of the rows that sorts by whichever column header is clicked, the
+total underneath it where sorting leaves it alone, and with --byte-histogram a chart of each input's bytes...
+
+    wc_clone -r --output-format=html --output=report.html src/
+*/
+use std::io::{self, Write};
+
+use crate::{CommandOptions, FileStats, Reporter};
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}
+table{border-collapse:collapse}th,td{padding:.3em .8em;border-bottom:1px solid #ddd}
+td{text-align:right;font-variant-numeric:tabular-nums}td:first-child{text-align:left}
+th{cursor:pointer;user-select:none;background:#f4f4f4}th:first-child{text-align:left}
+tfoot td{font-weight:bold;border-top:2px solid #888}svg{background:#fafafa;border:1px solid #ddd}
+rect{fill:#4a78b5}figure{margin:1.5em 0}figcaption{font-family:monospace}";
+
+// numbers sort as numbers (5 before 10), anything else as text; clicking again reverses it
+const SCRIPT: &str = "document.querySelectorAll('th').forEach((th, column) => th.addEventListener('click', () => {
+const body = th.closest('table').tBodies[0];
+const descending = th.dataset.order !== 'desc';
+document.querySelectorAll('th').forEach(other => delete other.dataset.order);
+th.dataset.order = descending ? 'desc' : 'asc';
+const key = row => { const text = row.cells[column].textContent; const number = parseFloat(text); return isNaN(number) ? text : number; };
+const rows = [...body.rows].sort((a, b) => { const x = key(a), y = key(b); return (x < y ? -1 : x > y ? 1 : 0) * (descending ? -1 : 1); });
+rows.forEach(row => body.appendChild(row));
+}));";
+
+const CHART_HEIGHT: u64 = 120;
+
+// the page can only be written once every row is in, so they're kept until finish
+#[derive(Debug, Default, Clone)]
+pub struct HtmlReporter {
+    columns: Vec<String>,
+    rows: Vec<String>,
+    total: Option<String>,
+    histograms: Vec<String>,
+}
+
+impl HtmlReporter {
+    fn table_row(&mut self, options: &CommandOptions, stats: &FileStats, topic: &str) -> String {
+        if self.columns.is_empty() {
+            self.columns = crate::column_names(options);
+        }
+        let mut row = format!("<tr><td>{}</td>", escape(topic));
+        for count in crate::selected_counts(options, stats) {
+            row.push_str(&format!("<td>{}</td>", escape(&count)));
+        }
+        row.push_str("</tr>");
+        row
+    }
+}
+
+impl Reporter for HtmlReporter {
+    fn row(&mut self, _out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        let topic = if topic.is_empty() { "(standard input)" } else { topic };
+        let row = self.table_row(options, stats, topic);
+        self.rows.push(row);
+        Ok(())
+    }
+
+    fn total(&mut self, _out: &mut impl Write, options: &CommandOptions, stats: &FileStats) -> io::Result<()> {
+        self.total = Some(self.table_row(options, stats, "total"));
+        Ok(())
+    }
+
+    fn details(&mut self, _out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        if let (Some(_), Some(histogram)) = (options.byte_histogram, stats.byte_histogram()) {
+            self.histograms.push(chart(histogram, topic));
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, _out: &mut impl Write, text: &str) -> io::Result<()> {
+        writeln!(io::stderr(), "{}", text)
+    }
+
+    fn finish(&mut self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>wc_clone report</title>")?;
+        writeln!(out, "<style>\n{}\n</style>\n</head>\n<body>\n<h1>wc_clone report</h1>", STYLE)?;
+
+        writeln!(out, "<table>\n<thead><tr><th>file</th>{}</tr></thead>", self.columns.iter().map(|name| format!("<th>{}</th>", escape(name))).collect::<String>())?;
+        writeln!(out, "<tbody>\n{}\n</tbody>", self.rows.join("\n"))?;
+        if let Some(total) = &self.total {
+            writeln!(out, "<tfoot>{}</tfoot>", total)?;
+        }
+        writeln!(out, "</table>")?;
+
+        if !self.histograms.is_empty() {
+            writeln!(out, "<h2>Byte histograms</h2>\n{}", self.histograms.join("\n"))?;
+        }
+        writeln!(out, "<script>\n{}\n</script>\n</body>\n</html>", SCRIPT)
+    }
+}
+
+// a bar for each byte value, scaled to the most common one, hovering one says which byte it is and how many there were
+fn chart(histogram: &[u64; 256], topic: &str) -> String {
+    let most = histogram.iter().copied().max().unwrap_or(0).max(1);
+    let mut bars = String::new();
+    for (byte, count) in histogram.iter().enumerate().filter(|(_, count)| **count > 0) {
+        let height = (count * CHART_HEIGHT).div_ceil(most);
+        let shown = match byte as u8 {
+            byte @ b'!'..=b'~' => format!(" '{}'", escape(&char::from(byte).to_string())),
+            _ => String::new(),
+        };
+        bars.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"2\" height=\"{}\"><title>0x{:02x}{}: {}</title></rect>",
+            byte * 2,
+            CHART_HEIGHT - height,
+            height,
+            byte,
+            shown,
+            count
+        ));
+    }
+    format!(
+        "<figure><svg width=\"512\" height=\"{}\" viewBox=\"0 0 512 {}\">{}</svg><figcaption>{}</figcaption></figure>",
+        CHART_HEIGHT,
+        CHART_HEIGHT,
+        bars,
+        escape(topic)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
@@ -0,0 +1,255 @@
+/*
+http:// and https:// operands. The response body is streamed through the counters the way a file's contents are, so
+the size or line count of a remote file is `wc_clone -lc https://...` instead of curl | wc. Redirects are followed (up
+to MAX_REDIRECTS), a gzip or deflate Content-Encoding is undone first so the counts are of the file and not of what
+went over the wire (that takes the compression feature, without it only identity is asked for), and a status that
+isn't 2xx is an error for that input like a missing file would be.
+
+Plain http is spoken here, one request per connection the way serve answers them. wc_clone has no TLS of its own, so
+https goes through curl (see child.rs), which then has to be installed...
+
+    wc_clone -lc https://example.com/export.csv http://mirror.local/access.log
+*/
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::i18n::tr;
+use crate::{child, decompress, stream, timeout, CommandOptions, ReadResult};
+
+const MAX_REDIRECTS: usize = 5;
+
+// same as serve, nobody sends headers this long
+const MAX_HEADER_BYTES: u64 = 64 * 1024;
+
+pub fn is_url(operand: &str) -> bool {
+    operand.starts_with("http://") || operand.starts_with("https://")
+}
+
+pub fn read_url(url: &str, command_options: &CommandOptions) -> ReadResult {
+    let body = open(url, command_options).and_then(|body| decompress::wrap(timeout::limit(body, command_options), command_options));
+    crate::read_result(body.and_then(|body| stream::count_reader(body, command_options, url)))
+}
+
+// the body of what url ends up at
+pub fn open(url: &str, command_options: &CommandOptions) -> io::Result<Box<dyn Read + Send>> {
+    let mut url = url.to_string();
+    for _ in 0..=MAX_REDIRECTS {
+        if url.starts_with("https://") {
+            return curl(&url, command_options);
+        }
+        match get(&url, command_options.timeout)? {
+            Response::Body(body) => return Ok(body),
+            Response::Redirect(location) => url = resolve(&url, &location),
+        }
+    }
+    Err(io::Error::other(tr!("more than {} redirects", MAX_REDIRECTS)))
+}
+
+enum Response {
+    Body(Box<dyn Read + Send>),
+    Redirect(String),
+}
+
+#[derive(Debug)]
+struct Target {
+    host: String,
+    port: u16,
+    path: String, // with the query, what goes in the request line
+}
+
+fn parse(url: &str) -> io::Result<Target> {
+    let unsupported = || io::Error::new(io::ErrorKind::InvalidInput, tr!("unsupported URL: {}", url));
+    let rest = url.strip_prefix("http://").ok_or_else(unsupported)?;
+    let rest = rest.split('#').next().unwrap_or("");
+    let (authority, path) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+    let path = match path {
+        "" => String::from("/"),
+        query if query.starts_with('?') => format!("/{}", query),
+        path => path.to_string(),
+    };
+
+    // user:password@ isn't something to be putting on a command line anyway
+    if authority.contains('@') {
+        return Err(unsupported());
+    }
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, port.parse().map_err(|_| unsupported())?),
+        _ => (authority, 80),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return Err(unsupported());
+    }
+    Ok(Target { host: host.to_string(), port, path })
+}
+
+// a Location relative to the URL it came back for
+fn resolve(url: &str, location: &str) -> String {
+    if location.contains("://") {
+        return location.to_string();
+    }
+    let origin_end = url.find("://").map_or(0, |scheme| scheme + 3);
+    let origin_end = url[origin_end..].find('/').map_or(url.len(), |path| origin_end + path);
+    match location.strip_prefix('/') {
+        Some(_) => format!("{}{}", &url[..origin_end], location),
+        None => {
+            let path = url[origin_end..].split(['?', '#']).next().unwrap_or("");
+            let dir = path.rfind('/').map_or("", |slash| &path[..slash]);
+            format!("{}{}/{}", &url[..origin_end], dir, location)
+        },
+    }
+}
+
+fn get(url: &str, timeout: Option<Duration>) -> io::Result<Response> {
+    let target = parse(url)?;
+    let mut connection = connect(&target, timeout)?;
+    let accept = if cfg!(feature = "compression") { "gzip, deflate" } else { "identity" };
+    write!(
+        connection,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: wc_clone/{}\r\nAccept-Encoding: {}\r\nConnection: close\r\n\r\n",
+        target.path,
+        target.host,
+        env!("CARGO_PKG_VERSION"),
+        accept
+    )?;
+    connection.flush()?;
+
+    let mut reader = BufReader::new(connection);
+    let head = read_head(&mut (&mut reader).take(MAX_HEADER_BYTES))?;
+    match head.status {
+        200..=299 => {},
+        301 | 302 | 303 | 307 | 308 if head.location.is_some() => return Ok(Response::Redirect(head.location.unwrap_or_default())),
+        _ => return Err(io::Error::other(format!("HTTP {}", head.status_line))),
+    }
+
+    let body: Box<dyn Read + Send> = match head.content_length {
+        _ if head.chunked => Box::new(Chunked { inner: reader, left: 0, done: false }),
+        Some(length) => Box::new(reader.take(length)),
+        None => Box::new(reader), // until the server hangs up
+    };
+    decode(body, head.content_encoding.as_deref()).map(Response::Body)
+}
+
+fn connect(target: &Target, timeout: Option<Duration>) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, tr!("no address for {}", target.host));
+    for address in (target.host.as_str(), target.port).to_socket_addrs()? {
+        let connected = match timeout {
+            Some(timeout) => TcpStream::connect_timeout(&address, timeout),
+            None => TcpStream::connect(address),
+        };
+        match connected {
+            Ok(connection) => {
+                // a server that stops sending is as stuck as a FIFO nobody writes to
+                connection.set_read_timeout(timeout)?;
+                return Ok(connection);
+            },
+            Err(err) => last_error = err,
+        }
+    }
+    Err(last_error)
+}
+
+#[derive(Debug, Default)]
+struct Head {
+    status: u16,
+    status_line: String, // "404 Not Found", for the error
+    content_length: Option<u64>,
+    chunked: bool,
+    content_encoding: Option<String>,
+    location: Option<String>,
+}
+
+fn read_head(reader: &mut impl BufRead) -> io::Result<Head> {
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, tr!("malformed HTTP response"));
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let status_line = line.trim_end().split_once(' ').map(|(_, status)| status.to_string()).ok_or_else(malformed)?;
+    let mut head = Head { status: status_line.get(..3).and_then(|code| code.parse().ok()).ok_or_else(malformed)?, status_line, ..Head::default() };
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(malformed()); // the connection closed, or MAX_HEADER_BYTES ran out
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            return Ok(head);
+        }
+        let Some((name, value)) = header.split_once(':') else { continue };
+        let value = value.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "content-length" => head.content_length = Some(value.parse().map_err(|_| malformed())?),
+            "transfer-encoding" => head.chunked = value.to_ascii_lowercase().contains("chunked"),
+            "content-encoding" => head.content_encoding = Some(value.to_ascii_lowercase()),
+            "location" => head.location = Some(value.to_string()),
+            _ => {},
+        }
+    }
+}
+
+fn decode(body: Box<dyn Read + Send>, encoding: Option<&str>) -> io::Result<Box<dyn Read + Send>> {
+    match encoding {
+        None | Some("identity") => Ok(body),
+        #[cfg(feature = "compression")]
+        Some("gzip" | "x-gzip") => Ok(Box::new(flate2::read::MultiGzDecoder::new(body))),
+        #[cfg(feature = "compression")]
+        Some("deflate") => Ok(Box::new(flate2::read::ZlibDecoder::new(body))),
+        Some(encoding) => Err(io::Error::new(io::ErrorKind::Unsupported, tr!("can't decode a Content-Encoding of {}", encoding))),
+    }
+}
+
+// Transfer-Encoding: chunked, a hex size line before each chunk and a 0 one at the end
+struct Chunked<R> {
+    inner: R,
+    left: u64, // of the chunk being read
+    done: bool,
+}
+
+impl<R: BufRead> Read for Chunked<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.left == 0 {
+            if self.done {
+                return Ok(0);
+            }
+            self.left = self.next_size()?;
+            if self.left == 0 {
+                self.done = true; // trailers, if any, aren't anything we count
+                return Ok(0);
+            }
+        }
+
+        let wanted = buffer.len().min(usize::try_from(self.left).unwrap_or(usize::MAX));
+        let read = self.inner.read(&mut buffer[..wanted])?;
+        if read == 0 && wanted > 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, tr!("the connection closed in the middle of a chunk")));
+        }
+        self.left -= read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> Chunked<R> {
+    fn next_size(&mut self) -> io::Result<u64> {
+        let mut line = String::new();
+        // the first line can be the CRLF that ends the chunk before
+        while line.trim().is_empty() {
+            line.clear();
+            if (&mut self.inner).take(1024).read_line(&mut line)? == 0 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, tr!("the connection closed in the middle of a chunk")));
+            }
+        }
+        let size = line.trim().split(';').next().unwrap_or("").trim();
+        u64::from_str_radix(size, 16).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, tr!("malformed HTTP response")))
+    }
+}
+
+// curl also takes care of the redirects and the Content-Encoding, --compressed decodes whatever it asked for
+fn curl(url: &str, command_options: &CommandOptions) -> io::Result<Box<dyn Read + Send>> {
+    let mut command = Command::new("curl");
+    command.args(["--silent", "--show-error", "--fail", "--location", "--compressed", "--max-redirs"]).arg(MAX_REDIRECTS.to_string());
+    command.arg(url);
+    let body = child::spawn(command, command_options).map_err(|err| io::Error::new(err.kind(), tr!("https needs curl, which couldn't be run: {}", err)))?;
+    Ok(Box::new(body))
+}
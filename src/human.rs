@@ -0,0 +1,87 @@
+/*
+--human and --si are for reading the counts, not for feeding them to something else: bytes as 1.4MiB (powers of 1024)
+or 1.5M (powers of 1000, like du --si) and the other counts with thousands separators, which is all --group-digits
+does. The separator is the locale's (see locale_separator). Only the rows change, snapshots, --check and the JSON
+outputs keep the exact numbers...
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Binary, // --human
+    Si,     // --si
+}
+
+impl Units {
+    fn base(self) -> f64 {
+        match self {
+            Units::Binary => 1024.0,
+            Units::Si => 1000.0,
+        }
+    }
+
+    fn suffixes(self) -> [&'static str; 7] {
+        match self {
+            Units::Binary => ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"],
+            Units::Si => ["", "k", "M", "G", "T", "P", "E"],
+        }
+    }
+}
+
+// one decimal under 10 and none above, the way du -h keeps them short
+pub fn size(bytes: u64, units: Units) -> String {
+    let suffixes = units.suffixes();
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= units.base() && unit + 1 < suffixes.len() {
+        value /= units.base();
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{}{}", bytes, suffixes[0])
+    } else if value < 9.95 {
+        format!("{:.1}{}", value, suffixes[unit])
+    } else {
+        format!("{:.0}{}", value, suffixes[unit])
+    }
+}
+
+// 1234567 as 1,234,567 (or 1.234.567...)
+pub fn group(count: u64, separator: char) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 * separator.len_utf8());
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/*
+The thousands separator for the locale LC_ALL, LC_NUMERIC or LANG names, the way setlocale picks between them. It goes
+by the language (and the country where that changes it) instead of asking libc, so it works the same without the
+locale being installed and on Windows. Anything unknown, C and POSIX included, gets a comma...
+
+    LC_NUMERIC=de_DE.UTF-8  1.234.567
+    LC_NUMERIC=fr_FR.UTF-8  1 234 567 (a no-break space)
+    LC_NUMERIC=de_CH.UTF-8  1'234'567
+*/
+pub fn locale_separator() -> char {
+    let locale = ["LC_ALL", "LC_NUMERIC", "LANG"].iter().filter_map(|name| std::env::var(name).ok()).find(|value| !value.is_empty());
+    locale.map_or(',', |locale| separator_for(&locale))
+}
+
+fn separator_for(locale: &str) -> char {
+    // de_CH.UTF-8@euro -> de, CH
+    let name = locale.split(['.', '@']).next().unwrap_or("");
+    let (language, country) = name.split_once(['_', '-']).unwrap_or((name, ""));
+
+    match (language, country) {
+        ("de" | "it" | "fr" | "rm", "CH" | "LI") => '\'',
+        ("de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "sl" | "hr" | "sr" | "vi", _) => '.',
+        ("fr" | "ru" | "pl" | "cs" | "sk" | "fi" | "sv" | "nb" | "nn" | "no" | "uk" | "hu" | "bg" | "et" | "lt" | "lv", _) => '\u{a0}',
+        _ => ',',
+    }
+}
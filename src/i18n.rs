@@ -0,0 +1,103 @@
+/*
+Translated messages. The English text is the key, like gettext: tr!("Encounted error reading file {}: {}", file, err)
+looks the message up in the catalog for the language LC_ALL, LC_MESSAGES or LANG asks for (the first one that's set,
+with LANGUAGE's list ahead of it, the way gettext goes through them) and fills in the {} in order. Anything without a
+translation, and every message under C or POSIX, stays English. The counts themselves and --compat's messages are
+never translated, those have to look exactly like the real wc's.
+
+Catalogs are .po files in locales/ built into the binary, only msgid/msgstr pairs on single lines are read. Adding a
+language is a new file there and a line in CATALOGS...
+*/
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+const CATALOGS: &[(&str, &str)] = &[
+    ("de", include_str!("../locales/de.po")),
+];
+
+macro_rules! tr {
+    ($message:literal) => {
+        $crate::i18n::translate($message)
+    };
+    ($message:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill(&$crate::i18n::translate($message), &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub(crate) use tr;
+
+pub fn translate(message: &str) -> String {
+    static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+    let catalog = CATALOG.get_or_init(|| language().and_then(catalog_for).map(parse_po).unwrap_or_default());
+    catalog.get(message).cloned().unwrap_or_else(|| message.to_string())
+}
+
+// each {} in turn, whatever order a translation needs its words in the arguments stay in the English order
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(at) = rest.find("{}") {
+        filled.push_str(&rest[..at]);
+        if let Some(arg) = args.next() {
+            filled.push_str(&arg.to_string());
+        }
+        rest = &rest[at + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+// de_DE.UTF-8 -> de. LANGUAGE can list several, the first one there's a catalog for wins, but like gettext it's only
+// looked at when the locale itself isn't C
+fn language() -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter().filter_map(|name| env::var(name).ok()).find(|value| !value.is_empty())?;
+    if locale == "C" || locale == "POSIX" {
+        return None;
+    }
+    let preferred = env::var("LANGUAGE").ok().filter(|value| !value.is_empty()).unwrap_or(locale);
+    preferred
+        .split(':')
+        .map(|locale| locale.split(['_', '.', '@']).next().unwrap_or("").to_string())
+        .find(|language| catalog_for(language.clone()).is_some())
+}
+
+fn catalog_for(language: String) -> Option<&'static str> {
+    CATALOGS.iter().find(|(name, _)| *name == language).map(|(_, catalog)| *catalog)
+}
+
+fn parse_po(contents: &str) -> HashMap<String, String> {
+    let mut catalog = HashMap::new();
+    let mut msgid = None;
+    for line in contents.lines().map(str::trim) {
+        if let Some(id) = line.strip_prefix("msgid ") {
+            msgid = unquote(id);
+        } else if let (Some(text), Some(id)) = (line.strip_prefix("msgstr "), msgid.take()) {
+            match unquote(text) {
+                Some(text) if !text.is_empty() && !id.is_empty() => {
+                    catalog.insert(id, text);
+                },
+                _ => {},
+            }
+        }
+    }
+    catalog
+}
+
+fn unquote(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next()? {
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                other => text.push(other),
+            },
+            (c, false) => text.push(c),
+        }
+    }
+    Some(text)
+}
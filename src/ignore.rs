@@ -0,0 +1,92 @@
+/*
+.wcignore files, for leaving vendored or generated files out of -r for good instead of with an --exclude on every run.
+One can sit in any directory that gets walked and has gitignore's syntax: a pattern per line, # comments, ! to take a
+file back in, a trailing / for directories only, and a pattern with a slash before its end is from the .wcignore's own
+directory (a leading one just anchors it) where one without matches the name at any depth. The last matching line wins
+and a deeper .wcignore comes after the ones above it. Like in git, a file in a directory that's ignored can't be taken
+back in, the directory is never walked...
+
+    vendor/
+    *.min.js
+    !keep.min.js
+*/
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::walk::glob_matches;
+use crate::winpath;
+
+pub const FILE_NAME: &str = ".wcignore";
+
+#[derive(Debug)]
+struct Rule {
+    pattern: String,
+    negated: bool,  // !pattern
+    dir_only: bool, // pattern/
+    anchored: bool, // matched against the path below the .wcignore's directory, not just the name
+}
+
+// the rules of one .wcignore and where it was
+#[derive(Debug)]
+pub struct IgnoreFile {
+    dir: PathBuf,
+    rules: Vec<Rule>,
+}
+
+// None when dir doesn't have one
+pub fn load(dir: &Path) -> io::Result<Option<IgnoreFile>> {
+    match fs::read_to_string(dir.join(FILE_NAME)) {
+        Ok(content) => Ok(Some(IgnoreFile { dir: dir.to_path_buf(), rules: content.lines().filter_map(parse_rule).collect() })),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    if line.starts_with('#') {
+        return None;
+    }
+    // trailing spaces don't count unless there's a backslash in front of them
+    let mut line = line.trim_end_matches('\r');
+    while line.ends_with(' ') && !line.ends_with("\\ ") {
+        line = &line[..line.len() - 1];
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').filter(|rest| rest.starts_with(['#', '!'])).unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    if line.is_empty() {
+        return None;
+    }
+
+    let anchored = line.contains('/');
+    let pattern = line.strip_prefix('/').unwrap_or(line).replace("\\ ", " ");
+    Some(Rule { pattern, negated, dir_only, anchored })
+}
+
+/*
+Whether the .wcignore files along the way (outermost first) leave path out. Only the files whose directory path is
+under get a say.
+*/
+pub fn is_ignored(ignore_files: &[IgnoreFile], path: &Path, is_dir: bool) -> bool {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut ignored = false;
+    for ignore_file in ignore_files {
+        let Ok(relative) = path.strip_prefix(&ignore_file.dir) else { continue };
+        let relative = winpath::slashed(relative);
+        for rule in &ignore_file.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            if glob_matches(&rule.pattern, if rule.anchored { &relative } else { &name }) {
+                ignored = !rule.negated;
+            }
+        }
+    }
+    ignored
+}
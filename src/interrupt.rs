@@ -0,0 +1,105 @@
+/*
+Ctrl-C handling. The handler only sets a flag, the readers and the pipeline check it between chunks and wind down so
+run_on_files can report what got finished before exiting with 130 (128 + SIGINT, what shells expect). A SIGINT goes to
+one thread of the process though, and only a read on that thread gets EINTR: a pipeline reader blocked on a FIFO or a
+slow stdin would sleep through it. So every read_chunk registers its thread while it's in the read, and the handler
+sends each registered thread a SIGURG (ignored by default, nothing else here uses it) to break the read off. A second
+Ctrl-C exits on the spot in case something is still stuck anyway...
+*/
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)]
+use std::sync::atomic::AtomicUsize;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+pub const EXIT_CODE: i32 = 130;
+
+// the threads in a read right now, 0 for a free slot. More readers than slots just aren't woken up
+#[cfg(unix)]
+const MAX_READING: usize = 256;
+#[cfg(unix)]
+static READING: [AtomicUsize; MAX_READING] = [const { AtomicUsize::new(0) }; MAX_READING];
+
+// set on a slot while the handler signals its thread, so the thread can't leave the read and exit meanwhile
+#[cfg(unix)]
+const SIGNALING: usize = 1;
+
+#[cfg(unix)]
+pub fn install() {
+    // SAFETY: the handlers only touch atomics and call pthread_kill and _exit, all async-signal-safe. SA_RESTART is
+    // left off on purpose, a read on the thread that gets either signal fails with EINTR and read_chunk sees the flag.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        libc::sigemptyset(&mut action.sa_mask);
+        action.sa_sigaction = on_sigint as *const () as libc::sighandler_t;
+        libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut());
+        action.sa_sigaction = on_wake as *const () as libc::sighandler_t;
+        libc::sigaction(libc::SIGURG, &action, std::ptr::null_mut());
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install() {}
+
+#[cfg(unix)]
+extern "C" fn on_sigint(_signal: libc::c_int) {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        unsafe { libc::_exit(EXIT_CODE) };
+    }
+    for slot in &READING {
+        let thread = slot.load(Ordering::SeqCst);
+        if thread != 0 && slot.compare_exchange(thread, thread | SIGNALING, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+            // SAFETY: the thread is still in its read, or waiting in Reading::drop for the slot to be given back
+            unsafe { libc::pthread_kill(thread as libc::pthread_t, libc::SIGURG) };
+            slot.store(thread, Ordering::SeqCst);
+        }
+    }
+}
+
+// only there so SIGURG interrupts the read instead of being ignored
+#[cfg(unix)]
+extern "C" fn on_wake(_signal: libc::c_int) {}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+// what an input that got cut short (or never started) is reported as
+pub fn interrupted_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "interrupted")
+}
+
+// the calling thread's slot in READING for as long as it's held, see read_chunk
+pub struct Reading {
+    #[cfg(unix)]
+    slot: Option<&'static AtomicUsize>,
+}
+
+impl Reading {
+    #[cfg(unix)]
+    pub fn start() -> Self {
+        // pthread_t is an address on Linux and macOS, never 0 and with the low bit clear
+        let thread = unsafe { libc::pthread_self() } as usize;
+        let slot = READING.iter().find(|slot| slot.compare_exchange(0, thread, Ordering::SeqCst, Ordering::SeqCst).is_ok());
+        Self { slot }
+    }
+
+    #[cfg(not(unix))]
+    pub fn start() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Reading {
+    fn drop(&mut self) {
+        let Some(slot) = self.slot else {
+            return;
+        };
+        let thread = slot.load(Ordering::SeqCst) & !SIGNALING;
+        while slot.compare_exchange(thread, 0, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            std::hint::spin_loop();
+        }
+    }
+}
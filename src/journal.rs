@@ -0,0 +1,170 @@
+/*
+--journal keeps a record of a run as it goes, so a scan of millions of files that gets interrupted (or killed, or the
+machine goes down) can be picked up again with --resume instead of starting over. It's JSON lines: what the run was
+counting first, then every file once it's counted with its counts, and about once a second the total so far.
+
+    {"journal":1,"options":["-lw","-r"]}
+    {"file":"src/lib.rs","stats":{...},"parts":[]}
+    {"total":{...},"files":1}
+
+With --resume the files in the journal aren't read again, their rows come from it, and only the ones that aren't there
+(or couldn't be counted, errors aren't kept) get counted and added. It has to be the same options: a journal from a
+run that counted something else is an error rather than a report mixing the two. A line cut off halfway by the crash
+is left out. Without --resume an existing journal is started over. Needs the serde feature, the counts are FileStats
+as serde writes them...
+*/
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use crate::FileStats;
+
+const VERSION: u64 = 1;
+// how often the buffered lines go out to the file, and a total with them
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub type Parts = Vec<(String, FileStats)>;
+// every file the journal has counts for
+pub type Done = HashMap<String, (FileStats, Parts)>;
+
+pub struct Journal {
+    path: String,
+    file: BufWriter<File>,
+    total: FileStats,
+    files: usize,
+    last_flush: Instant,
+}
+
+// the ones that only change how the run goes or what the report looks like, not the counts in it
+const NOT_COUNTING: &[&str] = &["--jobs", "-j", "--ordered", "--unordered", "--verbose", "--io", "--buffer-size", "--timeout", "--throttle",
+    "--no-cache-pollution", "--idle", "--output", "--output-format", "--log-format", "--run-info", "--quote-names", "--print0"];
+
+// the options that change the counts, so a journal is only resumed by a run counting the same things
+pub fn options_key(arguments: &[String]) -> Vec<String> {
+    let mut key = Vec::new();
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        let flag = argument.split_once('=').map_or(argument.as_str(), |(flag, _)| flag);
+        let flag = if flag.starts_with("-j") { "-j" } else { flag };
+        if flag == "--journal" || flag == "--resume" || NOT_COUNTING.contains(&flag) || argument.bytes().skip(1).all(|byte| byte == b'v') {
+            // and the value given after it, when it's a separate argument
+            if flag == argument && matches!(flag, "--journal" | "-j" | "--jobs" | "--io" | "--buffer-size" | "--timeout" | "--throttle" | "--output" | "--output-format" | "--log-format" | "--quote-names") {
+                arguments.next();
+            }
+            continue;
+        }
+        key.push(argument.clone());
+    }
+    key
+}
+
+/*
+Starts the journal at path, and with resume first reads back what an earlier run with the same options put in it:
+the counts of every file it got to. A journal that isn't there yet (or is empty, the run was killed before it wrote
+anything) just means there's nothing to resume.
+*/
+pub fn open(path: &str, options: &[String], resume: bool) -> io::Result<(Journal, Done)> {
+    let in_journal = |err: io::Error| io::Error::new(err.kind(), format!("{}: {}", path, err));
+    let done = match std::fs::read_to_string(path) {
+        Ok(content) if resume && !content.is_empty() => read_entries(&content, options).map_err(in_journal)?,
+        Err(err) if resume && err.kind() != io::ErrorKind::NotFound => return Err(in_journal(err)),
+        _ => HashMap::new(),
+    };
+
+    // written again from the start so a line the last run didn't finish doesn't end up in front of the next one
+    let mut journal = Journal {
+        path: path.to_string(),
+        file: BufWriter::new(File::create(path).map_err(in_journal)?),
+        total: FileStats::new(),
+        files: 0,
+        last_flush: Instant::now(),
+    };
+    journal.write_line(serde_json::json!({ "journal": VERSION, "options": options }))?;
+    for (file, (stats, parts)) in &done {
+        journal.record(file, stats, parts)?;
+    }
+    journal.flush()?;
+    Ok((journal, done))
+}
+
+impl Journal {
+    pub fn record(&mut self, file: &str, stats: &FileStats, parts: &[(String, FileStats)]) -> io::Result<()> {
+        self.write_line(entry(file, stats, parts)?)?;
+        self.total += stats;
+        self.files += 1;
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    // the total so far and everything before it, for when the run stops here
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_line(serde_json::json!({ "total": stats_value(&self.total)?, "files": self.files }))?;
+        self.last_flush = Instant::now();
+        self.file.flush().map_err(|err| io::Error::new(err.kind(), format!("{}: {}", self.path, err)))
+    }
+
+    fn write_line(&mut self, line: serde_json::Value) -> io::Result<()> {
+        writeln!(self.file, "{}", line).map_err(|err| io::Error::new(err.kind(), format!("{}: {}", self.path, err)))
+    }
+}
+
+fn entry(file: &str, stats: &FileStats, parts: &[(String, FileStats)]) -> io::Result<serde_json::Value> {
+    let mut part_values = Vec::new();
+    for (name, stats) in parts {
+        part_values.push(serde_json::json!([name, stats_value(stats)?]));
+    }
+    Ok(serde_json::json!({ "file": file, "stats": stats_value(stats)?, "parts": part_values }))
+}
+
+fn read_entries(content: &str, options: &[String]) -> io::Result<Done> {
+    let mut lines = content.lines();
+    let header: serde_json::Value = lines.next().and_then(|line| serde_json::from_str(line).ok()).unwrap_or_default();
+    if header["journal"].as_u64() != Some(VERSION) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a wc_clone journal"));
+    }
+    if header["options"] != serde_json::json!(options) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("the journal is from a run with other options ({}), --resume needs the same ones", header["options"]),
+        ));
+    }
+
+    let mut done = HashMap::new();
+    for line in lines {
+        // a total, or the line the run was writing when it stopped
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+        let Some(file) = entry["file"].as_str() else { continue };
+        let mut parts = Vec::new();
+        for part in entry["parts"].as_array().into_iter().flatten() {
+            parts.push((part[0].as_str().unwrap_or_default().to_string(), stats_from(&part[1])?));
+        }
+        done.insert(file.to_string(), (stats_from(&entry["stats"])?, parts));
+    }
+    Ok(done)
+}
+
+#[cfg(feature = "serde")]
+fn stats_value(stats: &FileStats) -> io::Result<serde_json::Value> {
+    serde_json::to_value(stats).map_err(io::Error::other)
+}
+
+#[cfg(feature = "serde")]
+fn stats_from(value: &serde_json::Value) -> io::Result<FileStats> {
+    use serde::Deserialize;
+
+    FileStats::deserialize(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+// --journal can't be given then, so none of this runs
+#[cfg(not(feature = "serde"))]
+fn stats_value(_stats: &FileStats) -> io::Result<serde_json::Value> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "wc_clone was built without the serde feature"))
+}
+
+#[cfg(not(feature = "serde"))]
+fn stats_from(_value: &serde_json::Value) -> io::Result<FileStats> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "wc_clone was built without the serde feature"))
+}
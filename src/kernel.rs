@@ -0,0 +1,232 @@
+/*
+The counting kernel on its own: the lines, words, characters and bytes of a byte slice, as a function of nothing but
+the bytes, for fuzzing and property tests. It's what the streaming reader does without any of the options:
+
+- bytes is the length
+- lines is the number of '\n' bytes
+- words is the number of maximal runs of bytes that aren't separators (space, \t, \r and \n, or isspace() in the C
+  locale for count_posix, which is what --posix and --compat count with)
+- chars is the number of bytes that don't continue a UTF-8 character (10xxxxxx), the characters of valid UTF-8. For
+  invalid input that's what wc_clone prints without --strict or --lossy, GNU wc and the decoding modes can differ
+
+Whatever the input, the counts hold to these, which Counts::check tests:
+
+- chars <= bytes
+- lines <= chars
+- words + lines <= bytes, and words + lines <= chars when the input is valid UTF-8 (every word has a character that
+  isn't a newline)
+- 2 * words <= bytes + 1 (words need a separator between them)
+
+and counting a ++ b in two pieces, with Kernel, gives the same counts as counting it whole, whatever the cut. Apart
+from words, the counts of the pieces just add up; words come out one less than the sum when a ends inside a word and b
+starts with the rest of it.
+
+Debug builds check the streaming reader against this on every input (and the counts against the invariants), so a
+`cargo test` or fuzzing run on a debug build also fuzzes the real thing...
+*/
+use crate::stream::{Separators, DEFAULT_SEPARATORS, POSIX_SEPARATORS};
+use crate::FileStats;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub lines: u64,
+    pub words: u64,
+    pub chars: u64,
+    pub bytes: u64,
+}
+
+impl Counts {
+    // the first invariant the counts break, valid_utf8 being whether the input they came from was
+    pub fn check(&self, valid_utf8: bool) -> Result<(), String> {
+        let broken = if self.chars > self.bytes {
+            "chars <= bytes"
+        } else if self.lines > self.chars {
+            "lines <= chars"
+        } else if self.words + self.lines > self.bytes {
+            "words + lines <= bytes"
+        } else if valid_utf8 && self.words + self.lines > self.chars {
+            "words + lines <= chars"
+        } else if 2 * self.words > self.bytes + 1 {
+            "2 * words <= bytes + 1"
+        } else {
+            return Ok(());
+        };
+        Err(format!("{} doesn't hold for {:?}", broken, self))
+    }
+}
+
+pub fn count(bytes: &[u8]) -> Counts {
+    let mut kernel = Kernel::new();
+    kernel.feed(bytes);
+    kernel.finish()
+}
+
+pub fn count_posix(bytes: &[u8]) -> Counts {
+    let mut kernel = Kernel::posix();
+    kernel.feed(bytes);
+    kernel.finish()
+}
+
+// count and count_posix a piece at a time, for input that doesn't come in one slice
+#[derive(Clone)]
+pub struct Kernel {
+    counts: Counts,
+    in_word: bool,
+    separators: &'static Separators,
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Kernel {
+    pub fn new() -> Self {
+        Self::with_separators(&DEFAULT_SEPARATORS)
+    }
+
+    pub fn posix() -> Self {
+        Self::with_separators(&POSIX_SEPARATORS)
+    }
+
+    // the marks of a --word-policy are the streaming reader's business, this only looks at the bytes
+    pub(crate) fn with_separators(separators: &'static Separators) -> Self {
+        Self { counts: Counts::default(), in_word: false, separators }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.counts.bytes += chunk.len() as u64;
+        for byte in chunk {
+            if *byte == b'\n' {
+                self.counts.lines += 1;
+            }
+            if self.separators.contains(*byte) {
+                self.counts.words += u64::from(self.in_word);
+                self.in_word = false;
+            } else {
+                self.in_word = true;
+            }
+            self.counts.chars += u64::from((*byte as i8) >= -0x40);
+        }
+    }
+
+    // the counts so far, a word still going at the end counting as one
+    pub fn counts(&self) -> Counts {
+        Counts { words: self.counts.words + u64::from(self.in_word), ..self.counts }
+    }
+
+    pub fn finish(self) -> Counts {
+        self.counts()
+    }
+}
+
+impl From<&FileStats> for Counts {
+    fn from(stats: &FileStats) -> Self {
+        Counts { lines: stats.lines(), words: stats.words(), chars: stats.chars(), bytes: stats.bytes() }
+    }
+}
+
+// how the characters of what's being validated were counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CharCount {
+    Kernel,                  // the same way, from the bytes
+    Decoded { valid: bool }, // by decoding, which only agrees with the kernel on valid UTF-8
+    Normalized,              // --normalize, anything goes
+}
+
+/*
+Debug builds only: the counts an input got against the kernel's counts of the same bytes (when there are any, a
+--word-policy with marks has none), and against the invariants. Release builds skip all of it.
+*/
+pub(crate) fn debug_validate(counted: Counts, reference: Option<Counts>, char_count: CharCount) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    if let Some(reference) = reference {
+        let compare_chars = matches!(char_count, CharCount::Kernel | CharCount::Decoded { valid: true });
+        let chars = if compare_chars { counted.chars } else { reference.chars };
+        debug_assert_eq!(Counts { chars, ..counted }, reference, "counted differently from the kernel");
+    }
+    if char_count != CharCount::Normalized {
+        let checked = counted.check(char_count == CharCount::Decoded { valid: true });
+        debug_assert!(checked.is_ok(), "{}", checked.unwrap_err());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift, enough to get inputs no one would write by hand without a crate for it
+    struct Bytes(u64);
+
+    impl Bytes {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        // mostly the bytes the counts care about: separators, newlines, ascii, UTF-8 and bytes that are neither
+        fn input(&mut self) -> Vec<u8> {
+            const INTERESTING: &[u8] = b" \t\r\n\x0b\x0cab\xc3\xa9\xe2\x82\xac\xf0\x9f\x98\x80\x80\xff";
+            let len = (self.next() % 64) as usize;
+            (0..len)
+                .map(|_| match self.next() % 4 {
+                    0 => self.next() as u8,
+                    _ => INTERESTING[(self.next() % INTERESTING.len() as u64) as usize],
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn counts_hold_to_the_invariants() {
+        let mut bytes = Bytes(0x2545_f491_4f6c_dd1d);
+        for _ in 0..10_000 {
+            let input = bytes.input();
+            let valid_utf8 = std::str::from_utf8(&input).is_ok();
+            for counts in [count(&input), count_posix(&input)] {
+                assert_eq!(counts.check(valid_utf8), Ok(()), "for {:?}", input);
+                assert_eq!(counts.bytes, input.len() as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn counting_in_pieces_is_counting_whole() {
+        let mut bytes = Bytes(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..10_000 {
+            let input = bytes.input();
+            let cut = (bytes.next() % (input.len() as u64 + 1)) as usize;
+            let mut kernel = Kernel::new();
+            kernel.feed(&input[..cut]);
+            kernel.feed(&input[cut..]);
+            assert_eq!(kernel.finish(), count(&input), "for {:?} cut at {}", input, cut);
+
+            // the pieces add up, but for a word cut in half
+            let (a, b) = (count(&input[..cut]), count(&input[cut..]));
+            let joined = input[..cut].last().is_some_and(|byte| !DEFAULT_SEPARATORS.contains(*byte))
+                && input[cut..].first().is_some_and(|byte| !DEFAULT_SEPARATORS.contains(*byte));
+            let sum = Counts {
+                lines: a.lines + b.lines,
+                words: a.words + b.words - u64::from(joined),
+                chars: a.chars + b.chars,
+                bytes: a.bytes + b.bytes,
+            };
+            assert_eq!(sum, count(&input), "for {:?} cut at {}", input, cut);
+        }
+    }
+
+    #[test]
+    fn known_counts() {
+        assert_eq!(count(b""), Counts::default());
+        assert_eq!(count(b"one two\nthree\n"), Counts { lines: 2, words: 3, chars: 14, bytes: 14 });
+        assert_eq!(count("h\u{e9}llo \u{1f600}".as_bytes()), Counts { lines: 0, words: 2, chars: 7, bytes: 11 });
+        // a vertical tab separates words in the C locale and nowhere else
+        assert_eq!(count(b"a\x0bb").words, 1);
+        assert_eq!(count_posix(b"a\x0bb").words, 2);
+    }
+}
@@ -13,18 +13,479 @@ print the newline counts
 -w, --words
 print the word counts
 
+--normalize=FORM
+with -m, count characters after NFC or NFD normalization (FORM is nfc or nfd), so accents count the same however the
+file encodes them; invalid UTF-8 counts as U+FFFD. Needs the normalize feature
+
+-L, --max-line-length
+print the display width of the longest line: tabs stop every --tab-width columns, wide (CJK, emoji) characters take
+two and combining marks none. The total is the longest of them all
+
+--show-longest[=content]
+with -L, also print where each file's longest line is as FILE:LINE on a line of its own, and with =content the
+start of the line too
+
+--eol=POLICY
+what ends a line for the line count: \n (lf, the default, and what every wc counts), only \r\n (crlf, so a \n inside
+a field of a Windows CSV export doesn't start a new line) or any of \n, \r\n and a lone \r (any, which also counts
+the lines of classic Mac files). Only the line count goes by it, -L and the other line columns still split at \n
+
+--tab-width=N
+tab stops for -L are N columns apart (default 8), to match what an editor shows. --whitespace-report looks at which
+indentation characters a line has and not at columns, so it's the same whatever N is
+
+--code
+print how many lines are blank, comments and code, with the comment syntax worked out from the file extension
+
+--unique-lines, --duplicate-lines
+print how many distinct lines each input has, or how many distinct lines appear more than once (sort -u | wc -l and
+sort | uniq -d | wc -l). Lines are compared byte for byte; the totals add up the per file counts
+
+--unique-words
+print how many distinct words each input has
+
+--approx
+estimate --unique-lines and --unique-words with a HyperLogLog sketch in a fixed 16K per input instead of keeping every
+line or word, printed as N±E where E covers 95% of cases. The totals are then distinct across all the inputs
+
+--numbers[=exclude]
+print how many words are numbers (-3, 1,000.5, 2.5e-3, 40%), and with =exclude leave them out of the word count
+
+--char-classes
+print how many of each input's characters are letters, digits, punctuation, whitespace and anything else, in that
+order. Letters and digits include the non-ascii ones, invalid UTF-8 counts as other
+
+--non-ascii
+print how many characters of each input are outside ascii
+
+--emoji
+print how many emoji each input has, one per pictograph whatever skin tone or variation selector follows it
+
+--whitespace-report
+print how many lines of each input end in spaces or tabs, and how many are indented with tabs, with spaces, and with
+a mix of both, in that order
+
+--reading-time[=WPM]
+print about how many minutes it takes to read each input, its words at WPM words a minute (default 200) rounded up.
+The total is for all the words together
+
+--plugin=PATH
+add a column counted by the shared library at PATH, see plugin.rs for what it has to export. Can be given more than
+once. Only when built with the plugins feature
+
+--word-policy=POLICY
+whether apostrophes and hyphens split words: joined (the default) counts "don't" and "well-known" as one word each,
+split-apostrophes, split-hyphens and split (both) count them as two. The typographic ’ and unicode hyphens count too
+
+--count-char=CHAR
+add a column counting CHAR, which is one character or one of the escapes \t, \n, \r, \0, \\ and \xNN (any byte).
+Can be given more than once, the columns come in the order they were given
+
+--blank-lines, --whitespace-only-lines
+add a column counting the lines with nothing on them, or with only spaces and tabs on them
+
+--count-lines-matching=PREFIX
+add a column counting the lines that start with PREFIX after their indentation, like # for comments. Can be given
+more than once, and the line columns come in the order they were given
+
+--entropy
+print the Shannon entropy of each input's bytes in bits per byte, from 0 to 8. Compressed and encrypted data is close
+to 8, text usually around 4 or 5
+
+--byte-histogram[=FORMAT]
+after each row print how often every byte value occurs in that input, as text (the default, only values that occur)
+or as a JSON line with all 256 counts
+
+--compress-ratio
+print roughly how well each input compresses: the deflated size of its first 1M as a fraction of the original. Needs
+the compression feature
+
+--hash=ALGORITHM
+print a sha256 or blake3 checksum of each input next to its counts, worked out in the same read. Totals get a -.
+Needs the hashes feature
+
+--detect-type
+add a type column saying what each input looks like from its first 8K (ascii, utf8, utf16le, utf16be, text in some
+other 8 bit encoding, binary or empty) and a language column with what its name or a #! line suggests it's written
+in, for telling why the counts of a file look off. "-" when there's no telling, and for totals
+
+--diff-input
+read unified diffs (e.g. from git diff) from the FILE operands or stdin and print the lines and words each one adds and
+removes per file, then the totals and how many files were touched
+
+--check
+read the output of an earlier run from the FILE operands (or stdin) and count every file listed in it again with the
+same options, printing OK or CHANGED for each. The exit status is 1 if anything changed or couldn't be read
+
 --help
-display this help and exit
+display this help and exit. It and the error messages are translated for the language LC_ALL, LC_MESSAGES or LANG
+(or LANGUAGE) asks for when there's a catalog for it in locales/, for now de
 
 --version
-output version information and exit
+output version information and exit: the version, the git commit and day it was built from, the target, the rustc
+that built it and the cargo features it has, everything a bug report needs
+
+--generate-man
+print a man page for wc_clone made from this list of options, for packagers (wc_clone --generate-man > wc_clone.1)
+
+--files-from=F
+read the input files from F, one name per line; - reads the list from stdin
+
+--files0-from=F
+same as --files-from but the names in F are separated by NUL characters
+
+--max-bytes=N
+stop reading each input after N bytes and count only that prefix
+
+--max-lines=N
+stop reading each input after N lines and count only that prefix
+
+--offset=N
+start reading each input N bytes in, e.g. at an offset reported by another tool
+
+--length=N
+count at most N bytes from the start offset; with either of these documents are counted as raw bytes
+
+--git=WHICH
+count the files git lists instead of the FILE operands, which narrow it down like pathspecs: tracked, staged,
+modified (changed but not staged) or diff REV (changed since the revision REV). Deleted files are left out
+
+-r, --recursive
+count the files under any directory operands instead of reporting an error for them. Hidden files and directories
+are left out unless --hidden is given, and so is whatever a .wcignore file (gitignore's syntax) in a directory on the
+way lists
+
+--include=GLOB, --exclude=GLOB
+with -r, only count files matching one of the --include globs and skip anything matching an --exclude glob (excluded
+directories aren't walked at all). Both can be given more than once; a glob with a / in it is matched against the
+path below the operand, otherwise against the name. * and ? don't match /, ** does
+
+--max-depth=N
+with -r, don't go more than N directories deep, 1 only counts the files directly inside each operand
+
+--newer-than=AGE, --older-than=AGE, --newer-than-file=PATH
+with -r, only count files last modified less (or more) than AGE ago, a number with s, m, h, d or w after it: 24h,
+1.5d, 2w. --newer-than-file counts the files modified after PATH was, like find -newer. Given together they pick the
+files in between
+
+--min-size=SIZE, --max-size=SIZE
+with -r, only count files of at least (or at most) SIZE bytes, K, M and G or KB, MB and GB after it like
+--buffer-size. Files outside them are left out from their metadata without being opened, so --max-size=10M keeps a
+scan of a source tree from reading the multi-gigabyte blobs someone checked in next to it
+
+--hidden
+with -r, count hidden files and walk hidden directories too
+
+--follow-symlinks
+with -r, follow symlinks to files and directories instead of skipping them. A link back to a directory that was
+already walked is reported as a filesystem loop rather than followed again
+
+--group-by=KEY
+print totals per file extension (ext) or per top level directory under each operand (dir) instead of a row per file;
+with -v the per file rows are printed too
+
+--dedupe=MODE
+count hard links to the same file (inode) or files with identical content (hash) only once in the totals; every
+input still gets its row, with a note saying which earlier input it duplicates. hash needs the hashes feature
+
+--skip-binary
+skip files that look binary (NUL bytes near the start) and report them as skipped, handy with -r
+
+--skip-empty
+leave the rows of empty (zero byte) files out, and say after the total how many there were. For trees full of
+placeholder files like .gitkeep and __init__.py
+
+--io=MODE
+how inputs are read: read streams them through a buffer, mmap maps them into memory, auto (the default) picks per
+file and skips reading entirely when only the byte count is needed
+
+--buffer-size=SIZE
+how much gets read at a time (default 64K), bigger buffers help on high latency network filesystems
+
+--max-memory=SIZE
+keep what's held in memory to about SIZE (at least 1M): read buffers shrink to fit, files bigger than SIZE are
+streamed instead of mapped, and --unique-lines or --unique-words on an input with more distinct items than fit switch
+to an estimate like --approx, with a warning
+
+--timeout=SECONDS
+give up on an input that's still being read after SECONDS (fractions are fine), report it as an error and go on with
+the next one. Meant for stalled FIFOs, pipes and slow network mounts
+
+--throttle=RATE
+read the files at no more than RATE altogether, in megabytes a second (--throttle=20) or with a unit like 512K or
+1G, so a big scan of a shared NFS mount or disk doesn't starve everything else using it. The -j jobs share it, and
+throttled files are read rather than mapped
+
+--idle
+run at the lowest CPU priority and in the idle IO class (ionice -c3 on Linux, the background policy on macOS and
+Windows), so a big background scan only gets the CPU and the disk when nothing else wants them
+
+--remote=[USER@]HOST:PATH
+count PATH on HOST, cat'ed over ssh and counted here. ssh has to get in without asking for a password (a key or an
+agent), and a relative PATH is from the home directory like scp's. Can be given more than once, and
+ssh://[USER@]HOST[:PORT]/PATH operands are read the same way
+
+--no-cache-pollution
+hint the kernel to drop the pages of each file once it's counted, so big scans don't evict everyone else's cache
+
+--auto
+pick how to count each file from its content instead of its name: PDFs, .odt documents and (named as one) .docx
+documents get their text counted, HTML pages the text without the markup, zip and tar archives their members and
+gzip, zstd and xz files what they decompress to, tarballs included. Anything else is counted as it is, whatever its
+extension says. The formats that need a feature are counted as bytes without it
+
+--no-decompress
+count stdin, FIFOs and remote inputs as they are even when they're gzip, zstd or xz compressed. Without it those are
+decompressed first (zstd and xz with the zstd and xz programs), so cat logs.gz | wc_clone -l counts the log's lines.
+Regular files are counted as they are unless --auto says otherwise, and with this so are .zip archives instead of
+their members, and whatever --auto would have decompressed or opened up
+
+-j, --jobs=N
+count up to N files at once, reading and counting on separate threads; defaults to the number of CPUs
+
+--strict
+with -m, treat input that isn't valid UTF-8 as an error: the file isn't counted and the exit status is 1
+
+--lossy
+with -m, count each invalid UTF-8 sequence as one character (U+FFFD) like a lossy decode would. Without either, -m
+counts the bytes that start a character and doesn't check anything
+
+--human, --si
+make the rows easier to read: bytes as 1.4MiB (powers of 1024) or, with --si, 1.5M (powers of 1000), and the other
+counts with thousands separators. Snapshots, --check and JSON output keep the exact numbers
+
+--group-digits
+print the counts with thousands separators, 1,234,567 or 1.234.567 and so on as LC_NUMERIC (or LC_ALL/LANG) has it
+
+--sort=name
+count (and print) the inputs in natural order, file2 before file10, the same on every platform and with any -j.
+Without it they're in the order given, -r walking each directory in byte order of the names
+
+--ordered, --unordered
+with -j, print the rows in the order the inputs were given (--ordered, the default), or each one as soon as its file
+is counted (--unordered) so a slow file doesn't hold the others' rows back. After a Ctrl-C --unordered only adds the
+partial total, the rows are already out
+
+--summary
+after the total, the min, max, mean, median and 95th percentile of each count over the files, e.g. the typical file
+size of a tree with -rc
+
+--output-format=FORMAT
+text (the default), json (an array with an object for each row), csv, html (a page with a table that sorts by
+any column, and the charts of --byte-histogram) or md (a markdown table for pasting into issues and PRs). With the arrow
+feature also parquet and arrow (Arrow IPC), for DuckDB, Polars and the like; those need --output. With all but text
+the messages about inputs that couldn't be counted go to stderr, except that json lists them after the rows as
+{"file", "error", "kind"} objects
+
+--run-info
+with --output-format=json or csv, end the report with what made it: the wc_clone version, the options, when the run
+started and finished (UTC) and the host. json gets a {"run": {...}} object after the rows, csv # comment lines
+
+--output=PATH
+write the report to PATH instead, the terminal only gets the messages about inputs that couldn't be counted, the last
+row and where the report went. sqlite:FILE (with the sqlite feature) appends every row of the run to the counts table
+of that database instead, with the run's ID and time
+
+--journal=PATH, --resume
+keep a journal of the run at PATH as it goes, every file's counts once it's counted and the total so far, and with
+--resume take the files an earlier run with the same options already counted from it instead of reading them again,
+for picking up a long -r scan where it stopped. Needs the serde feature
+
+--list-only
+print the inputs that would be counted instead of counting them, with -r the files it finds after --include,
+--exclude, --max-depth, the age and size filters and the .wcignore files had their say, and in --sort order.
+--skip-binary and --skip-empty look at the files as they're read, so the files they'd skip are still listed
+
+--print0
+end every line of the text output with a NUL instead of a newline and without the padding in front, so the rows can
+go to xargs -0 whatever the names have in them. The messages go to stderr instead
+
+--quote-names=STYLE
+how the text rows write the names of inputs: as they are (never, the default), quoted for a shell when they have
+spaces, quotes or control characters in them (shell, like GNU ls), or always in double quotes with C escapes (c-style),
+so a newline in a name can't pass for the end of a row
+
+--compat=STYLE
+print exactly what GNU (gnu) or BSD/macOS (bsd) wc would: their column widths, total line, error messages and
+isspace() word splitting, and with gnu the words and characters of its UTF-8 decoding, where bytes that don't decode
+are neither
+
+--posix
+behave exactly as POSIX specifies: words are split on isspace() (vertical tab and form feed too), documents are
+counted as raw bytes, counts are separated by a single space, and the options above that aren't in POSIX are refused
+
+-v, --verbose
+print extra detail, e.g. per-page counts for documents, and log each file's strategy and any skipped or unreadable
+files to stderr. -vv also logs file and chunk read timings, -vvv everything. Nothing is logged without the logging
+feature
+
+--log-format=FORMAT
+how -v logs: text (the default) or json, one object per line
 
 default is lines, chars, bytes....
+
+a FILE that's an http:// or https:// URL is downloaded and its body counted, after undoing any gzip or deflate
+Content-Encoding; https needs curl to be installed. With the cloud feature s3://bucket/key and gs://bucket/object are
+streamed through the aws and gcloud CLIs, with whatever credentials those find. With the archives feature a .zip is
+counted member by member, a row for each file in it (ARCHIVE:MEMBER) before the archive's own
+
+options can come before or after the FILEs (wc_clone notes.txt -l), "--" ends them so a file can start with a dash
+(wc_clone -- -notes.txt). With POSIXLY_CORRECT set, or --posix, the first FILE ends them instead
+
+an input that can't be counted doesn't stop the others: the errors are listed together after the total, how many
+and which, and the exit status is 1. With --compat they're printed between the rows to stderr like wc prints them
+
+sending SIGUSR1 (SIGINFO/Ctrl-T on BSD and macOS) while counting prints progress to stderr, Ctrl-C prints the results
+finished so far to stderr and exits with 130.
+
+wc_clone freq [OPTION]... [FILE]...
+print how often each word occurs, most frequent first
+    --top=N           only the N most frequent words
+    --format=FORMAT   text (the default), json or csv
+    --ignore-case     count "The" and "the" as the same word
+    --stopwords=FILE  leave out the words listed in FILE, "english" is a built-in list of common English words
+    --min-count=N     leave out words seen fewer than N times
+    --ngrams=N        count runs of N words (bigrams, trigrams...) instead of single words
+
+wc_clone snapshot [-o FILE] [OPTION]... FILE...
+save the counts of every FILE (as picked by the options above) to a JSON baseline in FILE, or print it
+
+wc_clone delta [--format=text|json] BASELINE [OPTION]... [FILE]...
+count the files in BASELINE again, or the FILEs given instead, and print how much each count grew or shrank per file
+and in total. Files that are new or gone since the baseline are marked as such, unchanged ones only show up with -v
+
+wc_clone serve [--listen=ADDR] [OPTION]...
+answer HTTP requests on ADDR (default 127.0.0.1:8080): POST /count counts the request body with the options given and
+sends back its row, GET /metrics has Prometheus metrics for bytes and inputs counted, errors and request latency.
+Needs the serve feature
+
+wc_clone selftest [--wc=PATH] [DIR]...
+count every file under each DIR (default .) with the system wc (or PATH) and with wc_clone --compat=gnu, with the usual
+options and through stdin, and print every line where they differ. The exit status is 1 if any did
+
+wc_clone bench FILE|--generate=SIZE [OPTION]...
+time counting FILE (or SIZE bytes of what generate writes) with every way of reading it: --io=read, a 1M --buffer-size,
+--io=mmap, --io=auto and -j across one copy per core, and print the best of 5 runs and the throughput of each
+
+wc_clone generate [--lines=N] [--avg-line-len=L] [--unicode] [--seed=S] [-o FILE]
+write N lines (default 1000) of random words, about L characters each (default 60), to FILE or stdout. --unicode mixes
+in accented, Greek, Cyrillic, CJK and emoji characters and combining accents; the same seed always writes the same text
+
+Features
+The default build has mmap, compression, hashes, normalize, logging and serve. --no-default-features builds just the
+counting, with none of their dependencies, and --features full turns on everything, cloud, pdf, office, archives,
+serde, plugins, sqlite and arrow included
 */
+mod adapters;
+mod archive;
+mod bench;
+mod cache;
+mod check;
+mod child;
+mod classes;
+mod cloud;
+mod code;
+#[cfg(feature = "arrow")]
+mod columnar;
+mod compat;
+mod counter;
+mod decompress;
+mod dedupe;
+mod diff;
+mod distinct;
+mod emoji;
+mod entropy;
+mod eol;
+mod filetype;
+mod freq;
+mod generate;
+mod git;
+mod hash;
+mod html;
+mod http;
+mod human;
+mod i18n;
+mod ignore;
+mod interrupt;
+mod journal;
+mod kernel;
+mod lineclass;
+mod logging;
+mod man;
+mod mime;
+mod normalize;
+mod numbers;
+mod occurrences;
+mod pipeline;
+mod plugin;
+pub mod prelude;
+mod priority;
+mod progress;
+mod quote;
+mod report;
+#[cfg(feature = "serve")]
+mod serve;
+mod selftest;
+mod snapshot;
+mod sort;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod ssh;
+mod strategy;
+mod stream;
+mod suggest;
+mod summary;
+mod throttle;
+mod timeout;
+mod utf8;
+mod walk;
+mod whitespace;
+mod width;
+mod winpath;
+mod words;
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::error::Error;
+use std::fmt;
 use std::fs;
-use std::io::{self, IsTerminal, Read};
+use std::io::{self, IsTerminal, Read, Write};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/*
+The public surface outside the prelude, for the crates that need more than counting a string or a reader. None of it
+is covered by the prelude's promise, a minor version can change it:
+
+- Counter and CloneCounter, for columns of your own through CommandOptions::register_counter
+- the kernel (count, count_posix, Counts, Kernel), the bare counts of a byte slice for fuzzing and property tests
+- Reporter, the reporters the binary has and ToFile, with what their methods get (Failure, RunInfo, Summary), for
+  run_on_files and run_from_stdin writing somewhere else or some other way
+*/
+pub(crate) use compat::Compat;
+pub use counter::{CloneCounter, Counter};
+pub use html::HtmlReporter;
+pub use kernel::{count, count_posix, Counts, Kernel};
+pub use report::{CsvReporter, Failure, JsonReporter, MarkdownReporter, Reporter, RunInfo, TextReporter, ToFile};
+use report::OutputFormat;
+use strategy::IoMode;
+pub use summary::Summary;
+use i18n::tr;
+use strategy::Strategy;
+use utf8::Utf8Mode;
+
+// how much of a file looks_binary checks for NUL bytes
+const BINARY_SNIFF_LEN: u64 = 8 * 1024;
+
+// the smallest --max-memory, and the smallest buffers it can shrink them to
+const MIN_MAX_MEMORY: usize = 1 << 20;
+const MIN_BUFFER_SIZE: usize = 4 * 1024;
+// for --reading-time without a value, what most reading time estimates go by
+const READING_WPM: u64 = 200;
 
 #[derive(Debug)] 
 pub struct CommandOptions {
@@ -32,25 +493,159 @@ pub struct CommandOptions {
     count_chars: bool,
     count_bytes: bool,
     count_lines: bool,
+    count_max_line_length: bool,
+    show_longest: bool,      // where the longest line is, for -L
+    show_longest_text: bool, // and what's on it
+    count_entropy: bool,
+    byte_histogram: Option<entropy::HistogramFormat>,
+    count_compress_ratio: bool,
+    count_code: bool,
+    count_unique_lines: bool,
+    count_duplicate_lines: bool,
+    count_unique_words: bool,
+    approx: bool,
+    numbers: Option<numbers::Mode>,
+    count_char_classes: bool,
+    count_non_ascii: bool,
+    count_emoji: bool,
+    count_whitespace: bool,
+    reading_wpm: Option<u64>, // words a minute, for --reading-time
+    count_chars_of: Vec<occurrences::Needle>,
+    count_lines_of: Vec<lineclass::LineClass>, // --blank-lines, --whitespace-only-lines and --count-lines-matching
+    word_policy: words::WordPolicy,
+    registry: counter::Registry, // counters from users of the library, see counter.rs
+    hash: Option<hash::Algorithm>,
+    detect_type: bool,
+    tab_width: usize,
+    verbosity: u8, // how many -v
+    log_format: logging::LogFormat,
+    human: Option<human::Units>, // --human/--si, for the native rows only
+    group_digits: Option<char>, // the thousands separator, with --group-digits or --human/--si
+    output: Option<String>, // --output, where the report goes instead of stdout
+    output_format: OutputFormat,
+    quote_names: quote::QuoteStyle, // for the names in text rows
+    print0: bool,
+    list_only: bool,
+    journal: Option<String>, // the file for --journal
+    resume: bool,
+    run_info: bool,
+    arguments: Vec<String>, // the options as given, for --run-info
+    summary: bool,
+    sort: Option<sort::SortKey>,
+    ordered: bool, // rows in input order, rather than as each file is done (--unordered)
+    help: bool,
+    version: bool,
+    generate_man: bool,
+    check: bool, // the operands are manifests to check rather than files to count
+    diff_input: bool, // or diffs to count the changes of
+    recursive: bool,
+    walk_options: walk::WalkOptions,
+    group_by: Option<walk::GroupBy>,
+    dedupe: Option<dedupe::Mode>,
+    skip_binary: bool,
+    skip_empty: bool,
     files: Vec<String>,
+    files_from: Option<String>, // the list the files were read from, if any
+    remotes: Vec<String>,       // the files that came from --remote, [USER@]HOST:PATH
+    git: Option<git::Selection>, // or which files git was asked for
+    read_limits: ReadLimits,
+    io_mode: IoMode,
+    utf8_mode: Utf8Mode, // always Fast unless chars are counted
+    eol: eol::Eol,
+    normalize: Option<normalize::Form>, // same
+    buffer_size: usize,
+    max_memory: Option<usize>,
+    timeout: Option<Duration>,
+    throttle: Option<u64>, // bytes a second
+    no_cache_pollution: bool,
+    idle: bool,
+    no_decompress: bool,
+    auto: bool,
+    posix: bool,
+    compat: Compat,
+    number_width: usize, // the --compat=gnu column width, set once the inputs are known
+    jobs: usize,
 }
 
-#[derive(Debug)]
-pub struct FileStats {
-    word_count: i32,
-    char_count: i32,
-    byte_count: i32,
-    line_count: i32,
+// How much of each input gets read, for sampling huge files where exact totals aren't needed...
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ReadLimits {
+    max_bytes: Option<usize>,
+    max_lines: Option<usize>,
+    offset: u64,
+    length: Option<u64>,
 }
 
+/*
+The counts for one input, or a total of them. Only what the options asked for gets counted, whatever wasn't is 0 (or
+None). Totals are made with + and sum(): counts add up, the longest line is the longest of them and the --approx
+sketches are merged so distinct counts stay distinct. With the serde feature they (de)serialize as is, sketches and
+histogram included, so a total can be picked up again later.
+*/
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileStats {
+    word_count: u64,
+    char_count: u64,
+    byte_count: u64,
+    line_count: u64,
+    max_line_length: u64,
+    longest_line: Option<u64>,    // for --show-longest, totals don't have one
+    longest_text: Option<String>, // --show-longest=content
+    #[cfg_attr(feature = "serde", serde(with = "entropy::histogram_serde"))]
+    byte_histogram: Option<Box<[u64; 256]>>, // for --entropy and --byte-histogram
+    sample_len: u64,                         // for --compress-ratio, how much was deflated
+    sample_compressed_len: u64,              // and what it came down to
+    blank_lines: u64,                        // for --code
+    comment_lines: u64,
+    code_lines: u64,
+    unique_lines: u64, // for --unique-lines, --duplicate-lines and --unique-words
+    duplicate_lines: u64,
+    unique_words: u64,
+    line_sketch: Option<Box<distinct::HyperLogLog>>, // with --approx
+    word_sketch: Option<Box<distinct::HyperLogLog>>,
+    numbers: u64, // for --numbers
+    char_classes: [u64; 5], // for --char-classes, indexed by the classes:: constants
+    non_ascii: u64, // for --non-ascii
+    emoji: u64,     // for --emoji
+    whitespace: [u64; 4], // for --whitespace-report, indexed by the whitespace:: constants
+    char_occurrences: Vec<u64>, // for --count-char, in the order they were given
+    line_classes: Vec<u64>,     // for --blank-lines and the like, in the order they were given
+    #[cfg_attr(feature = "serde", serde(skip))]
+    counters: Vec<Box<dyn Counter>>, // the registered ones, in the order they were registered
+    hash: Option<String>, // for --hash, totals don't have one
+    file_type: Option<String>, // for --detect-type, same
+    language: Option<String>,
+}
 
 
-pub enum ReadResult {
+// the first three only come from the document and archive adapters, builds without them never make those
+pub(crate) enum ReadResult {
+    #[cfg_attr(not(feature = "office"), allow(dead_code))]
     Utf8(String),
-    Binary(Vec<u8>),
-    ReadError(Box<dyn Error>)
+    #[cfg_attr(not(feature = "pdf"), allow(dead_code))]
+    Pages(Vec<String>),
+    #[cfg_attr(not(feature = "archives"), allow(dead_code))]
+    Members(Vec<(String, FileStats)>), // an archive's, named and already counted
+    Counted { stats: Box<FileStats>, invalid_utf8_at: Option<u64> }, // already counted by the streaming reader
+    SkippedBinary, // --skip-binary left it alone
+    Interrupted,   // Ctrl-C came before it was done
+    ReadError(Box<dyn Error + Send + Sync>)
 }
 
+// every long option build knows, for suggesting one when it's given something it doesn't
+const LONG_OPTIONS: &[&str] = &[
+    "--approx", "--auto", "--blank-lines", "--buffer-size", "--byte-histogram", "--bytes", "--char-classes", "--chars", "--check", "--code",
+    "--compat", "--compress-ratio", "--count-char", "--count-lines-matching", "--dedupe", "--detect-type", "--diff-input", "--duplicate-lines", "--emoji",
+    "--entropy", "--eol", "--exclude", "--files-from", "--files0-from", "--follow-symlinks", "--generate-man", "--git", "--group-by",
+    "--group-digits", "--hash", "--help", "--hidden", "--human", "--idle", "--include", "--io", "--jobs", "--journal", "--length", "--lines", "--list-only",
+    "--log-format", "--lossy", "--max-bytes", "--max-depth", "--max-line-length", "--max-lines", "--max-memory", "--max-size", "--min-size", "--newer-than", "--newer-than-file",
+    "--no-cache-pollution", "--no-decompress", "--non-ascii", "--normalize", "--numbers", "--offset", "--older-than", "--ordered", "--output",
+    "--output-format", "--plugin", "--posix", "--print0", "--quote-names", "--reading-time", "--recursive", "--remote", "--resume", "--run-info", "--show-longest", "--si", "--skip-binary", "--skip-empty", "--sort",
+    "--strict", "--summary", "--tab-width", "--throttle", "--timeout", "--unique-lines", "--unique-words", "--unordered", "--verbose", "--version",
+    "--whitespace-only-lines", "--whitespace-report", "--word-policy", "--words",
+];
+
 impl CommandOptions {
     fn new() -> Self {
         Self {
@@ -58,7 +653,78 @@ impl CommandOptions {
             count_chars: false,
             count_words: false,
             count_lines: false,
-            files: Vec::new()
+            count_max_line_length: false,
+            show_longest: false,
+            show_longest_text: false,
+            count_entropy: false,
+            byte_histogram: None,
+            count_compress_ratio: false,
+            count_code: false,
+            count_unique_lines: false,
+            count_duplicate_lines: false,
+            count_unique_words: false,
+            approx: false,
+            numbers: None,
+            count_char_classes: false,
+            count_non_ascii: false,
+            count_emoji: false,
+            count_whitespace: false,
+            reading_wpm: None,
+            count_chars_of: Vec::new(),
+            count_lines_of: Vec::new(),
+            word_policy: words::WordPolicy::Joined,
+            registry: counter::Registry::default(),
+            hash: None,
+            detect_type: false,
+            tab_width: width::TAB_WIDTH,
+            verbosity: 0,
+            log_format: logging::LogFormat::Text,
+            human: None,
+            group_digits: None,
+            output: None,
+            output_format: OutputFormat::Text,
+            quote_names: quote::QuoteStyle::Never,
+            print0: false,
+            list_only: false,
+            journal: None,
+            resume: false,
+            run_info: false,
+            arguments: Vec::new(),
+            summary: false,
+            sort: None,
+            ordered: true,
+            help: false,
+            version: false,
+            generate_man: false,
+            check: false,
+            diff_input: false,
+            recursive: false,
+            walk_options: walk::WalkOptions::default(),
+            group_by: None,
+            dedupe: None,
+            skip_binary: false,
+            skip_empty: false,
+            files: Vec::new(),
+            files_from: None,
+            remotes: Vec::new(),
+            git: None,
+            read_limits: ReadLimits::default(),
+            io_mode: IoMode::Auto,
+            utf8_mode: Utf8Mode::Fast,
+            eol: eol::Eol::Lf,
+            normalize: None,
+            buffer_size: stream::CHUNK_SIZE,
+            max_memory: None,
+            timeout: None,
+            throttle: None,
+            no_cache_pollution: false,
+            idle: false,
+            no_decompress: false,
+            auto: false,
+            posix: false,
+            compat: Compat::Native,
+            number_width: 1,
+            jobs: thread::available_parallelism().map_or(1, |jobs| jobs.get())
         }
     }
 
@@ -68,29 +734,361 @@ impl CommandOptions {
         let mut built_commands = CommandOptions::new();
 
         let mut use_default_options = true;
-        // should parse the command line arguments consuing the arguments and updating the "built_commands" until it reaches 
-        // an argument that doesn't start with "-" or "--"... This needs to be reworked so it cna handle multiple flags in one i.e., "-clm"
+        let mut extension: Option<String> = None; // first option used that changes results beyond what POSIX has
+        // options and operands can come in any order like GNU wc takes them (wc file -l), until a "--" says everything
+        // after it is a file even if it starts with a dash. POSIXLY_CORRECT (or --posix) stops at the first operand instead
+        let options_anywhere = env::var_os("POSIXLY_CORRECT").is_none();
         while let Some(s) = argv.next() {
-            if s.starts_with("-") {
+            if s == "--" {
+                built_commands.files.append(&mut argv.collect());
+                break;
+            }
+            // a lone - is stdin, an operand like any file
+            if let Some(short_flags) = s.strip_prefix('-').filter(|_| s != "-") {
+                built_commands.arguments.push(s.clone());
+                let (flag, inline_value) = match s.split_once('=') {
+                    Some((flag, value)) => (flag, Some(value.to_string())),
+                    None if s.starts_with("-j") && s.len() > 2 => ("-j", Some(s[2..].to_string())), // -j4
+                    None => (s.as_str(), None),
+                };
+
+                // the value is optional for these, so they can't go with the others
+                if flag == "--byte-histogram" {
+                    let format = inline_value.as_deref().map_or(Ok(entropy::HistogramFormat::Text), entropy::HistogramFormat::parse)?;
+                    built_commands.byte_histogram = Some(format);
+                    extension.get_or_insert(flag.to_string());
+                    continue;
+                }
+                if flag == "--numbers" {
+                    built_commands.numbers = Some(numbers::Mode::parse(inline_value.as_deref())?);
+                    extension.get_or_insert(flag.to_string());
+                    use_default_options = false;
+                    continue;
+                }
+                if flag == "--reading-time" {
+                    built_commands.reading_wpm = Some(match inline_value.as_deref().map(|value| (value, value.parse::<u64>())) {
+                        None => READING_WPM,
+                        Some((_, Ok(wpm))) if wpm > 0 => wpm,
+                        Some((value, _)) => return Err(format!("invalid value for '--reading-time': '{}' (expected words per minute)", value)),
+                    });
+                    extension.get_or_insert(flag.to_string());
+                    use_default_options = false;
+                    continue;
+                }
+                if flag == "--show-longest" {
+                    match inline_value.as_deref() {
+                        None => {},
+                        Some("content") => built_commands.show_longest_text = true,
+                        Some(value) => return Err(format!("invalid value for '--show-longest': '{}' (expected content)", value)),
+                    }
+                    built_commands.show_longest = true;
+                    extension.get_or_insert(flag.to_string());
+                    continue;
+                }
+
+                if matches!(flag, "--files-from" | "--files0-from" | "--max-bytes" | "--max-lines" | "--offset" | "--length" | "--io" | "--buffer-size" | "--max-memory" | "--timeout" | "--throttle" | "--remote" | "--compat" | "--eol" | "--tab-width" | "--count-char" | "--count-lines-matching" | "--plugin" | "--word-policy" | "--log-format" | "--output" | "--output-format" | "--quote-names" | "--journal" | "--sort" | "--normalize" | "--group-by" | "--dedupe" | "--hash" | "--git" | "--include" | "--exclude" | "--max-depth" | "--newer-than" | "--older-than" | "--newer-than-file" | "--min-size" | "--max-size" | "--jobs" | "-j") {
+                    let value = match inline_value.or_else(|| argv.next().inspect(|value| built_commands.arguments.push(value.clone()))) {
+                        Some(value) => value,
+                        None => return Err(tr!("option '{}' requires an argument", flag)),
+                    };
+
+                    if !matches!(flag, "--io" | "--buffer-size" | "--timeout" | "--throttle" | "--log-format" | "--jobs" | "-j") {
+                        extension.get_or_insert(flag.to_string());
+                    }
+
+                    match flag {
+                        "--max-bytes" => built_commands.read_limits.max_bytes = Some(parse_count(flag, &value)?),
+                        "--max-lines" => built_commands.read_limits.max_lines = Some(parse_count(flag, &value)?),
+                        "--offset" => built_commands.read_limits.offset = parse_count(flag, &value)? as u64,
+                        "--length" => built_commands.read_limits.length = Some(parse_count(flag, &value)? as u64),
+                        "--io" => built_commands.io_mode = IoMode::parse(&value)?,
+                        "--buffer-size" => built_commands.buffer_size = parse_size(flag, &value)?.max(1),
+                        "--timeout" => built_commands.timeout = Some(timeout::parse(&value)?),
+                        "--throttle" => built_commands.throttle = Some(throttle::parse(&value)?),
+                        "--remote" => {
+                            ssh::check(&value)?;
+                            built_commands.files.push(value.clone());
+                            built_commands.remotes.push(value);
+                        },
+                        "--max-memory" => match parse_size(flag, &value)? {
+                            size if size >= MIN_MAX_MEMORY => built_commands.max_memory = Some(size),
+                            _ => return Err(format!("invalid value for '--max-memory': '{}' (expected at least 1M)", value)),
+                        },
+                        "--compat" => built_commands.compat = Compat::parse(&value)?,
+                        "--include" => built_commands.walk_options.include.push(value),
+                        "--exclude" => built_commands.walk_options.exclude.push(value),
+                        "--max-depth" => built_commands.walk_options.max_depth = Some(parse_count(flag, &value)?),
+                        "--newer-than" => built_commands.walk_options.newer_than = Some(walk::parse_age(flag, &value)?),
+                        "--older-than" => built_commands.walk_options.older_than = Some(walk::parse_age(flag, &value)?),
+                        "--newer-than-file" => built_commands.walk_options.newer_than = Some(walk::modified(&value)?),
+                        "--min-size" => built_commands.walk_options.min_size = Some(parse_size(flag, &value)? as u64),
+                        "--max-size" => built_commands.walk_options.max_size = Some(parse_size(flag, &value)? as u64),
+                        "--group-by" => built_commands.group_by = Some(walk::GroupBy::parse(&value)?),
+                        "--dedupe" => built_commands.dedupe = Some(dedupe::Mode::parse(&value)?),
+                        "--hash" => built_commands.hash = Some(hash::Algorithm::parse(&value)?),
+                        "--git" => built_commands.git = Some(git::Selection::parse(&value, || argv.next())?),
+                        "--normalize" => built_commands.normalize = Some(normalize::Form::parse(&value)?),
+                        "--count-char" => {
+                            built_commands.count_chars_of.push(occurrences::Needle::parse(&value)?);
+                            use_default_options = false; // the only ones of these that are counters
+                        },
+                        "--count-lines-matching" => {
+                            built_commands.count_lines_of.push(lineclass::LineClass::prefix(&value)?);
+                            use_default_options = false;
+                        },
+                        "--plugin" => {
+                            plugin::load(&value, &mut built_commands)?;
+                            use_default_options = false;
+                        },
+                        "--word-policy" => built_commands.word_policy = words::WordPolicy::parse(&value)?,
+                        "--log-format" => built_commands.log_format = logging::LogFormat::parse(&value)?,
+                        "--output" => {
+                            if value.starts_with("sqlite:") && !cfg!(feature = "sqlite") {
+                                return Err(format!("can't write '{}': wc_clone was built without the sqlite feature", value));
+                            }
+                            built_commands.output = Some(value);
+                        },
+                        "--output-format" => built_commands.output_format = OutputFormat::parse(&value)?,
+                        "--quote-names" => built_commands.quote_names = quote::QuoteStyle::parse(&value)?,
+                        "--journal" => {
+                            if !cfg!(feature = "serde") {
+                                return Err(String::from("can't use '--journal': wc_clone was built without the serde feature"));
+                            }
+                            built_commands.journal = Some(value);
+                        },
+                        "--sort" => built_commands.sort = Some(sort::SortKey::parse(&value)?),
+                        "--tab-width" => built_commands.tab_width = parse_count(flag, &value)?.max(1),
+                        "--eol" => built_commands.eol = eol::Eol::parse(&value)?,
+                        "--jobs" | "-j" => built_commands.jobs = parse_count(flag, &value)?.max(1),
+                        _ => {
+                            let delimiter = if flag == "--files0-from" { b'\0' } else { b'\n' };
+                            built_commands.files.append(&mut read_file_list(&value, delimiter)?);
+                            built_commands.files_from = Some(value);
+                        }
+                    }
+                    continue;
+                }
+
+                // -v, -vv, -rv...: a cluster of nothing but -r and -v picks no counter either
+                let only_r_and_v = !short_flags.is_empty() && short_flags.bytes().all(|c| matches!(c, b'r' | b'v'));
+                let verbosity = if s == "--verbose" { 1 } else if only_r_and_v { short_flags.matches('v').count() } else { 0 };
+                if verbosity > 0 || only_r_and_v || matches!(s.as_str(), "--recursive" | "--skip-binary" | "--skip-empty" | "--hidden" | "--follow-symlinks" | "--check" | "--diff-input" | "--approx" | "--strict"
+                    | "--lossy" | "--human" | "--si" | "--group-digits" | "--summary" | "--ordered" | "--unordered" | "--print0"
+                    | "--list-only" | "--resume" | "--detect-type" | "--auto") {
+                    extension.get_or_insert(s.clone());
+                }
+
+                // these don't pick a counter, so keep the defaults
+                match s.as_str() {
+                    _ if verbosity > 0 || only_r_and_v => {
+                        built_commands.verbosity = built_commands.verbosity.saturating_add(u8::try_from(verbosity).unwrap_or(u8::MAX));
+                        built_commands.recursive |= only_r_and_v && short_flags.contains('r');
+                        continue;
+                    },
+                    "--recursive" => {
+                        built_commands.recursive = true;
+                        continue;
+                    },
+                    "--skip-binary" => {
+                        built_commands.skip_binary = true;
+                        continue;
+                    },
+                    "--skip-empty" => {
+                        built_commands.skip_empty = true;
+                        continue;
+                    },
+                    "--human" | "--si" => {
+                        let units = if s == "--si" { human::Units::Si } else { human::Units::Binary };
+                        if built_commands.human.is_some_and(|other| other != units) {
+                            return Err(String::from("--human and --si can't be used together"));
+                        }
+                        built_commands.human = Some(units);
+                        built_commands.group_digits = Some(human::locale_separator());
+                        continue;
+                    },
+                    "--group-digits" => {
+                        built_commands.group_digits = Some(human::locale_separator());
+                        continue;
+                    },
+                    "--summary" => {
+                        built_commands.summary = true;
+                        continue;
+                    },
+                    "--ordered" | "--unordered" => {
+                        built_commands.ordered = s == "--ordered";
+                        continue;
+                    },
+                    "--help" => {
+                        built_commands.help = true;
+                        continue;
+                    },
+                    "--version" => {
+                        built_commands.version = true;
+                        continue;
+                    },
+                    "--generate-man" => {
+                        built_commands.generate_man = true;
+                        continue;
+                    },
+                    "--approx" => {
+                        built_commands.approx = true;
+                        continue;
+                    },
+                    "--diff-input" => {
+                        built_commands.diff_input = true;
+                        continue;
+                    },
+                    "--check" => {
+                        built_commands.check = true;
+                        continue;
+                    },
+                    "--hidden" => {
+                        built_commands.walk_options.hidden = true;
+                        continue;
+                    },
+                    "--follow-symlinks" => {
+                        built_commands.walk_options.follow_symlinks = true;
+                        continue;
+                    },
+                    "--posix" => {
+                        built_commands.posix = true;
+                        continue;
+                    },
+                    "--no-cache-pollution" => {
+                        built_commands.no_cache_pollution = true;
+                        continue;
+                    },
+                    "--idle" => {
+                        built_commands.idle = true;
+                        continue;
+                    },
+                    "--no-decompress" => {
+                        built_commands.no_decompress = true;
+                        continue;
+                    },
+                    "--auto" => {
+                        built_commands.auto = true;
+                        continue;
+                    },
+                    "--run-info" => {
+                        built_commands.run_info = true;
+                        continue;
+                    },
+                    "--print0" => {
+                        built_commands.print0 = true;
+                        continue;
+                    },
+                    "--list-only" => {
+                        built_commands.list_only = true;
+                        continue;
+                    },
+                    "--resume" => {
+                        built_commands.resume = true;
+                        continue;
+                    },
+                    // a column, but not a count, so the default ones stay
+                    "--detect-type" => {
+                        built_commands.detect_type = true;
+                        continue;
+                    },
+                    "--strict" | "--lossy" => {
+                        let utf8_mode = if s == "--strict" { Utf8Mode::Strict } else { Utf8Mode::Lossy };
+                        if built_commands.utf8_mode != Utf8Mode::Fast && built_commands.utf8_mode != utf8_mode {
+                            return Err(String::from("--strict and --lossy can't be used together"));
+                        }
+                        built_commands.utf8_mode = utf8_mode;
+                        continue;
+                    },
+                    _ => {}
+                }
+
                 use_default_options = false; // make sure to
                 match s.as_str() {
                     "--bytes" => built_commands.count_bytes = true,
                     "--chars" => built_commands.count_chars = true,
                     "--words" => built_commands.count_words = true,
                     "--lines" => built_commands.count_lines = true,
+                    "--entropy" => {
+                        built_commands.count_entropy = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--code" => {
+                        built_commands.count_code = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--unique-lines" => {
+                        built_commands.count_unique_lines = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--duplicate-lines" => {
+                        built_commands.count_duplicate_lines = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--char-classes" => {
+                        built_commands.count_char_classes = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--non-ascii" => {
+                        built_commands.count_non_ascii = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--emoji" => {
+                        built_commands.count_emoji = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--blank-lines" | "--whitespace-only-lines" => {
+                        let class = if s == "--blank-lines" { lineclass::LineClass::Blank } else { lineclass::LineClass::WhitespaceOnly };
+                        if !built_commands.count_lines_of.contains(&class) {
+                            built_commands.count_lines_of.push(class);
+                        }
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--whitespace-report" => {
+                        built_commands.count_whitespace = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--unique-words" => {
+                        built_commands.count_unique_words = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--compress-ratio" => {
+                        if !cfg!(feature = "compression") {
+                            return Err(String::from("can't use '--compress-ratio': wc_clone was built without the compression feature"));
+                        }
+                        built_commands.count_compress_ratio = true;
+                        extension.get_or_insert(s.clone());
+                    },
+                    "--max-line-length" => {
+                        built_commands.count_max_line_length = true;
+                        extension.get_or_insert(s.clone());
+                    },
                     _ => {
-                        for c in s[1..].chars() {
+                        for c in short_flags.chars() {
                             match c {
                                 'c' => built_commands.count_bytes = true,
                                 'm' => built_commands.count_chars = true,
                                 'w' => built_commands.count_words = true,
                                 'l' => built_commands.count_lines = true,
-                                _ => return Err(format!("Recieved unsupported option: {}", s))
+                                'L' => {
+                                    built_commands.count_max_line_length = true;
+                                    extension.get_or_insert(String::from("-L"));
+                                },
+                                'v' => {
+                                    built_commands.verbosity = built_commands.verbosity.saturating_add(1);
+                                    extension.get_or_insert(String::from("-v"));
+                                },
+                                'r' => {
+                                    built_commands.recursive = true;
+                                    extension.get_or_insert(String::from("-r"));
+                                },
+                                _ => return Err(suggest::unsupported(&s, LONG_OPTIONS))
                             };
                         }
                     }
             
                 }
+            } else if options_anywhere && !built_commands.posix {
+                built_commands.files.push(s);
             } else {
                 built_commands.files.push(s);
                 built_commands.files.append(&mut argv.collect());
@@ -103,178 +1101,1526 @@ impl CommandOptions {
             built_commands.count_words = true;
             built_commands.count_bytes = true;
         }
-
-        if built_commands.files.len() < 1 {
-            return Err(String::from("No files spcified..."));
+        if !built_commands.count_chars {
+            built_commands.utf8_mode = Utf8Mode::Fast; // nothing to decode for
+            built_commands.normalize = None;
+        }
+        if built_commands.show_longest && !built_commands.count_max_line_length {
+            return Err(String::from("--show-longest only works together with -L"));
+        }
+        if let Some(selection) = &built_commands.git {
+            built_commands.files = git::list_files(selection, &built_commands.files)?;
+        }
+        built_commands.files = built_commands.files.into_iter().map(winpath::operand).collect();
+        if built_commands.run_info && !matches!(built_commands.output_format, OutputFormat::Json | OutputFormat::Csv) {
+            return Err(String::from("--run-info only works with --output-format=json or csv"));
+        }
+        if built_commands.print0 && (built_commands.output_format != OutputFormat::Text || built_commands.compat != Compat::Native) {
+            return Err(String::from("--print0 only works with the native text output, not --output-format or --compat"));
+        }
+        if built_commands.resume && built_commands.journal.is_none() {
+            return Err(String::from("--resume needs the --journal to resume from"));
+        }
+        if built_commands.journal.is_some() && !built_commands.registry.is_empty() {
+            return Err(String::from("--journal can't keep the counts of --plugin columns"));
+        }
+        if built_commands.list_only && built_commands.output_format != OutputFormat::Text {
+            return Err(String::from("--list-only prints names, not a report, so it can't be used with --output-format"));
+        }
+        if built_commands.throttle.is_some() && built_commands.io_mode == IoMode::Mmap {
+            return Err(String::from("--throttle paces the reads of the files, it can't be used with --io=mmap"));
+        }
+        if built_commands.output_format.is_binary() && built_commands.output.is_none() {
+            return Err(String::from("--output-format=parquet and arrow only go to a file, give one with --output"));
+        }
+        if (built_commands.human.is_some() || built_commands.group_digits.is_some()) && built_commands.compat != Compat::Native {
+            return Err(String::from("--human, --si and --group-digits can't be used with --compat, it prints exactly what that wc would"));
+        }
+        if let Some(max_memory) = built_commands.max_memory {
+            // the read buffers get a quarter of it, the --unique-* sets the rest
+            let most = max_memory / 4 / (built_commands.jobs.max(1) * pipeline::BUFFERS_PER_JOB);
+            built_commands.buffer_size = built_commands.buffer_size.min(most.max(MIN_BUFFER_SIZE));
+        }
+        if let (true, Some(flag)) = (built_commands.posix, extension) {
+            return Err(tr!("'{}' isn't part of POSIX wc and can't be used with --posix", flag));
         }
 
         Ok(built_commands)
     }
-}
 
-impl FileStats {
-    fn new() -> Self {
-        Self {
-            word_count: 0,
-            char_count: 0,
-            byte_count: 0,
-            line_count: 0,
-        }
+    // how many numbers each row has
+    fn counters(&self) -> usize {
+        [
+            self.count_lines,
+            self.count_words,
+            self.count_chars,
+            self.count_bytes,
+            self.count_max_line_length,
+            self.count_entropy,
+            self.count_compress_ratio,
+            self.count_code,
+            self.count_code, // three columns
+            self.count_code,
+            self.count_unique_lines,
+            self.count_duplicate_lines,
+            self.count_unique_words,
+            self.numbers.is_some(),
+            self.count_char_classes,
+            self.count_char_classes, // five columns
+            self.count_char_classes,
+            self.count_char_classes,
+            self.count_char_classes,
+            self.count_non_ascii,
+            self.count_emoji,
+            self.count_whitespace,
+            self.count_whitespace, // four columns
+            self.count_whitespace,
+            self.count_whitespace,
+            self.reading_wpm.is_some(),
+            self.hash.is_some(),
+            self.detect_type,
+            self.detect_type, // two columns
+        ]
+        .iter()
+        .filter(|counter| **counter)
+        .count()
+            + self.count_chars_of.len()
+            + self.count_lines_of.len()
+            + self.registry.len()
     }
 
-    fn add(&mut self, other: &FileStats) {
-        self.word_count += other.word_count;
-        self.char_count += other.char_count;
-        self.byte_count += other.byte_count;
-        self.line_count += other.line_count;
+    /*
+    Adds a column of counts from outside the crate. factory makes the counter for each input, and name is what --check
+    and snapshots call the column.
+    */
+    pub fn register_counter(&mut self, name: impl Into<String>, factory: impl Fn() -> Box<dyn Counter> + Send + Sync + 'static) {
+        self.registry.register(name.into(), Box::new(factory));
     }
-}
-// Main "run" programs either reads from stdin (if TTY), else will parse command options an execute on file's from options...
-pub fn run() {
-    if io::stdin().lock().is_terminal() {
-        run_from_term();
-    } else {
-        run_from_stdin();
+
+    // the sets (or sketches) behind --unique-lines/--duplicate-lines and --unique-words, None when there's nothing to count
+    fn distinct_lines(&self, path: &str) -> Option<distinct::Distinct> {
+        let exact = self.count_duplicate_lines || (self.count_unique_lines && !self.approx);
+        let approx = self.count_unique_lines && self.approx;
+        (exact || approx).then(|| self.capped(distinct::Distinct::new(None, exact, approx), self.count_duplicate_lines, path))
     }
-}
 
-// reads from stdin and then applies stat logic either on an in memory string or a raw buffer.
-pub fn run_from_stdin() {
-    // read stdin to a string, on failure default to 
-    let mut stdin = io::stdin();
-    let mut buffer: Vec<u8> = Vec::new();
-    let mut default_options = CommandOptions::new();
-    
-    default_options.count_lines = true;
-    default_options.count_words = true;
-    default_options.count_bytes = true;
+    fn distinct_words(&self, path: &str) -> Option<distinct::Distinct> {
+        self.count_unique_words.then(|| self.capped(distinct::Distinct::new(Some(self.separators()), !self.approx, self.approx), false, path))
+    }
 
-    if let Ok(_) = stdin.read_to_end(&mut buffer) {
-        let stats = match std::str::from_utf8(&buffer) {
-            Ok(s) => get_stats(&s),
-            Err(_) => get_stats_bin(&buffer)
+    // with --max-memory every set being filled at once (one of each per job) gets an even share of what the buffers leave
+    fn capped(&self, distinct: distinct::Distinct, duplicates: bool, path: &str) -> distinct::Distinct {
+        let Some(max_memory) = self.max_memory else {
+            return distinct;
         };
-    
-        print_run_results(&default_options, &stats, "");
+        let sets = usize::from(self.count_unique_lines || self.count_duplicate_lines) + usize::from(self.count_unique_words);
+        let share = max_memory / 4 * 3 / (self.jobs.max(1) * sets.max(1));
+        distinct.with_max_memory(share, duplicates, if path.is_empty() { "stdin" } else { path })
     }
-}
 
-pub fn run_from_term() {
-    match CommandOptions::build(env::args()) {
-        Ok(mut command_options) => {
-            let mut all_stats: Vec<(FileStats, &str)> = Vec::new();
-            let mut aggregated_stats = FileStats::new();
-
-            for file in &command_options.files {
-                match read_file(&file) {
-                    ReadResult::Utf8(utf8) => { 
-                        let file_stats = get_stats(&utf8);
-                        all_stats.push((file_stats, file));
-                    },
-                    ReadResult::Binary(bin) => { 
-                        // this is very simple and probably incorrect but enough for now, this is a learning exercise :).
-                        if command_options.count_chars {
-                            println!("wc_clone: {} Illegal byte sequence", file); 
-                            command_options.count_chars = false;
-                        }
-                        let file_stats = get_stats_bin(&bin);
-                        all_stats.push((file_stats, file));
-                    },
-                    ReadResult::ReadError(err) => {
-                        println!("Encounted error reading file {}: {}", file, err)
-                    }
-                }
-            }
+    // --non-ascii and --emoji come out of the same decoding
+    fn counts_emoji(&self) -> bool {
+        self.count_non_ascii || self.count_emoji
+    }
 
-            for (stats, topic) in &all_stats {
-                aggregated_stats.add(&stats);
-                print_run_results(&command_options, &stats, topic)
-            }
+    fn needs_histogram(&self) -> bool {
+        self.count_entropy || self.byte_histogram.is_some()
+    }
 
-            if all_stats.len() > 1 {
-                print_run_results(&command_options, &aggregated_stats, "total")
-            }
-        },
+    // the other formats have quoting of their own
+    fn shown_name<'n>(&self, name: &'n str) -> Cow<'n, str> {
+        match self.output_format {
+            OutputFormat::Text => self.quote_names.quote(name),
+            _ => Cow::Borrowed(name),
+        }
+    }
 
-        Err(err) => println!("{}", err)
-    };
+    // the platform wcs split on isspace() too
+    fn separators(&self) -> &'static stream::Separators {
+        self.word_policy.separators(self.posix || self.compat != Compat::Native)
+    }
+}
+
+fn parse_count(flag: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("invalid number for '{}': '{}'", flag, value))
 }
 
 /*
-Reads a file as utf8 and falls back to processing as byte vec if unable to parse as valid utf8..
+Sizes take an optional suffix: K, M and G (or KiB, MiB, GiB) are powers of 1024, KB, MB and GB powers of 1000.
 */
-pub fn read_file(path: &str) -> ReadResult {
-    match fs::read_to_string(path) {
-        Ok(utf8_file) => ReadResult::Utf8(utf8_file),
-        Err(io_err) => {
-            if io_err.kind() == io::ErrorKind::InvalidData {
-                match fs::read(path) {
-                    Ok(binary_file) => ReadResult::Binary(binary_file),
-                    Err(err) => ReadResult::ReadError(Box::new(err)),
-                }
-            } else {
-                ReadResult::ReadError(Box::new(io_err))
-            }
-        }
-    }
+fn parse_size(flag: &str, value: &str) -> Result<usize, String> {
+    let digits_end = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, suffix) = value.split_at(digits_end);
+
+    let multiplier: usize = match suffix {
+        "" => 1,
+        "K" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        _ => return Err(format!("invalid size for '{}': '{}'", flag, value)),
+    };
+
+    parse_count(flag, number)?
+        .checked_mul(multiplier)
+        .ok_or(format!("size for '{}' is too large: '{}'", flag, value))
 }
 
 /*
-Same as utf8 implementation, only it operates on binary directly...
+Reads the names listed in a --files-from/--files0-from list, "-" reads the list from stdin. Empty entries are skipped and
+newline lists tolerate CRLF endings since that's what a lot of ad-hoc scripts produce...
 */
-fn get_stats(file_content: &str) -> FileStats {
-    let mut run_results = FileStats::new();
+fn read_file_list(path: &str, delimiter: u8) -> Result<Vec<String>, String> {
+    let mut contents: Vec<u8> = Vec::new();
+    let read_result = if path == "-" {
+        io::stdin().read_to_end(&mut contents)
+    } else {
+        fs::File::open(path).and_then(|mut list| list.read_to_end(&mut contents))
+    };
 
-    run_results.byte_count = file_content.len() as i32;
-    let mut in_word = false; // Keep track if we're inside a word
+    if let Err(err) = read_result {
+        return Err(format!("cannot open '{}' for reading: {}", path, err));
+    }
 
-    for c in file_content.chars() {
-        run_results.char_count += 1;
+    Ok(contents
+        .split(|byte| *byte == delimiter)
+        .map(|name| if delimiter == b'\n' { name.strip_suffix(b"\r").unwrap_or(name) } else { name })
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect())
+}
 
-        if c == '\n' {
-            run_results.line_count += 1;
-            if in_word {
-                run_results.word_count += 1;
-                in_word = false;
-            }
-        } else if c == ' ' || c == '\t' || c == '\r' {
-            if in_word {
-                run_results.word_count += 1;
-                in_word = false;
-            }
-        } else {
-            in_word = true;
+impl ReadLimits {
+    // a byte range points into the file itself, so it only makes sense on the raw bytes
+    fn is_range(&self) -> bool {
+        self.offset > 0 || self.length.is_some()
+    }
+
+    // the most bytes we read from after the offset, if there's a limit on that at all
+    fn byte_cap(&self) -> Option<u64> {
+        match (self.max_bytes, self.length) {
+            (Some(max_bytes), Some(length)) => Some((max_bytes as u64).min(length)),
+            (max_bytes, length) => max_bytes.map(|max| max as u64).or(length),
         }
     }
 
-    // Check if the last word continues to the end of the content
-    if in_word {
-        run_results.word_count += 1;
+    /*
+    Length of the longest prefix of content inside the limits, a line limit keeps the newline ending the last line.
+    */
+    fn prefix_len(&self, content: &[u8]) -> usize {
+        let len = self.byte_cap().map_or(content.len(), |cap| cap.min(content.len() as u64) as usize);
+
+        match self.max_lines {
+            Some(0) => 0,
+            Some(max_lines) => content[..len]
+                .iter()
+                .enumerate()
+                .filter(|(_, byte)| **byte == b'\n')
+                .nth(max_lines - 1)
+                .map_or(len, |(i, _)| i + 1),
+            None => len,
+        }
     }
 
-    run_results
-}
+    /*
+    Cuts extracted text (documents) down to what's left of the limits and uses up that much of them, so the
+    limits apply across all the pages of a document rather than to each one...
+    */
+    fn consume_text(&mut self, text: &mut String) {
+        let mut len = self.prefix_len(text.as_bytes());
+        while !text.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        if let Some(max_bytes) = self.max_bytes.as_mut() {
+            *max_bytes -= len;
+        }
+        if let Some(max_lines) = self.max_lines.as_mut() {
+            *max_lines -= text.as_bytes()[..len].iter().filter(|byte| **byte == b'\n').count();
+        }
+
+        text.truncate(len);
+    }
+}
+
+impl FileStats {
+    pub fn new() -> Self {
+        Self {
+            word_count: 0,
+            char_count: 0,
+            byte_count: 0,
+            line_count: 0,
+            max_line_length: 0,
+            longest_line: None,
+            longest_text: None,
+            byte_histogram: None,
+            sample_len: 0,
+            sample_compressed_len: 0,
+            blank_lines: 0,
+            comment_lines: 0,
+            code_lines: 0,
+            unique_lines: 0,
+            duplicate_lines: 0,
+            unique_words: 0,
+            line_sketch: None,
+            word_sketch: None,
+            numbers: 0,
+            char_classes: [0; 5],
+            non_ascii: 0,
+            emoji: 0,
+            whitespace: [0; 4],
+            char_occurrences: Vec::new(),
+            line_classes: Vec::new(),
+            counters: Vec::new(),
+            hash: None,
+            file_type: None,
+            language: None,
+        }
+    }
+
+    pub fn lines(&self) -> u64 {
+        self.line_count
+    }
+
+    pub fn words(&self) -> u64 {
+        self.word_count
+    }
+
+    pub fn chars(&self) -> u64 {
+        self.char_count
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.byte_count
+    }
+
+    pub fn max_line_length(&self) -> u64 {
+        self.max_line_length
+    }
+
+    // 1 is the first line, totals don't have one
+    pub fn longest_line(&self) -> Option<u64> {
+        self.longest_line
+    }
+
+    // bits per byte, when --entropy or --byte-histogram counted the bytes
+    pub fn entropy(&self) -> Option<f64> {
+        self.byte_histogram.as_deref().map(entropy::shannon)
+    }
+
+    pub fn byte_histogram(&self) -> Option<&[u64; 256]> {
+        self.byte_histogram.as_deref()
+    }
+
+    pub fn compress_ratio(&self) -> Option<f64> {
+        (self.sample_len > 0).then(|| self.sample_compressed_len as f64 / self.sample_len as f64)
+    }
+
+    pub fn blank_lines(&self) -> u64 {
+        self.blank_lines
+    }
+
+    pub fn comment_lines(&self) -> u64 {
+        self.comment_lines
+    }
+
+    pub fn code_lines(&self) -> u64 {
+        self.code_lines
+    }
+
+    // the estimate with --approx
+    pub fn unique_lines(&self) -> u64 {
+        self.unique_lines + self.line_sketch.as_deref().map_or(0, distinct::HyperLogLog::estimate)
+    }
+
+    pub fn duplicate_lines(&self) -> u64 {
+        self.duplicate_lines
+    }
+
+    pub fn unique_words(&self) -> u64 {
+        self.unique_words + self.word_sketch.as_deref().map_or(0, distinct::HyperLogLog::estimate)
+    }
+
+    pub fn numbers(&self) -> u64 {
+        self.numbers
+    }
+
+    // letters, digits, punctuation, whitespace and other
+    pub fn char_classes(&self) -> [u64; 5] {
+        self.char_classes
+    }
+
+    pub fn non_ascii(&self) -> u64 {
+        self.non_ascii
+    }
+
+    pub fn emoji(&self) -> u64 {
+        self.emoji
+    }
+
+    // trailing whitespace, tab indented, space indented and mixed indent
+    pub fn whitespace(&self) -> [u64; 4] {
+        self.whitespace
+    }
+
+    // one per --count-char, in the order they were given
+    pub fn char_occurrences(&self) -> Vec<u64> {
+        self.char_occurrences.clone()
+    }
+
+    // one per --blank-lines, --whitespace-only-lines and --count-lines-matching, in the order they were given
+    pub fn line_classes(&self) -> Vec<u64> {
+        self.line_classes.clone()
+    }
+
+    // one per registered counter, in the order they were registered
+    pub fn counters(&self) -> &[Box<dyn Counter>] {
+        &self.counters
+    }
+
+    pub fn hash(&self) -> Option<&str> {
+        self.hash.as_deref()
+    }
+
+    pub fn file_type(&self) -> Option<&str> {
+        self.file_type.as_deref()
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    // the extra columns are printed with two decimals
+    fn entropy_text(&self) -> String {
+        format!("{:.2}", self.entropy().unwrap_or(0.0))
+    }
+
+    fn compress_ratio_text(&self) -> String {
+        format!("{:.2}", self.compress_ratio().unwrap_or(1.0))
+    }
+
+    fn hash_text(&self) -> &str {
+        self.hash.as_deref().unwrap_or("-")
+    }
+
+    fn file_type_text(&self) -> &str {
+        self.file_type.as_deref().unwrap_or("-")
+    }
+
+    fn language_text(&self) -> &str {
+        self.language.as_deref().unwrap_or("-")
+    }
+
+    fn set_file_type(&mut self, (file_type, language): (String, Option<String>)) {
+        self.file_type = Some(file_type);
+        self.language = language;
+    }
+
+    // a row that was never counted (a directory with --compat=gnu) has no counters
+    fn counter_report(&self, index: usize) -> String {
+        self.counters.get(index).map_or(String::from("-"), |counter| counter.report())
+    }
+
+    fn set_longest(&mut self, longest: width::Longest) {
+        self.max_line_length = longest.width;
+        self.longest_line = (longest.line > 0).then_some(longest.line);
+        self.longest_text = longest.text;
+    }
+
+    // an estimate is kept as the sketch alone, so a total over inputs with and without one is the exact counts plus
+    // the estimate of the merged sketches (an input only has one when --max-memory ran out)
+    fn set_distinct_lines(&mut self, counts: distinct::Counts) {
+        self.unique_lines = if counts.sketch.is_some() { 0 } else { counts.unique };
+        self.duplicate_lines = counts.duplicate;
+        self.line_sketch = counts.sketch;
+    }
+
+    fn set_distinct_words(&mut self, counts: distinct::Counts) {
+        self.unique_words = if counts.sketch.is_some() { 0 } else { counts.unique };
+        self.word_sketch = counts.sketch;
+    }
+
+    fn set_numbers(&mut self, numbers: u64, mode: numbers::Mode) {
+        self.numbers = numbers;
+        if mode == numbers::Mode::Exclude {
+            self.word_count -= self.numbers;
+        }
+    }
+
+    fn set_char_classes(&mut self, counts: [u64; 5]) {
+        self.char_classes = counts;
+    }
+
+    fn set_emoji(&mut self, counts: emoji::Counts) {
+        self.non_ascii = counts.non_ascii;
+        self.emoji = counts.emoji;
+    }
+
+    fn set_whitespace(&mut self, counts: [u64; 4]) {
+        self.whitespace = counts;
+    }
+
+    fn set_char_occurrences(&mut self, counts: Vec<u64>) {
+        self.char_occurrences = counts;
+    }
+
+    fn set_line_classes(&mut self, counts: Vec<u64>) {
+        self.line_classes = counts;
+    }
+
+    fn unique_lines_text(&self) -> String {
+        distinct_count(self.unique_lines, self.line_sketch.as_deref())
+    }
+
+    fn unique_words_text(&self) -> String {
+        distinct_count(self.unique_words, self.word_sketch.as_deref())
+    }
+
+    fn set_code_lines(&mut self, (blank, comment, code): (u64, u64, u64)) {
+        self.blank_lines = blank;
+        self.comment_lines = comment;
+        self.code_lines = code;
+    }
+
+}
+
+impl Default for FileStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// lines, words and bytes, what wc prints without options
+impl fmt::Display for FileStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.line_count, self.word_count, self.byte_count)
+    }
+}
+
+impl AddAssign<&FileStats> for FileStats {
+    fn add_assign(&mut self, other: &FileStats) {
+        self.word_count += other.word_count;
+        self.char_count += other.char_count;
+        self.byte_count += other.byte_count;
+        self.line_count += other.line_count;
+        self.max_line_length = self.max_line_length.max(other.max_line_length);
+        if let Some(histogram) = &other.byte_histogram {
+            let total = self.byte_histogram.get_or_insert_with(|| Box::new([0; 256]));
+            for (count, other_count) in total.iter_mut().zip(histogram.iter()) {
+                *count += other_count;
+            }
+        }
+        self.sample_len += other.sample_len;
+        self.sample_compressed_len += other.sample_compressed_len;
+        self.blank_lines += other.blank_lines;
+        self.comment_lines += other.comment_lines;
+        self.code_lines += other.code_lines;
+        self.unique_lines += other.unique_lines;
+        self.duplicate_lines += other.duplicate_lines;
+        self.unique_words += other.unique_words;
+        for (class, other_class) in self.char_classes.iter_mut().zip(other.char_classes) {
+            *class += other_class;
+        }
+        self.numbers += other.numbers;
+        self.non_ascii += other.non_ascii;
+        self.emoji += other.emoji;
+        for (lines, other_lines) in self.whitespace.iter_mut().zip(other.whitespace) {
+            *lines += other_lines;
+        }
+        // totals start out without any
+        if self.char_occurrences.len() < other.char_occurrences.len() {
+            self.char_occurrences.resize(other.char_occurrences.len(), 0);
+        }
+        for (count, other_count) in self.char_occurrences.iter_mut().zip(&other.char_occurrences) {
+            *count += other_count;
+        }
+        if self.line_classes.len() < other.line_classes.len() {
+            self.line_classes.resize(other.line_classes.len(), 0);
+        }
+        for (count, other_count) in self.line_classes.iter_mut().zip(&other.line_classes) {
+            *count += other_count;
+        }
+        counter::merge_all(&mut self.counters, &other.counters);
+        merge_sketch(&mut self.line_sketch, other.line_sketch.as_deref());
+        merge_sketch(&mut self.word_sketch, other.word_sketch.as_deref());
+    }
+}
+
+impl AddAssign for FileStats {
+    fn add_assign(&mut self, other: FileStats) {
+        *self += &other;
+    }
+}
+
+impl Add for FileStats {
+    type Output = FileStats;
+
+    fn add(mut self, other: FileStats) -> FileStats {
+        self += &other;
+        self
+    }
+}
+
+impl Sum for FileStats {
+    fn sum<I: Iterator<Item = FileStats>>(iter: I) -> FileStats {
+        iter.fold(FileStats::new(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a FileStats> for FileStats {
+    fn sum<I: Iterator<Item = &'a FileStats>>(iter: I) -> FileStats {
+        iter.fold(FileStats::new(), |mut total, stats| {
+            total += stats;
+            total
+        })
+    }
+}
+
+// an estimate comes with its margin, a sketch that's been merged into is estimated again
+fn distinct_count(exact: u64, sketch: Option<&distinct::HyperLogLog>) -> String {
+    match sketch {
+        Some(sketch) => {
+            let estimate = sketch.estimate();
+            format!("{}±{}", exact + estimate, (estimate as f64 * distinct::HyperLogLog::ERROR).ceil())
+        },
+        None => exact.to_string(),
+    }
+}
+
+fn merge_sketch(total: &mut Option<Box<distinct::HyperLogLog>>, other: Option<&distinct::HyperLogLog>) {
+    match (total.as_mut(), other) {
+        (Some(total), Some(other)) => total.merge(other),
+        (None, Some(other)) => *total = Some(Box::new(other.clone())),
+        _ => {},
+    }
+}
+
+// the everyday options, the header above has all of them. Each line is translated on its own so a catalog can leave
+// the option names alone
+const HELP: &[&str] = &[
+    "Usage: wc_clone [OPTION]... [FILE]...",
+    "Print newline, word, and byte counts for each FILE, and a total line if more than one FILE is specified.",
+    "With no FILE, or when FILE is -, read standard input.",
+    "",
+    "  -c, --bytes            print the byte counts",
+    "  -m, --chars            print the character counts",
+    "  -l, --lines            print the newline counts",
+    "  -w, --words            print the word counts",
+    "  -L, --max-line-length  print the display width of the longest line",
+    "  -r, --recursive        count the files in directories too",
+    "  -j, --jobs=N           count N files at a time",
+    "      --output=PATH      write the report to PATH",
+    "      --output-format=F  text, json, csv, html or md",
+    "      --compat=gnu       output exactly like GNU wc",
+    "  -v, --verbose          log what's happening to stderr, -vv for more",
+    "      --help             display this help and exit",
+    "      --version          output version information and exit",
+    "",
+    "Messages are translated for LANGUAGE, LC_ALL, LC_MESSAGES or LANG when there's a catalog for it.",
+];
+
+fn print_help() {
+    for line in HELP {
+        println!("{}", i18n::translate(line));
+    }
+}
+
+// everything a bug report needs to get the same binary again, build.rs fills in the details cargo doesn't know
+fn print_version() {
+    let features: Vec<&str> = [
+        ("mmap", cfg!(feature = "mmap")),
+        ("compression", cfg!(feature = "compression")),
+        ("hashes", cfg!(feature = "hashes")),
+        ("normalize", cfg!(feature = "normalize")),
+        ("logging", cfg!(feature = "logging")),
+        ("serve", cfg!(feature = "serve")),
+        ("cloud", cfg!(feature = "cloud")),
+        ("pdf", cfg!(feature = "pdf")),
+        ("office", cfg!(feature = "office")),
+        ("archives", cfg!(feature = "archives")),
+        ("serde", cfg!(feature = "serde")),
+        ("plugins", cfg!(feature = "plugins")),
+        ("sqlite", cfg!(feature = "sqlite")),
+        ("arrow", cfg!(feature = "arrow")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect();
+
+    println!("wc_clone {}", env!("CARGO_PKG_VERSION"));
+    println!("commit: {}", env!("WC_CLONE_COMMIT"));
+    println!("built: {}", env!("WC_CLONE_BUILD_DATE"));
+    println!("target: {}", env!("WC_CLONE_TARGET"));
+    println!("rustc: {}", env!("WC_CLONE_RUSTC"));
+    println!("features: {}", if features.is_empty() { String::from("none") } else { features.join(", ") });
+}
+
+// Main "run" program parses the command options, then executes on the files from the options or reads from stdin (if not a TTY) when none were given...
+pub fn run() {
+    interrupt::install();
+    progress::install();
+
+    match env::args().nth(1).as_deref() {
+        Some("freq") => return freq::run(env::args().skip(2)),
+        Some("snapshot") => return snapshot::run_snapshot(env::args().skip(2)),
+        Some("delta") => return snapshot::run_delta(env::args().skip(2)),
+        Some("selftest") => return selftest::run(env::args().skip(2)),
+        Some("bench") => return bench::run(env::args().skip(2)),
+        Some("generate") => return generate::run(env::args().skip(2)),
+        #[cfg(feature = "serve")]
+        Some("serve") => return serve::run(env::args().skip(2)),
+        #[cfg(not(feature = "serve"))]
+        Some("serve") => {
+            println!("can't serve: wc_clone was built without the serve feature");
+            return;
+        },
+        _ => {},
+    }
+
+    match CommandOptions::build(env::args()) {
+        Ok(command_options) => {
+            logging::init(&command_options);
+            // before any of the counting threads start, they get it from this one
+            if command_options.idle {
+                if let Err(err) = priority::idle() {
+                    eprintln!("{}", tr!("wc_clone: couldn't lower the priority: {}", err));
+                }
+            }
+            if command_options.help {
+                print_help();
+            } else if command_options.version {
+                print_version();
+            } else if command_options.generate_man {
+                print!("{}", man::generate());
+            } else if command_options.check {
+                check::run(command_options);
+            } else if command_options.diff_input {
+                diff::run(command_options);
+            } else if command_options.files.is_empty() && command_options.files_from.is_none() && command_options.git.is_none()
+                && io::stdin().lock().is_terminal() {
+                println!("{}", tr!("No files spcified..."));
+            } else {
+                match command_options.output_format {
+                    OutputFormat::Text if command_options.print0 => report(command_options, TextReporter::print0()),
+                    OutputFormat::Text => report(command_options, TextReporter::default()),
+                    OutputFormat::Json => report(command_options, JsonReporter::default()),
+                    OutputFormat::Csv => report(command_options, CsvReporter::default()),
+                    OutputFormat::Html => report(command_options, HtmlReporter::default()),
+                    OutputFormat::Markdown => report(command_options, MarkdownReporter::default()),
+                    #[cfg(feature = "arrow")]
+                    OutputFormat::Parquet => report(command_options, columnar::ColumnarReporter::new(columnar::Columnar::Parquet)),
+                    #[cfg(feature = "arrow")]
+                    OutputFormat::Arrow => report(command_options, columnar::ColumnarReporter::new(columnar::Columnar::Ipc)),
+                }
+            }
+        },
+
+        Err(err) => println!("{}", err)
+    };
+}
+
+// the files (or stdin, with none given) through the reporter, to --output or stdout
+fn report(command_options: CommandOptions, mut reporter: impl Reporter) {
+    #[cfg(feature = "sqlite")]
+    if let Some(database) = command_options.output.as_deref().and_then(|output| output.strip_prefix("sqlite:")) {
+        match sqlite::SqliteReporter::create(database) {
+            Ok(mut reporter) => finish_output(count_inputs(command_options, &mut reporter)),
+            Err(err) => {
+                eprintln!("wc_clone: {}", err);
+                process::exit(1);
+            },
+        }
+        return;
+    }
+
+    match command_options.output.clone() {
+        Some(path) => match report::ToFile::create(reporter, &path) {
+            Ok(mut reporter) => finish_output(count_inputs(command_options, &mut reporter)),
+            Err(err) => {
+                eprintln!("{}", tr!("wc_clone: can't write {}: {}", path, err));
+                process::exit(1);
+            },
+        },
+        None => finish_output(count_inputs(command_options, &mut reporter)),
+    }
+}
+
+/*
+On a Windows console std writes stdout with WriteConsoleW, so non-ascii names come out as they are whatever the code page
+is. That only works for valid UTF-8, which is why everything printed is a String (names that aren't valid Unicode get a
+U+FFFD) and the binary formats only go to --output. Redirected it's UTF-8 bytes like everywhere else.
+*/
+fn count_inputs(command_options: CommandOptions, reporter: &mut impl Reporter) -> io::Result<()> {
+    let out = &mut io::stdout().lock();
+    if command_options.files.is_empty() && command_options.files_from.is_none() && command_options.git.is_none() && !command_options.list_only {
+        run_from_stdin(command_options, reporter, out)
+    } else {
+        run_on_files(command_options, reporter, out)
+    }
+}
+
+// a closed pipe (wc_clone ... | head) isn't worth a message, the exit status still says the output is incomplete
+fn finish_output(written: io::Result<()>) {
+    match written {
+        Ok(()) => {},
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => process::exit(1),
+        Err(err) => {
+            eprintln!("{}", tr!("wc_clone: write error: {}", err));
+            process::exit(1);
+        },
+    }
+}
+
+// reads from stdin through the streaming reader, same as any other pipe.
+pub fn run_from_stdin(mut command_options: CommandOptions, reporter: &mut impl Reporter, out: &mut impl Write) -> io::Result<()> {
+    let started = SystemTime::now();
+    let _tracked = progress::track("stdin");
+    if command_options.compat == Compat::Gnu {
+        command_options.number_width = compat::gnu_width(&["-"], command_options.counters());
+    }
+    let command_options = &command_options;
+    let _span = tracing::info_span!("stdin").entered();
+
+    match decompress::wrap(timeout::stdin(command_options), command_options).and_then(|stdin| stream::count_reader(stdin, command_options, "")) {
+        Ok((_, Some(offset))) if command_options.utf8_mode == Utf8Mode::Strict => {
+            eprintln!("{}", tr!("wc_clone: stdin: invalid UTF-8 at byte {}", offset));
+            process::exit(1);
+        },
+        Ok((stats, _)) => {
+            reporter.row(out, command_options, &stats, "")?;
+            reporter.details(out, command_options, &stats, "(standard input)")?;
+        },
+        // a timeout, or compressed input that's cut short or can't be decompressed
+        Err(err) if !interrupt::is_interrupted() => {
+            eprintln!("{}", tr!("wc_clone: stdin: {}", err));
+            process::exit(1);
+        },
+        Err(_) => {},
+    }
+
+    if interrupt::is_interrupted() {
+        out.flush()?;
+        eprintln!("{}", tr!("wc_clone: interrupted before stdin was fully counted"));
+        process::exit(interrupt::EXIT_CODE);
+    }
+    if command_options.run_info {
+        reporter.run_info(out, &RunInfo::finished(&command_options.arguments, started))?;
+    }
+    reporter.finish(out)
+}
+
+pub fn run_on_files(mut command_options: CommandOptions, reporter: &mut impl Reporter, out: &mut impl Write) -> io::Result<()> {
+    let started = SystemTime::now();
+    let mut all_stats: Vec<Row> = Vec::new();
+    let mut failed = false;
+    // tagged with how many rows come first: with --compat they go out between the rows like the real wcs print them,
+    // otherwise they're all listed after the total so a long run's errors don't get lost among its rows
+    let mut errors: Vec<Failure> = Vec::new();
+
+    let operands = command_options.files.clone(); // before -r replaces the directories, for --group-by=dir
+    if command_options.recursive {
+        let (files, walk_errors) = walk::expand_operands(&command_options.files, &command_options.walk_options);
+        for (dir, err) in walk_errors {
+            tracing::warn!(dir, error = %err, "couldn't read directory");
+            let message = tr!("Encounted error reading directory {}: {}", dir, err);
+            match command_options.compat {
+                Compat::Native => errors.push(Failure { file: dir, error: err.to_string(), kind: err.kind(), message, before: 0 }),
+                _ => reporter.message(out, &message)?,
+            }
+            failed = true;
+        }
+        command_options.files = files;
+    }
+    if let Some(sort) = command_options.sort {
+        sort.sort(&mut command_options.files);
+    }
+    if command_options.list_only {
+        return list_inputs(&command_options, &errors, reporter, out);
+    }
+
+    if command_options.compat == Compat::Gnu {
+        let inputs: Vec<&str> = command_options.files.iter().map(|file| file.as_str()).collect();
+        command_options.number_width = compat::gnu_width(&inputs, command_options.counters());
+    }
+
+    let dedupe_keys = match command_options.dedupe {
+        Some(mode) => dedupe::keys(&command_options.files, mode),
+        None => Vec::new(),
+    };
+    let (mut journal, resumed) = match &command_options.journal {
+        Some(path) => match journal::open(path, &journal::options_key(&command_options.arguments), command_options.resume) {
+            Ok((journal, resumed)) => (Some(journal), resumed),
+            Err(err) => {
+                eprintln!("{}", tr!("wc_clone: journal {}", err));
+                process::exit(1);
+            },
+        },
+        None => (None, HashMap::new()),
+    };
+    // what's left to count, the files --resume has counts for get their rows from the journal
+    let pending: Vec<usize> = (0..command_options.files.len()).filter(|i| !resumed.contains_key(&command_options.files[*i])).collect();
+    let pending_files: Cow<[String]> = match resumed.is_empty() {
+        true => Cow::Borrowed(&command_options.files),
+        false => Cow::Owned(pending.iter().map(|i| command_options.files[*i].clone()).collect()),
+    };
+
+    let mut totals = Totals::new(&operands, &dedupe_keys);
+    if command_options.ordered {
+        let mut written = Ok(());
+        let mut next = 0; // the first file that's neither counted nor taken from the journal yet
+        pipeline::count_each(&pending_files, &command_options, command_options.jobs, true, |index, result| {
+            if written.is_err() {
+                return;
+            }
+            let file_index = pending[index];
+            for (i, file) in command_options.files.iter().enumerate().take(file_index).skip(next) {
+                take_resumed(i, file, &resumed, &mut all_stats);
+            }
+            next = file_index + 1;
+            let rows = all_stats.len();
+            written = take_result(file_index, result, &command_options, reporter, out, &mut errors, &mut all_stats).and_then(|file_failed| {
+                failed |= file_failed;
+                match (journal.as_mut(), all_stats.last()) {
+                    (Some(journal), Some((stats, topic, parts, _))) if all_stats.len() > rows => journal.record(topic, stats, parts),
+                    _ => Ok(()),
+                }
+            });
+        });
+        written?;
+        for (i, file) in command_options.files.iter().enumerate().skip(next) {
+            take_resumed(i, file, &resumed, &mut all_stats);
+        }
+
+        if interrupt::is_interrupted() {
+            out.flush()?;
+            if let Some(journal) = journal.as_mut() {
+                journal.flush()?;
+            }
+            report_interrupted(&command_options, &all_stats, &errors, true);
+            process::exit(interrupt::EXIT_CODE);
+        }
+
+        for (i, row) in all_stats.iter().enumerate() {
+            print_compat_errors(&command_options, &errors, i);
+            totals.print_row(&command_options, row, reporter, out)?;
+        }
+    } else {
+        // what the journal has goes out first, the rest as it's counted
+        for (i, file) in command_options.files.iter().enumerate().filter(|(_, file)| resumed.contains_key(*file)) {
+            take_resumed(i, file, &resumed, &mut all_stats);
+            if let Some(row) = all_stats.last() {
+                totals.print_row(&command_options, row, reporter, out)?;
+            }
+        }
+
+        // --unordered, each row goes out as soon as its file is done
+        let mut written = Ok(());
+        pipeline::count_each(&pending_files, &command_options, command_options.jobs, false, |index, result| {
+            if written.is_err() {
+                return;
+            }
+            let rows = all_stats.len();
+            written = take_result(pending[index], result, &command_options, reporter, out, &mut errors, &mut all_stats).and_then(|file_failed| {
+                failed |= file_failed;
+                if command_options.compat != Compat::Native {
+                    for failure in errors.drain(..) {
+                        eprintln!("{}", failure.message);
+                    }
+                }
+                match all_stats.last() {
+                    Some(row @ (stats, topic, page_stats, _)) if all_stats.len() > rows => {
+                        if let Some(journal) = journal.as_mut() {
+                            journal.record(topic, stats, page_stats)?;
+                        }
+                        totals.print_row(&command_options, row, reporter, out)
+                    },
+                    _ => Ok(()),
+                }
+            });
+        });
+        written?;
+
+        if interrupt::is_interrupted() {
+            out.flush()?;
+            if let Some(journal) = journal.as_mut() {
+                journal.flush()?;
+            }
+            report_interrupted(&command_options, &all_stats, &errors, false);
+            process::exit(interrupt::EXIT_CODE);
+        }
+    }
+    if let Some(journal) = journal.as_mut() {
+        journal.flush()?;
+    }
+    let Totals { groups, aggregated: aggregated_stats, empty, .. } = totals;
+
+    print_compat_errors(&command_options, &errors, all_stats.len());
+    for (group, stats) in &groups {
+        let group = match command_options.group_by {
+            Some(walk::GroupBy::Dir) => command_options.shown_name(group),
+            _ => Cow::Borrowed(group.as_str()), // *.rs, not a name
+        };
+        reporter.row(out, &command_options, stats, &group)?;
+    }
+
+    // the real wcs go by how many inputs there were, whether or not they could all be counted
+    let inputs = if command_options.compat == Compat::Native { all_stats.len() } else { command_options.files.len() };
+    if inputs > 1 {
+        reporter.total(out, &command_options, &aggregated_stats)?;
+        reporter.details(out, &command_options, &aggregated_stats, "total")?;
+    }
+    match empty {
+        0 => {},
+        1 => reporter.message(out, &tr!("wc_clone: 1 empty file skipped"))?,
+        _ => reporter.message(out, &tr!("wc_clone: {} empty files skipped", empty))?,
+    }
+    if command_options.summary {
+        let summary = Summary::of(&command_options, all_stats.iter().map(|(stats, _, _, _)| stats));
+        reporter.summary(out, &command_options, &summary)?;
+    }
+    if command_options.compat == Compat::Native {
+        reporter.errors(out, &errors)?;
+    }
+    if command_options.run_info {
+        reporter.run_info(out, &RunInfo::finished(&command_options.arguments, started))?;
+    }
+    reporter.finish(out)?;
+
+    if failed {
+        out.flush()?;
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/*
+What a file's result adds to the rows, or the message saying why it doesn't add one. The bool is whether it failed.
+*/
+fn take_result<'a>(
+    index: usize,
+    result: ReadResult,
+    command_options: &'a CommandOptions,
+    reporter: &mut impl Reporter,
+    out: &mut impl Write,
+    errors: &mut Vec<Failure>,
+    all_stats: &mut Vec<Row<'a>>,
+) -> io::Result<bool> {
+    let file = command_options.files[index].as_str();
+    match result {
+        ReadResult::Utf8(utf8) => { 
+            let mut file_stats = get_stats(&utf8, file, command_options);
+            document_details(&mut file_stats, file, command_options);
+            all_stats.push((file_stats, file, Vec::new(), index));
+        },
+        ReadResult::Counted { invalid_utf8_at: Some(offset), .. } if command_options.utf8_mode == Utf8Mode::Strict => {
+            errors.push(Failure {
+                file: file.to_string(),
+                error: tr!("invalid UTF-8 at byte {}", offset),
+                kind: io::ErrorKind::InvalidData,
+                message: tr!("wc_clone: {}: invalid UTF-8 at byte {}", file, offset),
+                before: all_stats.len(),
+            });
+            return Ok(true);
+        },
+        ReadResult::Counted { stats, .. } => {
+            all_stats.push((*stats, file, Vec::new(), index));
+        },
+        ReadResult::Pages(pages) => {
+            let page_stats: Vec<FileStats> = pages.iter().map(|page| get_stats(page, file, command_options)).collect();
+            let mut file_stats = FileStats::new();
+            for stats in &page_stats {
+                file_stats += stats;
+            }
+            document_details(&mut file_stats, file, command_options);
+            // the pages only get rows of their own with -v
+            let page_rows = match command_options.verbosity {
+                0 => Vec::new(),
+                _ => page_stats.into_iter().enumerate().map(|(i, stats)| (format!("page {}", i + 1), stats)).collect(),
+            };
+            all_stats.push((file_stats, file, page_rows, index));
+        },
+        ReadResult::Members(members) => {
+            let mut file_stats = FileStats::new();
+            for (_, stats) in &members {
+                file_stats += stats;
+            }
+            document_details(&mut file_stats, file, command_options);
+            all_stats.push((file_stats, file, members, index));
+        },
+        ReadResult::SkippedBinary => {
+            tracing::info!(file, "skipped binary file");
+            reporter.message(out, &tr!("wc_clone: {}: skipped binary file", file))?;
+        },
+        ReadResult::Interrupted => {},
+        ReadResult::ReadError(err) => {
+            tracing::warn!(file, error = %err, "couldn't read");
+            let message = match command_options.compat {
+                Compat::Native => tr!("Encounted error reading file {}: {}", file, err),
+                _ => compat::error_message(file, &*err),
+            };
+            let kind = err.downcast_ref::<io::Error>().map_or(io::ErrorKind::Other, io::Error::kind);
+            errors.push(Failure { file: file.to_string(), error: err.to_string(), kind, message, before: all_stats.len() });
+            if command_options.compat == Compat::Gnu && compat::is_directory_error(&*err) {
+                all_stats.push((FileStats::new(), file, Vec::new(), index));
+            }
+            return Ok(true);
+        },
+    }
+    Ok(false)
+}
+
+// a page of a document or a member of an archive: what goes after the colon in its row, and its counts
+type Part = (String, FileStats);
+
+// an input's counts, its name, the breakdown of a document or an archive and which of command_options.files it is
+type Row<'a> = (FileStats, &'a str, Vec<Part>, usize);
+
+// what the rows add up to as they're printed: the total, the --group-by groups and the first input with each --dedupe key
+struct Totals<'a> {
+    operands: &'a [String], // as given, for --group-by=dir
+    dedupe_keys: &'a [Option<dedupe::Key>],
+    first_with_key: HashMap<&'a dedupe::Key, &'a str>,
+    groups: BTreeMap<String, FileStats>,
+    aggregated: FileStats,
+    empty: usize, // files --skip-empty left out
+}
+
+impl<'a> Totals<'a> {
+    fn new(operands: &'a [String], dedupe_keys: &'a [Option<dedupe::Key>]) -> Self {
+        Self { operands, dedupe_keys, first_with_key: HashMap::new(), groups: BTreeMap::new(), aggregated: FileStats::new(), empty: 0 }
+    }
+
+    // a file's row (after its pages with -v, or an archive's members), unless --group-by only wants the groups
+    fn print_row(&mut self, command_options: &'a CommandOptions, row: &Row<'a>, reporter: &mut impl Reporter, out: &mut impl Write) -> io::Result<()> {
+        let (stats, topic, parts, index) = row;
+        let topic = *topic;
+        // nothing to add to the totals either
+        if command_options.skip_empty && is_empty_file(topic, stats) {
+            self.empty += 1;
+            return Ok(());
+        }
+
+        // --dedupe, the first counted input with each key is the one that goes into the totals
+        let key = match command_options.dedupe {
+            Some(_) => self.dedupe_keys.get(*index).and_then(Option::as_ref),
+            None => None,
+        };
+        let duplicate_of = key.and_then(|key| self.first_with_key.get(key).copied());
+        if let (Some(key), None) = (key, duplicate_of) {
+            self.first_with_key.insert(key, topic);
+        }
+
+        if duplicate_of.is_none() {
+            self.aggregated += stats;
+        }
+        if let Some(group_by) = command_options.group_by {
+            if duplicate_of.is_none() {
+                *self.groups.entry(walk::group_key(topic, self.operands, group_by)).or_default() += stats;
+            }
+            if command_options.verbosity == 0 {
+                return Ok(());
+            }
+        }
+        let name = command_options.shown_name(topic);
+        for (part, part_stats) in parts {
+            reporter.row(out, command_options, part_stats, &format!("{}:{}", name, command_options.shown_name(part)))?;
+        }
+        match (duplicate_of, command_options.dedupe) {
+            (Some(original), Some(mode)) => {
+                let original = command_options.shown_name(original);
+                reporter.row(out, command_options, stats, &format!("{} {}", name, mode.annotation(&original)))?
+            },
+            _ => reporter.row(out, command_options, stats, &name)?,
+        }
+        reporter.details(out, command_options, stats, &name)
+    }
+}
+
+// a file that's really empty, not one whose --offset is past the end or a procfs entry that only claims to be
+fn is_empty_file(path: &str, stats: &FileStats) -> bool {
+    stats.bytes() == 0 && fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.len() == 0)
+}
+
+// the row --resume has for file from the journal
+fn take_resumed<'a>(index: usize, file: &'a str, resumed: &journal::Done, all_stats: &mut Vec<Row<'a>>) {
+    if let Some((stats, parts)) = resumed.get(file) {
+        all_stats.push((stats.clone(), file, parts.clone(), index));
+    }
+}
+
+// --list-only, the inputs a run would count and the directories it couldn't get into
+fn list_inputs(command_options: &CommandOptions, errors: &[Failure], reporter: &mut impl Reporter, out: &mut impl Write) -> io::Result<()> {
+    let end = if command_options.print0 { '\0' } else { '\n' };
+    for file in &command_options.files {
+        write!(out, "{}{}", command_options.shown_name(file), end)?;
+    }
+    reporter.errors(out, errors)?;
+    if !errors.is_empty() {
+        out.flush()?;
+        process::exit(1);
+    }
+    Ok(())
+}
+
+// the row run_on_files would have printed for a file, or why there isn't one. For --check and delta
+fn counted_stats(result: ReadResult, file: &str, command_options: &CommandOptions) -> Result<FileStats, String> {
+    match result {
+        ReadResult::Utf8(text) => {
+            let mut stats = get_stats(&text, file, command_options);
+            document_details(&mut stats, file, command_options);
+            Ok(stats)
+        },
+        ReadResult::Pages(pages) => {
+            let mut stats = FileStats::new();
+            for page in &pages {
+                stats += &get_stats(page, file, command_options);
+            }
+            document_details(&mut stats, file, command_options);
+            Ok(stats)
+        },
+        ReadResult::Counted { invalid_utf8_at: Some(offset), .. } if command_options.utf8_mode == Utf8Mode::Strict => {
+            Err(format!("invalid UTF-8 at byte {}", offset))
+        },
+        ReadResult::Counted { stats, .. } => Ok(*stats),
+        ReadResult::Members(members) => {
+            let mut stats = FileStats::new();
+            for (_, member_stats) in &members {
+                stats += member_stats;
+            }
+            document_details(&mut stats, file, command_options);
+            Ok(stats)
+        },
+        ReadResult::SkippedBinary => Err(String::from("skipped binary file")),
+        ReadResult::Interrupted => Err(String::from("interrupted")),
+        ReadResult::ReadError(err) => Err(err.to_string()),
+    }
+}
+
+// documents are counted from their text, but the checksum and the type are of the file itself
+fn document_details(stats: &mut FileStats, path: &str, command_options: &CommandOptions) {
+    stats.hash = command_options.hash.and_then(|algorithm| hash::file_digest(path, algorithm).ok());
+    if command_options.detect_type {
+        stats.set_file_type(filetype::detect_file(path));
+    }
+}
+
+fn print_compat_errors(command_options: &CommandOptions, errors: &[Failure], row: usize) {
+    if command_options.compat == Compat::Native {
+        return; // those wait for Reporter::errors
+    }
+    for failure in errors.iter().filter(|failure| failure.before == row) {
+        eprintln!("{}", failure.message);
+    }
+}
+
+/*
+After a Ctrl-C the files that did get counted go to stderr along with their total, so the work isn't all lost but
+nothing on stdout looks like a complete run...
+*/
+fn report_interrupted(command_options: &CommandOptions, all_stats: &[Row], errors: &[Failure], with_rows: bool) {
+    let mut partial_total = FileStats::new();
+
+    for (stats, topic, _, _) in all_stats {
+        partial_total += stats;
+        if with_rows {
+            eprintln!("{}", format_run_results(command_options, stats, topic));
+        }
+    }
+
+    eprintln!("{}", format_run_results(command_options, &partial_total, "partial total (interrupted)"));
+    if command_options.compat == Compat::Native {
+        for failure in errors {
+            eprintln!("{}", failure.message);
+        }
+    }
+}
+
+/*
+Counts a file with whatever IO strategy suits it, documents go through their adapter and come back as text instead...
+*/
+pub(crate) fn read_file(path: &str, command_options: &CommandOptions) -> ReadResult {
+    let _tracked = progress::track(path);
+    let _span = tracing::info_span!("file", path).entered();
+    if let Some(result) = read_remote(path, command_options) {
+        return result;
+    }
+    // stdin given as an operand, counted the way it is without any
+    if path == "-" {
+        return read_result(decompress::wrap(timeout::stdin(command_options), command_options).and_then(|stdin| stream::count_reader(stdin, command_options, "")));
+    }
+    if command_options.skip_binary && looks_binary(path) {
+        return ReadResult::SkippedBinary;
+    }
+
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) => return ReadResult::ReadError(Box::new(err)),
+    };
+    tracing::debug!(size = metadata.len(), "opened");
+    let kind = content_kind(path, &metadata, command_options);
+    if let Some(kind) = kind {
+        tracing::info!(mime = kind.mime(), "content type");
+    }
+
+    if let Some(read_document) = document_adapter(path, &metadata, kind, command_options) {
+        tracing::info!("extracting document text");
+        let mut read_limits = command_options.read_limits;
+        return match read_document(path) {
+            ReadResult::Utf8(mut text) => {
+                read_limits.consume_text(&mut text);
+                ReadResult::Utf8(text)
+            },
+            ReadResult::Pages(mut pages) => {
+                for page in pages.iter_mut() {
+                    read_limits.consume_text(page);
+                }
+                ReadResult::Pages(pages)
+            },
+            other => other
+        };
+    }
+    if opens_archive(path, &metadata, kind, command_options) {
+        tracing::info!("counting archive members");
+        return archive::read_zip(path, command_options);
+    }
+    match kind {
+        Some(mime::Kind::Tar) => {
+            tracing::info!("counting archive members");
+            return match timeout::open(path, command_options) {
+                Ok(file) => archive::read_tar(file, command_options),
+                Err(err) => read_error(err),
+            };
+        },
+        Some(mime::Kind::Compressed) => return read_decompressed(path, command_options),
+        _ => {},
+    }
+
+    let strategy = choose_strategy(path, &metadata, command_options);
+    tracing::info!(?strategy, "strategy chosen");
+    let started = Instant::now();
+    let counted = strategy::count_file(path, &metadata, strategy, command_options);
+    if let Ok((stats, _)) = &counted {
+        tracing::debug!(bytes = stats.bytes(), elapsed_ms = started.elapsed().as_secs_f64() * 1000.0, "counted");
+    }
+    read_result(counted)
+}
+
+// URLs, object storage and files on other machines, which come through a reader of their own and never from the disk
+fn read_remote(path: &str, command_options: &CommandOptions) -> Option<ReadResult> {
+    if http::is_url(path) {
+        Some(http::read_url(path, command_options))
+    } else if cloud::is_uri(path) {
+        Some(cloud::read_uri(path, command_options))
+    } else if is_ssh(path, command_options) {
+        Some(ssh::read(path, command_options))
+    } else {
+        None
+    }
+}
+
+fn is_remote(path: &str, command_options: &CommandOptions) -> bool {
+    http::is_url(path) || cloud::is_uri(path) || is_ssh(path, command_options)
+}
+
+fn is_ssh(path: &str, command_options: &CommandOptions) -> bool {
+    ssh::is_uri(path) || command_options.remotes.iter().any(|remote| remote == path)
+}
+
+fn read_result(counted: io::Result<(FileStats, Option<u64>)>) -> ReadResult {
+    match counted {
+        Ok((stats, invalid_utf8_at)) => ReadResult::Counted { stats: Box::new(stats), invalid_utf8_at },
+        Err(err) => read_error(err),
+    }
+}
+
+// a read cut short by Ctrl-C isn't really an error, the run is just over
+fn read_error(err: io::Error) -> ReadResult {
+    if err.kind() == io::ErrorKind::Interrupted && interrupt::is_interrupted() {
+        ReadResult::Interrupted
+    } else {
+        ReadResult::ReadError(Box::new(err))
+    }
+}
+
+// a gzip, zstd or xz file --auto found, counted as what it decompresses to, or the members of the tarball in it
+fn read_decompressed(path: &str, command_options: &CommandOptions) -> ReadResult {
+    let mut stream = match timeout::open(path, command_options).and_then(|file| decompress::wrap(file, command_options)) {
+        Ok(stream) => stream,
+        Err(err) => return read_error(err),
+    };
+    let start = match stream::read_start(&mut stream, mime::TAR_SNIFF_LEN as usize) {
+        Ok(start) => start,
+        Err(err) => return read_error(err),
+    };
+    let is_tar = mime::is_tar(&start);
+    let stream = io::Cursor::new(start).chain(stream);
+    if is_tar {
+        tracing::info!("counting archive members");
+        archive::read_tar(stream, command_options)
+    } else {
+        read_result(stream::count_reader(stream, command_options, path))
+    }
+}
+
+/*
+What --auto makes of a file's content, None without it. Only regular files read without a byte range are looked at,
+the rest are Text, and so are archives and compressed files when --no-decompress or --compat want their bytes.
+*/
+fn content_kind(path: &str, metadata: &fs::Metadata, command_options: &CommandOptions) -> Option<mime::Kind> {
+    if !command_options.auto {
+        return None;
+    }
+    if !metadata.is_file() || command_options.read_limits.is_range() {
+        return Some(mime::Kind::Text);
+    }
+    match mime::sniff(path).unwrap_or(mime::Kind::Text) {
+        mime::Kind::Zip | mime::Kind::Tar | mime::Kind::Compressed if command_options.no_decompress || command_options.compat != Compat::Native => {
+            Some(mime::Kind::Text)
+        },
+        kind => Some(kind),
+    }
+}
+
+// a byte range points into the file itself, so documents are only extracted without one. POSIX wc has no idea what a
+// document is either. With --auto it's the content that decides rather than the name
+fn document_adapter(path: &str, metadata: &fs::Metadata, kind: Option<mime::Kind>, command_options: &CommandOptions) -> Option<fn(&str) -> ReadResult> {
+    if !metadata.is_file() || command_options.read_limits.is_range() || command_options.posix {
+        return None;
+    }
+    match kind {
+        Some(kind) => adapters::adapter_for_type(kind.mime()),
+        None => adapters::adapter_for(path),
+    }
+}
+
+// the same goes for archives, and --no-decompress and --compat are about the bytes that were read as well
+fn opens_archive(path: &str, metadata: &fs::Metadata, kind: Option<mime::Kind>, command_options: &CommandOptions) -> bool {
+    let is_zip = match kind {
+        Some(kind) => kind == mime::Kind::Zip && cfg!(feature = "archives"),
+        None => archive::is_archive(path),
+    };
+    is_zip
+        && metadata.is_file()
+        && !command_options.read_limits.is_range()
+        && !command_options.posix
+        && !command_options.no_decompress
+        && command_options.compat == Compat::Native
+}
+
+fn choose_strategy(path: &str, metadata: &fs::Metadata, command_options: &CommandOptions) -> Strategy {
+    let bytes_only = command_options.count_bytes && !command_options.count_words && !command_options.count_chars && !command_options.count_lines
+        && !command_options.count_max_line_length && !command_options.needs_histogram() && !command_options.count_compress_ratio
+        && !command_options.count_code && !command_options.count_unique_lines && !command_options.count_duplicate_lines
+        && !command_options.count_unique_words && !command_options.count_char_classes && !command_options.count_whitespace
+        && !command_options.count_non_ascii && !command_options.count_emoji && command_options.numbers.is_none()
+        && command_options.reading_wpm.is_none()
+        && command_options.count_chars_of.is_empty() && command_options.count_lines_of.is_empty()
+        && command_options.registry.is_empty() && command_options.hash.is_none() && !command_options.detect_type;
+    match strategy::choose(metadata, path, command_options.io_mode, bytes_only, &command_options.read_limits, command_options.max_memory) {
+        // a mapping's reads are page faults, nothing to pace
+        Strategy::Mmap if command_options.throttle.is_some() => Strategy::Read,
+        strategy => strategy,
+    }
+}
+
+/*
+Whether read_file would end up streaming this file through a buffer, the pipeline does those reads itself so the
+reading and counting can happen on different threads. Binary files that are going to be skipped don't count.
+*/
+fn is_streamed(path: &str, command_options: &CommandOptions) -> bool {
+    if is_remote(path, command_options) {
+        return false;
+    }
+    match fs::metadata(path) {
+        Ok(metadata) => {
+            let kind = content_kind(path, &metadata, command_options);
+            document_adapter(path, &metadata, kind, command_options).is_none()
+                && !opens_archive(path, &metadata, kind, command_options)
+                && !matches!(kind, Some(mime::Kind::Tar | mime::Kind::Compressed))
+                && choose_strategy(path, &metadata, command_options) == Strategy::Read
+                && !(command_options.skip_binary && looks_binary(path))
+        },
+        Err(_) => false,
+    }
+}
+
+/*
+Pipes, devices, sockets and anything else that isn't a regular file, plus regular files reporting a size of 0 since
+that's what procfs entries do even when they have content. These go through the streaming reader...
+*/
+fn is_special_file(path: &str) -> bool {
+    match fs::metadata(path) {
+        Ok(metadata) => !metadata.is_file() || metadata.len() == 0,
+        Err(_) => false, // let the normal read report the error
+    }
+}
 
 /*
-Prints run results based on the user configuration and a utf8 string...will return a 4 len vec containing the count of each data point.
-This is useful for aggregating the results...
+Cheap binary check for --skip-binary: text files basically never contain a NUL byte, executables and images almost
+always have one near the start. Only regular files are sniffed since peeking at a pipe would eat its content...
 */
-fn get_stats_bin(file_content: &[u8]) -> FileStats {
+fn looks_binary(path: &str) -> bool {
+    if is_special_file(path) {
+        return false;
+    }
+
+    let mut start: Vec<u8> = Vec::new();
+    match fs::File::open(path) {
+        Ok(file) => file.take(BINARY_SNIFF_LEN).read_to_end(&mut start).is_ok() && start.contains(&0),
+        Err(_) => false,
+    }
+}
+
+/*
+Same as utf8 implementation, only it operates on binary directly...
+*/
+fn get_stats(file_content: &str, file: &str, command_options: &CommandOptions) -> FileStats {
+    let separators = command_options.separators();
     let mut run_results = FileStats::new();
 
-    run_results.byte_count = file_content.len() as i32;
+    run_results.byte_count = file_content.len() as u64;
     let mut in_word = false; // Keep track if we're inside a word
+    let mut line_width = width::LineWidth::new(command_options.tab_width, command_options.show_longest_text);
+    let mut char_classes = classes::CharClasses::new();
+    let mut emoji = emoji::EmojiCounter::new();
+    let mut occurrences = occurrences::Occurrences::new(&command_options.count_chars_of);
+    occurrences.feed(file_content.as_bytes());
+    let mut line_classes = lineclass::LineClasses::new(&command_options.count_lines_of);
+    line_classes.feed(file_content.as_bytes());
+    let mut whitespace = whitespace::WhitespaceReport::new();
+    if command_options.count_whitespace {
+        whitespace.feed(file_content.as_bytes());
+    }
+    run_results.counters = command_options.registry.start();
+    for counter in run_results.counters.iter_mut() {
+        counter.feed(file_content.as_bytes());
+    }
+    let mut numbers = numbers::NumberCounter::new(separators);
+    if command_options.numbers.is_some() {
+        numbers.feed(file_content.as_bytes());
+    }
 
-    for byte in file_content {
+    for c in file_content.chars() {
         run_results.char_count += 1;
+        if command_options.count_char_classes {
+            char_classes.feed_char(c);
+        }
+        if command_options.counts_emoji() {
+            emoji.feed_char(c);
+        }
+        if command_options.count_max_line_length {
+            line_width.feed_char(c);
+        }
 
-        if *byte == b'\n' {
+        if c == '\n' {
             run_results.line_count += 1;
             if in_word {
                 run_results.word_count += 1;
                 in_word = false;
             }
-        } else if *byte == b' ' || *byte == b'\t' || *byte == b'\r' {
+        } else if separators.ends_word(c) {
             if in_word {
                 run_results.word_count += 1;
                 in_word = false;
@@ -288,37 +2634,383 @@ fn get_stats_bin(file_content: &[u8]) -> FileStats {
     if in_word {
         run_results.word_count += 1;
     }
+    if command_options.eol != eol::Eol::Lf {
+        let mut line_ends = eol::LineEnds::new(command_options.eol);
+        line_ends.feed(file_content.as_bytes());
+        run_results.line_count = line_ends.finish();
+    }
+    if cfg!(debug_assertions) {
+        let reference = (!separators.has_marks() && command_options.eol == eol::Eol::Lf).then(|| {
+            let mut reference = kernel::Kernel::with_separators(separators);
+            reference.feed(file_content.as_bytes());
+            reference.finish()
+        });
+        kernel::debug_validate(kernel::Counts::from(&run_results), reference, kernel::CharCount::Decoded { valid: true });
+    }
+    run_results.set_longest(line_width.finish());
+    run_results.set_char_classes(char_classes.finish());
+    if let Some(mode) = command_options.numbers {
+        run_results.set_numbers(numbers.finish(), mode);
+    }
+    run_results.set_emoji(emoji.finish());
+    run_results.set_whitespace(whitespace.finish());
+    run_results.set_char_occurrences(occurrences.finish());
+    run_results.set_line_classes(line_classes.finish());
+    if let Some(form) = command_options.normalize {
+        run_results.char_count = form.count_chars(file_content);
+    }
+    if command_options.needs_histogram() {
+        let mut histogram = Box::new([0; 256]);
+        for byte in file_content.bytes() {
+            histogram[byte as usize] += 1;
+        }
+        run_results.byte_histogram = Some(histogram);
+    }
+    if command_options.count_code {
+        // document text has no comments
+        let mut classifier = code::LineClassifier::new(&code::PLAIN_TEXT);
+        classifier.feed(file_content.as_bytes());
+        run_results.set_code_lines(classifier.finish());
+    }
+    if let Some(mut lines) = command_options.distinct_lines(file) {
+        lines.feed(file_content.as_bytes());
+        run_results.set_distinct_lines(lines.finish());
+    }
+    if let Some(mut words) = command_options.distinct_words(file) {
+        words.feed(file_content.as_bytes());
+        run_results.set_distinct_words(words.finish());
+    }
+    if command_options.count_compress_ratio {
+        let sample = &file_content.as_bytes()[..file_content.len().min(entropy::SAMPLE_LEN)];
+        run_results.sample_len = sample.len() as u64;
+        run_results.sample_compressed_len = entropy::compressed_len(sample);
+    }
 
     run_results
 }
 
+fn format_run_results(options: &CommandOptions, stats: &FileStats, topic: &str) -> String {
+    if options.posix {
+        return format_posix(&selected_counts(options, stats), topic);
+    }
+    match options.compat {
+        Compat::Gnu => return compat::format_gnu(&selected_counts(options, stats), topic, options.number_width),
+        Compat::Bsd => return compat::format_bsd(&selected_counts(options, stats), topic),
+        Compat::Native => {},
+    }
 
-/*
-Prints results based on a vec of stats and a topic
-*/
-fn print_run_results(options: &CommandOptions, stats: &FileStats, topic: &str) {
     let mut results = String::new();
 
     if options.count_lines {
-        results.push_str(format!(" {}", stats.line_count).as_str());
+        results.push_str(format!(" {}", format_count(options, stats.line_count)).as_str());
     }
 
     if options.count_words {
-        results.push_str(format!(" {}", stats.word_count).as_str());
+        results.push_str(format!(" {}", format_count(options, stats.word_count)).as_str());
     }
 
     if options.count_chars {
-        results.push_str(format!(" {}", stats.char_count).as_str());
+        results.push_str(format!(" {}", format_count(options, stats.char_count)).as_str());
     }
 
     if options.count_bytes {
-        results.push_str(format!(" {}", stats.byte_count).as_str());
+        results.push_str(format!(" {}", format_bytes(options, stats.byte_count)).as_str());
+    }
+
+    if options.count_max_line_length {
+        results.push_str(format!(" {}", format_count(options, stats.max_line_length)).as_str());
+    }
+
+    if options.count_entropy {
+        results.push_str(format!(" {}", stats.entropy_text()).as_str());
+    }
+
+    if options.count_compress_ratio {
+        results.push_str(format!(" {}", stats.compress_ratio_text()).as_str());
+    }
+
+    if options.count_code {
+        results.push_str(format!(" {} {} {}", format_count(options, stats.blank_lines), format_count(options, stats.comment_lines), format_count(options, stats.code_lines)).as_str());
+    }
+
+    if options.count_unique_lines {
+        results.push_str(format!(" {}", stats.unique_lines_text()).as_str());
+    }
+
+    if options.count_duplicate_lines {
+        results.push_str(format!(" {}", format_count(options, stats.duplicate_lines)).as_str());
+    }
+
+    if options.count_unique_words {
+        results.push_str(format!(" {}", stats.unique_words_text()).as_str());
+    }
+
+    if options.numbers.is_some() {
+        results.push_str(format!(" {}", format_count(options, stats.numbers)).as_str());
+    }
+
+    if options.count_char_classes {
+        for count in stats.char_classes {
+            results.push_str(format!(" {}", format_count(options, count)).as_str());
+        }
+    }
+
+    if options.count_non_ascii {
+        results.push_str(format!(" {}", format_count(options, stats.non_ascii)).as_str());
+    }
+
+    if options.count_emoji {
+        results.push_str(format!(" {}", format_count(options, stats.emoji)).as_str());
+    }
+
+    if options.count_whitespace {
+        for lines in stats.whitespace {
+            results.push_str(format!(" {}", format_count(options, lines)).as_str());
+        }
+    }
+
+    if let Some(wpm) = options.reading_wpm {
+        results.push_str(format!(" {}", format_count(options, reading_minutes(stats, wpm))).as_str());
+    }
+
+    for index in 0..options.count_chars_of.len() {
+        results.push_str(format!(" {}", format_count(options, *stats.char_occurrences.get(index).unwrap_or(&0))).as_str());
+    }
+
+    for index in 0..options.count_lines_of.len() {
+        results.push_str(format!(" {}", format_count(options, *stats.line_classes.get(index).unwrap_or(&0))).as_str());
+    }
+
+    for index in 0..options.registry.len() {
+        results.push_str(format!(" {}", stats.counter_report(index)).as_str());
+    }
+
+    if options.hash.is_some() {
+        results.push_str(format!(" {}", stats.hash_text()).as_str());
+    }
+
+    if options.detect_type {
+        results.push_str(format!(" {} {}", stats.file_type_text(), stats.language_text()).as_str());
     }
 
     results.push_str(format!(" {}", topic).as_str());
-    println!("{results}");
+    results
+}
+
+// --human/--si and --group-digits only change how the native rows look
+fn format_count(options: &CommandOptions, count: u64) -> String {
+    match options.group_digits {
+        Some(separator) => human::group(count, separator),
+        None => count.to_string(),
+    }
+}
+
+fn format_bytes(options: &CommandOptions, bytes: u64) -> String {
+    match options.human {
+        Some(units) => human::size(bytes, units),
+        None => format_count(options, bytes),
+    }
+}
+
+// rounded up, so a short post is a minute and not 0
+fn reading_minutes(stats: &FileStats, wpm: u64) -> u64 {
+    stats.word_count.div_ceil(wpm)
+}
+
+/*
+POSIX wants "%d %d %d %s\n": the counts in lines, words, chars/bytes order and a single space between everything, with
+no name at all for stdin.
+*/
+fn format_posix(counts: &[String], topic: &str) -> String {
+    let mut fields: Vec<String> = counts.to_vec();
+    if !topic.is_empty() {
+        fields.push(topic.to_string());
+    }
+    fields.join(" ")
+}
+
+// what selected_counts has in each position, for --check to say what changed
+fn column_names(options: &CommandOptions) -> Vec<String> {
+    let columns = [
+        (options.count_lines, "lines"),
+        (options.count_words, "words"),
+        (options.count_chars, "chars"),
+        (options.count_bytes, "bytes"),
+        (options.count_max_line_length, "max line length"),
+        (options.count_entropy, "entropy"),
+        (options.count_compress_ratio, "compress ratio"),
+        (options.count_code, "blank"),
+        (options.count_code, "comment"),
+        (options.count_code, "code"),
+        (options.count_unique_lines, "unique lines"),
+        (options.count_duplicate_lines, "duplicate lines"),
+        (options.count_unique_words, "unique words"),
+        (options.numbers.is_some(), "numbers"),
+        (options.count_char_classes, "letters"),
+        (options.count_char_classes, "digits"),
+        (options.count_char_classes, "punctuation"),
+        (options.count_char_classes, "whitespace"),
+        (options.count_char_classes, "other"),
+        (options.count_non_ascii, "non-ascii"),
+        (options.count_emoji, "emoji"),
+        (options.count_whitespace, "trailing whitespace"),
+        (options.count_whitespace, "tab indented"),
+        (options.count_whitespace, "space indented"),
+        (options.count_whitespace, "mixed indent"),
+        (options.reading_wpm.is_some(), "reading minutes"),
+    ];
+    let mut names: Vec<String> = columns.iter().filter(|(selected, _)| *selected).map(|(_, name)| name.to_string()).collect();
+    names.extend(options.count_chars_of.iter().map(|needle| format!("count {}", needle.name())));
+    names.extend(options.count_lines_of.iter().map(|class| class.name()));
+    names.extend(options.registry.names().map(String::from));
+    if options.hash.is_some() {
+        names.push(String::from("hash"));
+    }
+    if options.detect_type {
+        names.push(String::from("type"));
+        names.push(String::from("language"));
+    }
+    names
+}
+
+// the counts that were asked for, in the order every wc prints them
+fn selected_counts(options: &CommandOptions, stats: &FileStats) -> Vec<String> {
+    let mut counts = Vec::new();
+
+    if options.count_lines {
+        counts.push(stats.line_count.to_string());
+    }
+    if options.count_words {
+        counts.push(stats.word_count.to_string());
+    }
+    if options.count_chars {
+        counts.push(stats.char_count.to_string());
+    }
+    if options.count_bytes {
+        counts.push(stats.byte_count.to_string());
+    }
+    if options.count_max_line_length {
+        counts.push(stats.max_line_length.to_string());
+    }
+    if options.count_entropy {
+        counts.push(stats.entropy_text());
+    }
+    if options.count_compress_ratio {
+        counts.push(stats.compress_ratio_text());
+    }
+    if options.count_code {
+        counts.push(stats.blank_lines.to_string());
+        counts.push(stats.comment_lines.to_string());
+        counts.push(stats.code_lines.to_string());
+    }
+    if options.count_unique_lines {
+        counts.push(stats.unique_lines_text());
+    }
+    if options.count_duplicate_lines {
+        counts.push(stats.duplicate_lines.to_string());
+    }
+    if options.count_unique_words {
+        counts.push(stats.unique_words_text());
+    }
+    if options.numbers.is_some() {
+        counts.push(stats.numbers.to_string());
+    }
+    if options.count_char_classes {
+        counts.extend(stats.char_classes.iter().map(|count| count.to_string()));
+    }
+    if options.count_non_ascii {
+        counts.push(stats.non_ascii.to_string());
+    }
+    if options.count_emoji {
+        counts.push(stats.emoji.to_string());
+    }
+    if options.count_whitespace {
+        counts.extend(stats.whitespace.iter().map(|lines| lines.to_string()));
+    }
+    if let Some(wpm) = options.reading_wpm {
+        counts.push(reading_minutes(stats, wpm).to_string());
+    }
+    for index in 0..options.count_chars_of.len() {
+        counts.push(stats.char_occurrences.get(index).unwrap_or(&0).to_string());
+    }
+    for index in 0..options.count_lines_of.len() {
+        counts.push(stats.line_classes.get(index).unwrap_or(&0).to_string());
+    }
+    for index in 0..options.registry.len() {
+        counts.push(stats.counter_report(index));
+    }
+    if options.hash.is_some() {
+        counts.push(stats.hash_text().to_string());
+    }
+    if options.detect_type {
+        counts.push(stats.file_type_text().to_string());
+        counts.push(stats.language_text().to_string());
+    }
+
+    counts
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> CommandOptions {
+        let args = std::iter::once("wc_clone").chain(args.iter().copied()).map(String::from);
+        CommandOptions::build(args).unwrap()
+    }
+
+    fn counters(options: &CommandOptions) -> (bool, bool, bool, bool) {
+        (options.count_lines, options.count_words, options.count_chars, options.count_bytes)
+    }
+
+    #[test]
+    fn r_and_v_clusters_keep_the_default_counters() {
+        for cluster in ["-rv", "-vr", "-rvv"] {
+            let options = parse(&[cluster, "notes.txt"]);
+            assert_eq!(counters(&options), (true, true, false, true), "for {}", cluster);
+            assert!(options.recursive, "for {}", cluster);
+            assert_eq!(options.verbosity as usize, cluster.matches('v').count(), "for {}", cluster);
+            assert_eq!(options.files, ["notes.txt"]);
+        }
+
+        let lines = parse(&["-rl", "notes.txt"]);
+        assert_eq!(counters(&lines), (true, false, false, false));
+        assert!(lines.recursive);
+    }
+
+    #[test]
+    fn a_lone_dash_is_stdin_not_an_option() {
+        let options = parse(&["-"]);
+        assert_eq!(options.files, ["-"]);
+        assert_eq!(counters(&options), (true, true, false, true));
 
+        let options = parse(&["notes.txt", "-", "-w"]);
+        assert_eq!(options.files, ["notes.txt", "-"]);
+        assert_eq!(counters(&options), (false, true, false, false));
+    }
 
+    // the bytes counted from content with these options, read through, seeked and mapped
+    fn counted_bytes(args: &[&str], content: &[u8]) -> [u64; 3] {
+        let options = parse(args);
+        let path = std::env::temp_dir().join(format!("wc_clone-range-{}", std::process::id()));
+        fs::write(&path, content).unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let read = stream::count_reader(content, &options, "").unwrap().0.byte_count;
+        let seeked = stream::count_file(fs::File::open(&path).unwrap(), &options, &path).unwrap().0.byte_count;
+        fs::remove_file(&path).unwrap();
+        let start = (options.read_limits.offset as usize).min(content.len());
+        let mapped = options.read_limits.prefix_len(&content[start..]) as u64;
+        [read, seeked, mapped]
+    }
 
+    #[test]
+    fn byte_ranges_stay_inside_the_input_and_the_limits() {
+        let content = b"0123456789\n";
+        assert_eq!(counted_bytes(&["-c", "--offset=20"], content), [0; 3]);
+        assert_eq!(counted_bytes(&["-c", "--offset=11"], content), [0; 3]);
+        assert_eq!(counted_bytes(&["-c", "--offset=8", "--length=6"], content), [3; 3]);
+        // --max-bytes cuts the range short, whichever comes first
+        assert_eq!(counted_bytes(&["-c", "--offset=2", "--length=6", "--max-bytes=4"], content), [4; 3]);
+        assert_eq!(counted_bytes(&["-c", "--offset=2", "--length=3", "--max-bytes=4"], content), [3; 3]);
+    }
+}
@@ -0,0 +1,113 @@
+/*
+Columns counting lines of a kind, for the hygiene checks that are a grep -c away but take a pass over the file each:
+--blank-lines (nothing on them at all), --whitespace-only-lines (spaces and tabs and nothing else) and
+--count-lines-matching PREFIX for lines that start with PREFIX once their indentation is left out, # or // for
+comments in a config or a script. A \r before the newline doesn't count as being on the line, and a last line without
+a newline is a line like any other. The columns come in the order the options were given...
+
+    wc_clone --blank-lines --count-lines-matching '#' nginx.conf
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineClass {
+    Blank,
+    WhitespaceOnly,
+    Prefix(String),
+}
+
+impl LineClass {
+    pub fn prefix(value: &str) -> Result<LineClass, String> {
+        match value {
+            "" => Err(String::from("invalid value for '--count-lines-matching': '' (expected what the lines start with)")),
+            _ => Ok(LineClass::Prefix(value.to_string())),
+        }
+    }
+
+    // for naming the column
+    pub fn name(&self) -> String {
+        match self {
+            LineClass::Blank => String::from("blank lines"),
+            LineClass::WhitespaceOnly => String::from("whitespace-only lines"),
+            LineClass::Prefix(prefix) => format!("lines matching {}", prefix),
+        }
+    }
+}
+
+// counts the lines of every class in one pass over the chunks
+pub struct LineClasses {
+    classes: Vec<LineClass>,
+    counts: Vec<u64>,
+    head: Vec<u8>, // the start of the line after its indentation, as much as the longest prefix needs
+    head_len: usize,
+    in_indent: bool,
+    has_blanks: bool,  // spaces or tabs
+    has_content: bool, // anything else
+    line_empty: bool,
+}
+
+impl LineClasses {
+    pub fn new(classes: &[LineClass]) -> Self {
+        let head_len = classes.iter().map(|class| match class {
+            LineClass::Prefix(prefix) => prefix.len(),
+            _ => 0,
+        }).max().unwrap_or(0);
+        Self {
+            classes: classes.to_vec(),
+            counts: vec![0; classes.len()],
+            head: Vec::with_capacity(head_len),
+            head_len,
+            in_indent: true,
+            has_blanks: false,
+            has_content: false,
+            line_empty: true,
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        for byte in chunk {
+            match byte {
+                b'\n' => self.end_line(),
+                b'\r' => self.line_empty = false,
+                b' ' | b'\t' if self.in_indent => {
+                    self.has_blanks = true;
+                    self.line_empty = false;
+                },
+                _ => {
+                    if self.head.len() < self.head_len {
+                        self.head.push(*byte);
+                    }
+                    self.has_blanks |= matches!(byte, b' ' | b'\t');
+                    self.has_content |= !matches!(byte, b' ' | b'\t');
+                    self.in_indent = false;
+                    self.line_empty = false;
+                },
+            }
+        }
+    }
+
+    // a last line without a newline still counts
+    pub fn finish(mut self) -> Vec<u64> {
+        if !self.line_empty {
+            self.end_line();
+        }
+        self.counts
+    }
+
+    fn end_line(&mut self) {
+        for (class, count) in self.classes.iter().zip(self.counts.iter_mut()) {
+            let matches = match class {
+                LineClass::Blank => !self.has_content && !self.has_blanks,
+                LineClass::WhitespaceOnly => !self.has_content && self.has_blanks,
+                LineClass::Prefix(prefix) => self.head.starts_with(prefix.as_bytes()),
+            };
+            if matches {
+                *count += 1;
+            }
+        }
+        self.head.clear();
+        self.in_indent = true;
+        self.has_blanks = false;
+        self.has_content = false;
+        self.line_empty = true;
+    }
+}
@@ -0,0 +1,53 @@
+/*
+Diagnostics for long runs, on stderr so they never end up mixed in with the counts. -v logs the files opened, the
+strategy picked for each and the ones skipped or failed, -vv adds how long each file and every chunk read took, and
+-vvv everything else there is. --log-format=json writes one JSON object per event, for feeding into whatever collects
+logs...
+
+    wc_clone -vv --log-format=json -r src/ 2> log.json
+*/
+#[cfg(feature = "logging")]
+use std::io::{self, IsTerminal};
+
+#[cfg(feature = "logging")]
+use tracing::Level;
+
+use crate::CommandOptions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Result<LogFormat, String> {
+        match value {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("invalid value for '--log-format': '{}' (expected text or json)", value)),
+        }
+    }
+}
+
+// nothing is installed without -v, so the events cost next to nothing then. A library user that already set up a
+// subscriber of its own keeps it
+#[cfg(feature = "logging")]
+pub fn init(command_options: &CommandOptions) {
+    let level = match command_options.verbosity {
+        0 => return,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+
+    let builder = tracing_subscriber::fmt().with_max_level(level).with_writer(io::stderr);
+    let _ = match command_options.log_format {
+        LogFormat::Text => builder.with_ansi(io::stderr().is_terminal()).try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    };
+}
+
+// -v still prints its details without the logging feature, there's just nothing to write the events out
+#[cfg(not(feature = "logging"))]
+pub fn init(_command_options: &CommandOptions) {}
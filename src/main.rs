@@ -1,6 +1,3 @@
-
-use wc_clone;
-
 fn main() {
     wc_clone::run();
 }
@@ -0,0 +1,177 @@
+/*
+--generate-man prints a man page (roff, for section 1) made from the option list at the top of lib.rs, the same text
+the code is documented with, so a packager's page can't fall behind the flags: an option that gets added there is in
+the next page. The list is read the way it's written, a line starting with a dash begins an option and the lines
+after it up to a blank one describe it, "wc_clone ..." lines are the subcommands and anything else is prose...
+
+    wc_clone --generate-man > wc_clone.1
+    man ./wc_clone.1
+*/
+
+// everything between the /* and */ heading lib.rs
+fn documentation() -> &'static str {
+    let source = include_str!("lib.rs");
+    let start = source.find("/*").map_or(0, |start| start + 2);
+    let end = source[start..].find("*/").map_or(source.len(), |end| start + end);
+    &source[start..end]
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    head: String,         // the option (or the subcommand's synopsis)
+    text: Vec<String>,    // its description, a line each the way they're wrapped in the source
+    listing: Vec<String>, // indented lines, printed as they are
+}
+
+#[derive(Debug, Default)]
+struct Page {
+    summary: String,
+    options: Vec<Entry>,
+    commands: Vec<Entry>,
+    notes: Vec<Vec<String>>,
+    features: Vec<String>,
+}
+
+fn parse(documentation: &str) -> Page {
+    let mut page = Page::default();
+    let mut lines = documentation.lines().map(str::trim_end).skip_while(|line| line.trim().is_empty());
+
+    // "Description" and the one line under it
+    if lines.clone().next() == Some("Description") {
+        lines.next();
+        page.summary = lines.next().unwrap_or("").to_string();
+    }
+
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in lines.chain([""]) {
+        if line.trim().is_empty() {
+            add_paragraph(&mut page, &paragraph);
+            paragraph.clear();
+        } else {
+            paragraph.push(line);
+        }
+    }
+    page
+}
+
+fn add_paragraph(page: &mut Page, paragraph: &[&str]) {
+    let Some((first, rest)) = paragraph.split_first() else {
+        return;
+    };
+
+    if first.starts_with('-') || first.starts_with("wc_clone ") {
+        let mut entry = Entry { head: first.to_string(), ..Entry::default() };
+        for line in rest {
+            if line.starts_with(' ') {
+                entry.listing.push(line.trim().to_string());
+            } else {
+                entry.text.push(line.to_string());
+            }
+        }
+        match first.starts_with('-') {
+            true => page.options.push(entry),
+            false => page.commands.push(entry),
+        }
+    } else if *first == "Features" {
+        page.features = rest.iter().map(|line| line.to_string()).collect();
+    } else {
+        page.notes.push(paragraph.iter().map(|line| line.to_string()).collect());
+    }
+}
+
+pub fn generate() -> String {
+    let page = parse(documentation());
+    let mut man = String::new();
+
+    man.push_str(&format!(
+        ".TH WC_CLONE 1 \"{}\" \"wc_clone {}\" \"User Commands\"\n",
+        env!("WC_CLONE_BUILD_DATE"),
+        env!("CARGO_PKG_VERSION")
+    ));
+    man.push_str(".SH NAME\nwc_clone \\- print newline, word, and byte counts for each file\n");
+    man.push_str(".SH SYNOPSIS\n.B wc_clone\n[\\fIOPTION\\fR]... [\\fIFILE\\fR]...\n");
+    for command in &page.commands {
+        man.push_str(&format!(".br\n{}\n", synopsis(&command.head)));
+    }
+
+    man.push_str(".SH DESCRIPTION\n");
+    man.push_str(&text(std::slice::from_ref(&page.summary)));
+    for note in &page.notes {
+        man.push_str(".PP\n");
+        man.push_str(&text(note));
+    }
+
+    man.push_str(".SH OPTIONS\n");
+    for option in &page.options {
+        man.push_str(&format!(".TP\n{}\n", option_head(&option.head)));
+        man.push_str(&entry_body(option));
+    }
+
+    if !page.commands.is_empty() {
+        man.push_str(".SH COMMANDS\n");
+        for command in &page.commands {
+            man.push_str(&format!(".TP\n{}\n", synopsis(&command.head)));
+            man.push_str(&entry_body(command));
+        }
+    }
+
+    if !page.features.is_empty() {
+        man.push_str(".SH FEATURES\n");
+        man.push_str(&text(&page.features));
+    }
+    man
+}
+
+fn entry_body(entry: &Entry) -> String {
+    let mut body = text(&entry.text);
+    if !entry.listing.is_empty() {
+        body.push_str(".RS\n.nf\n");
+        body.push_str(&text(&entry.listing));
+        body.push_str(".fi\n.RE\n");
+    }
+    body
+}
+
+// "-j, --jobs=N" -> the names in bold and what they take in italics
+fn option_head(head: &str) -> String {
+    head.split(", ")
+        .map(|option| {
+            let split = option.find(['=', '[']).unwrap_or(option.len());
+            let (name, argument) = option.split_at(split);
+            let argument = match argument.strip_prefix("[=") {
+                Some(value) => format!("[=\\fI{}\\fR]", escape(value.trim_end_matches(']'))),
+                None if !argument.is_empty() => format!("=\\fI{}\\fR", escape(&argument[1..])),
+                None => String::new(),
+            };
+            format!("\\fB{}\\fR{}", escape(name), argument)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// "wc_clone serve [--listen=ADDR] [OPTION]..." -> the command in bold
+fn synopsis(head: &str) -> String {
+    let mut words = head.splitn(3, ' ');
+    let program = words.next().unwrap_or("");
+    let command = words.next().unwrap_or("");
+    let rest = words.next().unwrap_or("");
+    format!("\\fB{} {}\\fR {}", escape(program), escape(command), escape(rest)).trim_end().to_string()
+}
+
+fn text(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            let line = escape(line.trim());
+            // a line starting with . or ' would be taken for a request
+            match line.starts_with(['.', '\'']) {
+                true => format!("\\&{}\n", line),
+                false => format!("{}\n", line),
+            }
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
@@ -0,0 +1,95 @@
+/*
+--auto picks the way a file is counted from what's in it instead of its name: a PDF saved as report.bin still gets
+its text extracted, an HTML page gets the text a browser would show counted instead of its markup, a gzip or xz file
+counts what it decompresses to (a .tar.gz the files in it), a tar or zip archive is counted member by member, and a
+notes.pdf that's really plain text is counted as the text it is. The first 512 bytes decide:
+
+- %PDF- is a PDF, with the pdf feature
+- a zip whose first member is ODF's mimetype is an .odt, with the office feature; otherwise the content can't tell a
+  .docx from any other zip, so it's a .docx when it's named one (office feature) and an archive when it isn't (archives)
+- ustar at byte 257 is a tar archive
+- the gzip, zstd and xz magic numbers are compressed, like they are for stdin
+- <!DOCTYPE html or <html at the start (after any blank lines) is HTML
+- anything else is counted as it is
+
+A format whose feature isn't built falls back to counting the bytes, like the extension would without it. Only regular
+files are looked at, and --no-decompress and --compat keep archives and compressed files as the bytes they are...
+*/
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::decompress;
+
+// enough for the tar magic
+pub const TAR_SNIFF_LEN: u64 = 512;
+
+const ODT_MIMETYPE: &[u8] = b"mimetypeapplication/vnd.oasis.opendocument.text";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Text,
+    Html,
+    Pdf,
+    Docx,
+    Odt,
+    Zip,
+    Tar,
+    Compressed,
+}
+
+impl Kind {
+    // what the logs call it, and what the adapters are looked up by
+    pub fn mime(self) -> &'static str {
+        match self {
+            Kind::Text => "text/plain",
+            Kind::Html => "text/html",
+            Kind::Pdf => "application/pdf",
+            Kind::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            Kind::Odt => "application/vnd.oasis.opendocument.text",
+            Kind::Zip => "application/zip",
+            Kind::Tar => "application/x-tar",
+            Kind::Compressed => "application/octet-stream",
+        }
+    }
+}
+
+pub fn sniff(path: &str) -> io::Result<Kind> {
+    let mut start = Vec::new();
+    File::open(path)?.take(TAR_SNIFF_LEN).read_to_end(&mut start)?;
+    Ok(kind_of(&start, path))
+}
+
+// the name only helps with the zips that could be a document
+fn kind_of(start: &[u8], path: &str) -> Kind {
+    if start.starts_with(b"%PDF-") {
+        Kind::Pdf
+    } else if start.starts_with(b"PK\x03\x04") {
+        let named_docx = Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("docx"));
+        match start.get(30..30 + ODT_MIMETYPE.len()) {
+            Some(member) if member == ODT_MIMETYPE => Kind::Odt,
+            _ if named_docx => Kind::Docx,
+            _ => Kind::Zip,
+        }
+    } else if is_tar(start) {
+        Kind::Tar
+    } else if decompress::is_compressed(start) {
+        Kind::Compressed
+    } else if is_html(start) {
+        Kind::Html
+    } else {
+        Kind::Text
+    }
+}
+
+// ustar and GNU tar both have it, only the ancient v7 format doesn't
+pub fn is_tar(start: &[u8]) -> bool {
+    start.get(257..262) == Some(b"ustar")
+}
+
+fn is_html(start: &[u8]) -> bool {
+    let start = start.strip_prefix(b"\xef\xbb\xbf").unwrap_or(start);
+    let first = start.iter().position(|byte| !byte.is_ascii_whitespace()).unwrap_or(start.len());
+    let head = start[first..].iter().take(14).map(u8::to_ascii_lowercase).collect::<Vec<u8>>();
+    head.starts_with(b"<!doctype html") || head.starts_with(b"<html")
+}
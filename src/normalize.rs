@@ -0,0 +1,96 @@
+/*
+--normalize=nfc|nfd counts characters after Unicode normalization, so "é" is the same number of characters whether the
+file spelled it as one precomposed code point or as e plus a combining accent. Different tools (and operating systems)
+save the same text both ways. Without the normalize feature there are no forms and --normalize is refused...
+*/
+#[cfg(feature = "normalize")]
+use unicode_normalization::UnicodeNormalization;
+
+use crate::utf8::Utf8Decoder;
+
+// past this many bytes without a safe place to cut, the pending text gets normalized anyway
+const MAX_PENDING: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Form {
+    #[cfg(feature = "normalize")]
+    Nfc,
+    #[cfg(feature = "normalize")]
+    Nfd,
+}
+
+#[cfg(feature = "normalize")]
+impl Form {
+    pub fn parse(value: &str) -> Result<Form, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "nfc" => Ok(Form::Nfc),
+            "nfd" => Ok(Form::Nfd),
+            _ => Err(format!("invalid value for '--normalize': '{}' (expected nfc or nfd)", value)),
+        }
+    }
+
+    pub fn count_chars(&self, text: &str) -> u64 {
+        match self {
+            Form::Nfc => text.nfc().count() as u64,
+            Form::Nfd => text.nfd().count() as u64,
+        }
+    }
+}
+
+#[cfg(not(feature = "normalize"))]
+impl Form {
+    pub fn parse(value: &str) -> Result<Form, String> {
+        Err(format!("can't use '--normalize={}': wc_clone was built without the normalize feature", value))
+    }
+
+    pub fn count_chars(&self, _text: &str) -> u64 {
+        match *self {}
+    }
+}
+
+/*
+Normalizing needs to see a character together with the marks that follow it, so decoded text is held back until a
+point where nothing can combine across: right before an ascii character, since no ascii character ever composes with
+or reorders against what comes before it.
+*/
+pub struct Normalizer {
+    form: Form,
+    decoder: Utf8Decoder,
+    pending: String,
+    chars: u64,
+}
+
+impl Normalizer {
+    pub fn new(form: Form) -> Self {
+        Self {
+            form,
+            decoder: Utf8Decoder::new(),
+            pending: String::new(),
+            chars: 0,
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let pending = &mut self.pending;
+        self.decoder.decode(bytes, |c| pending.push(c.unwrap_or(char::REPLACEMENT_CHARACTER)));
+
+        let cut = match self.pending.rfind(|c: char| c.is_ascii()) {
+            Some(cut) => cut,
+            None if self.pending.len() > MAX_PENDING => self.pending.len(),
+            None => return,
+        };
+        self.chars += self.form.count_chars(&self.pending[..cut]);
+        self.pending.drain(..cut);
+    }
+
+    // see StreamCounter::drop_partial_char
+    pub fn drop_partial_char(&mut self) {
+        self.decoder.drop_partial_char();
+    }
+
+    pub fn finish(mut self) -> u64 {
+        let pending = &mut self.pending;
+        self.decoder.finish(|c| pending.push(c.unwrap_or(char::REPLACEMENT_CHARACTER)));
+        self.chars + self.form.count_chars(&self.pending)
+    }
+}
@@ -0,0 +1,84 @@
+/*
+--numbers counts the words that are numbers, for data heavy documents and logs where "how much of this is prose" is
+the question. A number is digits with optional . or , between them (1,000.5 and 1.000,5 both), a leading sign, an
+exponent and a trailing %, so -3, 2.5e-3 and 40% are numbers but v2, 0x1f and 12:30 aren't. Punctuation a sentence
+puts around it doesn't matter: (42) and 42. still count. With --numbers=exclude they're also left out of -w...
+*/
+use crate::stream::Separators;
+
+// nothing longer is taken for a number, it saves keeping arbitrarily long words around
+const MAX_NUMBER_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Separate, // a column of its own, the word count stays the same
+    Exclude,  // and they're not words anymore
+}
+
+impl Mode {
+    pub fn parse(value: Option<&str>) -> Result<Mode, String> {
+        match value {
+            None => Ok(Mode::Separate),
+            Some("exclude") => Ok(Mode::Exclude),
+            Some(value) => Err(format!("invalid value for '--numbers': '{}' (expected exclude)", value)),
+        }
+    }
+}
+
+pub struct NumberCounter {
+    separators: &'static Separators,
+    word: Vec<u8>,
+    too_long: bool,
+    numbers: u64,
+}
+
+impl NumberCounter {
+    pub fn new(separators: &'static Separators) -> Self {
+        Self { separators, word: Vec::new(), too_long: false, numbers: 0 }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            if *byte == b'\n' || self.separators.contains(*byte) {
+                self.end_word();
+            } else if self.word.len() < MAX_NUMBER_LEN {
+                self.word.push(*byte);
+            } else {
+                self.too_long = true;
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> u64 {
+        self.end_word();
+        self.numbers
+    }
+
+    fn end_word(&mut self) {
+        if !self.too_long && is_number(&self.word) {
+            self.numbers += 1;
+        }
+        self.word.clear();
+        self.too_long = false;
+    }
+}
+
+fn is_number(word: &[u8]) -> bool {
+    let word = word.strip_prefix(b"(").unwrap_or(word);
+    let end = word.iter().rposition(|byte| !matches!(byte, b'.' | b',' | b';' | b':' | b'!' | b'?' | b')')).map_or(0, |last| last + 1);
+    let word = &word[..end];
+    let word = word.strip_suffix(b"%").unwrap_or(word);
+    let word = word.strip_prefix(b"+").or_else(|| word.strip_prefix(b"-")).unwrap_or(word);
+
+    let (mantissa, exponent) = match word.iter().position(|byte| matches!(byte, b'e' | b'E')) {
+        Some(at) => (&word[..at], Some(&word[at + 1..])),
+        None => (word, None),
+    };
+    // digits, and a . or , only ever between two of them
+    let mantissa_ok = mantissa.split(|byte| matches!(byte, b'.' | b',')).all(|digits| !digits.is_empty() && digits.iter().all(u8::is_ascii_digit));
+    let exponent_ok = exponent.is_none_or(|exponent| {
+        let digits = exponent.strip_prefix(b"+").or_else(|| exponent.strip_prefix(b"-")).unwrap_or(exponent);
+        !digits.is_empty() && digits.iter().all(u8::is_ascii_digit)
+    });
+    mantissa_ok && exponent_ok
+}
@@ -0,0 +1,83 @@
+/*
+--count-char adds a column counting one character, for the counts wc itself can't give: semicolons in minified js,
+tabs in a TSV (the columns are the tabs per line plus one), NUL bytes in something that should be text. The character
+can be written as is or as an escape, \t \n \r \0 \\ or \xNN for a byte that doesn't have to be valid utf8 at all.
+Non-ascii characters are matched on their utf8 bytes, which is the same thing as counting them in valid utf8...
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Needle {
+    bytes: Vec<u8>,
+    name: String, // what the user wrote, for naming the column
+}
+
+impl Needle {
+    pub fn parse(value: &str) -> Result<Needle, String> {
+        let invalid = || format!("invalid value for '--count-char': '{}' (expected one character, or an escape like \\t or \\x00)", value);
+        let bytes = match value {
+            "\\t" => vec![b'\t'],
+            "\\n" => vec![b'\n'],
+            "\\r" => vec![b'\r'],
+            "\\0" => vec![b'\0'],
+            "\\\\" => vec![b'\\'],
+            _ => match value.strip_prefix("\\x") {
+                Some(hex) if hex.len() == 2 => vec![u8::from_str_radix(hex, 16).map_err(|_| invalid())?],
+                _ => {
+                    let mut chars = value.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => c.to_string().into_bytes(),
+                        _ => return Err(invalid()),
+                    }
+                },
+            },
+        };
+        Ok(Needle { bytes, name: value.to_string() })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// counts every needle in one pass over the chunks
+pub struct Occurrences {
+    needles: Vec<Vec<u8>>,
+    counts: Vec<u64>,
+    matched: Vec<usize>, // how much of each needle the last chunk ended in, a character can be split across two
+}
+
+impl Occurrences {
+    pub fn new(needles: &[Needle]) -> Self {
+        Self {
+            needles: needles.iter().map(|needle| needle.bytes.clone()).collect(),
+            counts: vec![0; needles.len()],
+            matched: vec![0; needles.len()],
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        for ((needle, count), matched) in self.needles.iter().zip(self.counts.iter_mut()).zip(self.matched.iter_mut()) {
+            if let [byte] = needle.as_slice() {
+                *count += chunk.iter().filter(|b| *b == byte).count() as u64;
+                continue;
+            }
+            // the first byte of a utf8 sequence never shows up later in it, so on a mismatch the match can only
+            // start over at this byte
+            for byte in chunk {
+                if *byte == needle[*matched] {
+                    *matched += 1;
+                } else {
+                    *matched = usize::from(*byte == needle[0]);
+                }
+                if *matched == needle.len() {
+                    *count += 1;
+                    *matched = 0;
+                }
+            }
+        }
+    }
+
+    pub fn finish(self) -> Vec<u64> {
+        self.counts
+    }
+}
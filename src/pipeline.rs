@@ -0,0 +1,225 @@
+/*
+Multi-file counting split into a producer/consumer pipeline so disks and CPUs stay busy at the same time:
+
+    reader threads  --(file jobs, chunks)-->  counter threads  --(results)-->  collector
+
+Readers grab the next file off a shared index, and for streamed files hand a job to the counters straight away and
+keep filling buffers for it while it's being counted. Counters take whichever job is next, so a slow file never holds
+the others up, and the collector puts the results back in input order. Files that don't stream (documents, mmap,
+metadata only) are just counted whole on the reader thread...
+*/
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::stream::{self, LimitedCounter};
+use crate::{decompress, interrupt, progress, timeout, CommandOptions, ReadResult};
+
+// how many chunks a reader can get ahead of the counter working on its file
+const CHUNKS_IN_FLIGHT: usize = 4;
+
+// buffers a job can have at once: the chunks in flight, the one being read and the one being counted
+pub const BUFFERS_PER_JOB: usize = CHUNKS_IN_FLIGHT + 2;
+
+enum Chunk {
+    Data(Vec<u8>),
+    End { cut_by_bytes: bool },
+    Failed(io::Error),
+}
+
+enum Job {
+    Stream { index: usize, chunks: Receiver<Chunk> },
+    Done { index: usize, result: Box<ReadResult> }, // boxed, the stats are big next to a receiver
+}
+
+/*
+Counts every file and returns the results in the same order as the files. After a Ctrl-C whatever wasn't finished
+comes back as Interrupted.
+*/
+pub fn count_all(files: &[String], command_options: &CommandOptions, jobs: usize) -> Vec<ReadResult> {
+    let mut results = Vec::with_capacity(files.len());
+    count_each(files, command_options, jobs, true, |_, result| results.push(result));
+    results
+}
+
+/*
+Hands each file's result (with its index in files) to on_result as soon as it can: in order, as soon as every file
+before it is done too, or otherwise the moment it's counted. With a single job (or file) this is just a loop over
+read_file, no threads involved.
+*/
+pub fn count_each(files: &[String], command_options: &CommandOptions, jobs: usize, in_order: bool, mut on_result: impl FnMut(usize, ReadResult)) {
+    if jobs <= 1 || files.len() <= 1 {
+        for (index, file) in files.iter().enumerate() {
+            let result = if interrupt::is_interrupted() { ReadResult::Interrupted } else { crate::read_file(file, command_options) };
+            on_result(index, result);
+        }
+        return;
+    }
+
+    // reading is mostly waiting, so fewer readers than counters is plenty to keep the counters fed
+    let readers = jobs.div_ceil(2).min(files.len());
+    let counters = jobs.min(files.len());
+
+    let next_file = AtomicUsize::new(0);
+    let (job_sender, job_receiver) = mpsc::sync_channel::<Job>(jobs);
+    let job_receiver = Mutex::new(job_receiver);
+    let (result_sender, result_receiver) = mpsc::channel::<(usize, ReadResult)>();
+
+    thread::scope(|scope| {
+        for _ in 0..readers {
+            let job_sender = job_sender.clone();
+            let next_file = &next_file;
+            scope.spawn(move || read_files(files, command_options, next_file, job_sender));
+        }
+
+        for _ in 0..counters {
+            let result_sender = result_sender.clone();
+            let job_receiver = &job_receiver;
+            scope.spawn(move || count_jobs(files, command_options, job_receiver, result_sender));
+        }
+
+        // the collector only finishes once every sender is gone, so drop ours
+        drop(job_sender);
+        drop(result_sender);
+        if in_order {
+            deliver_in_order(files.len(), result_receiver, on_result);
+        } else {
+            deliver_as_completed(files.len(), result_receiver, on_result);
+        }
+    })
+}
+
+fn read_files(files: &[String], command_options: &CommandOptions, next_file: &AtomicUsize, job_sender: SyncSender<Job>) {
+    while !interrupt::is_interrupted() {
+        let index = next_file.fetch_add(1, Ordering::Relaxed);
+        let Some(path) = files.get(index) else { return };
+
+        if !crate::is_streamed(path, command_options) {
+            let result = Box::new(crate::read_file(path, command_options));
+            if job_sender.send(Job::Done { index, result }).is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let (chunk_sender, chunks) = mpsc::sync_channel(CHUNKS_IN_FLIGHT);
+        if job_sender.send(Job::Stream { index, chunks }).is_err() {
+            return;
+        }
+        stream_file(path, command_options, chunk_sender);
+    }
+}
+
+fn stream_file(path: &str, command_options: &CommandOptions, chunk_sender: SyncSender<Chunk>) {
+    let _span = tracing::info_span!("file", path).entered();
+    let read_limits = &command_options.read_limits;
+    let opened = timeout::open(path, command_options).and_then(|file| match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => {
+            stream::seek_offset(file, read_limits).map(|file| stream::capped(Box::new(file) as Box<dyn Read + Send>, read_limits))
+        },
+        _ => decompress::wrap(file, command_options).and_then(|stream| stream::limited_reader(stream, read_limits)), // a FIFO or a device
+    });
+    let mut reader = match opened {
+        Ok(reader) => reader,
+        Err(err) => {
+            let _ = chunk_sender.send(Chunk::Failed(err));
+            return;
+        }
+    };
+
+    loop {
+        let mut buffer = vec![0; command_options.buffer_size];
+        let chunk = match stream::read_chunk(&mut reader, &mut buffer) {
+            Ok(0) => Chunk::End { cut_by_bytes: stream::was_cut(&reader, read_limits) },
+            Ok(read) => {
+                buffer.truncate(read);
+                Chunk::Data(buffer)
+            },
+            Err(err) => Chunk::Failed(err),
+        };
+
+        let is_last = !matches!(chunk, Chunk::Data(_));
+        // a failed send means the counter has seen enough (line limit), stop reading
+        if chunk_sender.send(chunk).is_err() || is_last {
+            return;
+        }
+    }
+}
+
+fn count_jobs(files: &[String], command_options: &CommandOptions, job_receiver: &Mutex<Receiver<Job>>, result_sender: mpsc::Sender<(usize, ReadResult)>) {
+    loop {
+        // only hold the lock long enough to take a job, the counting happens after it's released
+        let job = match job_receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+
+        let (index, result) = match job {
+            Ok(Job::Done { index, result }) => (index, *result),
+            Ok(Job::Stream { index, chunks }) => {
+                let _tracked = progress::track(&files[index]);
+                (index, count_chunks(&files[index], command_options, chunks))
+            },
+            Err(_) => return, // every reader is done
+        };
+
+        if result_sender.send((index, result)).is_err() {
+            return;
+        }
+    }
+}
+
+fn count_chunks(path: &str, command_options: &CommandOptions, chunks: Receiver<Chunk>) -> ReadResult {
+    let mut counter = LimitedCounter::new(command_options, path);
+
+    loop {
+        match chunks.recv() {
+            Ok(Chunk::Data(data)) => {
+                if !counter.feed(&data) {
+                    let (stats, invalid_utf8_at) = counter.finish(false);
+                    return ReadResult::Counted { stats: Box::new(stats), invalid_utf8_at };
+                }
+            },
+            Ok(Chunk::End { cut_by_bytes }) => {
+                let (stats, invalid_utf8_at) = counter.finish(cut_by_bytes);
+                return ReadResult::Counted { stats: Box::new(stats), invalid_utf8_at };
+            },
+            Ok(Chunk::Failed(err)) => return crate::read_error(err),
+            Err(_) => return ReadResult::ReadError(Box::new(io::Error::other("reader thread went away"))),
+        }
+    }
+}
+
+fn deliver_in_order(count: usize, result_receiver: Receiver<(usize, ReadResult)>, mut on_result: impl FnMut(usize, ReadResult)) {
+    let mut pending: BTreeMap<usize, ReadResult> = BTreeMap::new();
+    let mut next = 0;
+
+    for (index, result) in result_receiver {
+        pending.insert(index, result);
+        while let Some(result) = pending.remove(&next) {
+            on_result(next, result);
+            next += 1;
+        }
+    }
+
+    // after a Ctrl-C the readers stop taking new files, those never get a result
+    for index in next..count {
+        on_result(index, pending.remove(&index).unwrap_or(ReadResult::Interrupted));
+    }
+}
+
+fn deliver_as_completed(count: usize, result_receiver: Receiver<(usize, ReadResult)>, mut on_result: impl FnMut(usize, ReadResult)) {
+    let mut delivered = vec![false; count];
+    for (index, result) in result_receiver {
+        delivered[index] = true;
+        on_result(index, result);
+    }
+
+    for index in (0..count).filter(|index| !delivered[*index]) {
+        on_result(index, ReadResult::Interrupted);
+    }
+}
@@ -0,0 +1,134 @@
+/*
+--plugin loads counters from shared libraries (built with the plugins feature), for metrics that only make sense
+inside one organization and don't belong in the crate. A plugin is anything that exports these C functions, one
+state per input:
+
+    const char *wc_plugin_name(void);                       // the column, e.g. "todos"
+    void *wc_plugin_new(void);                              // a fresh state, for each input and the total
+    void *wc_plugin_clone(const void *state);
+    void wc_plugin_feed(void *state, const uint8_t *bytes, size_t len);
+    void wc_plugin_merge(void *state, const void *other);   // other's counts into state, for the total
+    uint64_t wc_plugin_report(const void *state);           // the value of the column
+    void wc_plugin_free(void *state);
+
+Inputs are counted on several threads with -j, so different states can be fed at the same time (one state is only
+ever used from one thread at a time). Chunks are whatever the reader read, anything spanning two is up to the plugin.
+Loading a library runs its initialization code, so only load plugins you'd run as a program...
+*/
+use crate::CommandOptions;
+
+#[cfg(feature = "plugins")]
+pub fn load(path: &str, command_options: &mut CommandOptions) -> Result<(), String> {
+    let api = std::sync::Arc::new(dylib::Api::load(path)?);
+    let name = api.name();
+    command_options.register_counter(name, move || Box::new(dylib::PluginCounter::new(&api)));
+    Ok(())
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn load(path: &str, _command_options: &mut CommandOptions) -> Result<(), String> {
+    Err(format!("can't load plugin '{}': wc_clone was built without the plugins feature", path))
+}
+
+#[cfg(feature = "plugins")]
+mod dylib {
+    use std::any::Any;
+    use std::ffi::{c_char, c_void, CStr};
+    use std::fmt;
+    use std::sync::Arc;
+
+    use libloading::Library;
+
+    use crate::Counter;
+
+    // the functions a plugin exports, valid for as long as the library stays loaded
+    pub struct Api {
+        name: unsafe extern "C" fn() -> *const c_char,
+        new: unsafe extern "C" fn() -> *mut c_void,
+        clone: unsafe extern "C" fn(*const c_void) -> *mut c_void,
+        feed: unsafe extern "C" fn(*mut c_void, *const u8, usize),
+        merge: unsafe extern "C" fn(*mut c_void, *const c_void),
+        report: unsafe extern "C" fn(*const c_void) -> u64,
+        free: unsafe extern "C" fn(*mut c_void),
+        _library: Library, // last, so it's unloaded after everything above is gone
+    }
+
+    impl Api {
+        pub fn load(path: &str) -> Result<Api, String> {
+            let error = |err: libloading::Error| format!("can't load plugin '{}': {}", path, err);
+            // SAFETY: loading runs the library's initializers, which is what asking for a plugin means. The symbols are
+            // trusted to have the signatures documented in plugin.rs, there's no way to check them
+            unsafe {
+                let library = Library::new(path).map_err(error)?;
+                Ok(Api {
+                    name: *library.get(b"wc_plugin_name\0").map_err(error)?,
+                    new: *library.get(b"wc_plugin_new\0").map_err(error)?,
+                    clone: *library.get(b"wc_plugin_clone\0").map_err(error)?,
+                    feed: *library.get(b"wc_plugin_feed\0").map_err(error)?,
+                    merge: *library.get(b"wc_plugin_merge\0").map_err(error)?,
+                    report: *library.get(b"wc_plugin_report\0").map_err(error)?,
+                    free: *library.get(b"wc_plugin_free\0").map_err(error)?,
+                    _library: library,
+                })
+            }
+        }
+
+        pub fn name(&self) -> String {
+            // SAFETY: a NUL terminated string that lives as long as the library
+            let name = unsafe { (self.name)() };
+            if name.is_null() {
+                return String::from("plugin");
+            }
+            unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned()
+        }
+    }
+
+    pub struct PluginCounter {
+        api: Arc<Api>,
+        state: *mut c_void,
+    }
+
+    // SAFETY: a state is only used by whoever owns the PluginCounter, plugins are required to cope with that being
+    // a different thread than the one that made it
+    unsafe impl Send for PluginCounter {}
+
+    impl PluginCounter {
+        pub fn new(api: &Arc<Api>) -> Self {
+            Self { api: Arc::clone(api), state: unsafe { (api.new)() } }
+        }
+    }
+
+    impl Counter for PluginCounter {
+        fn feed(&mut self, chunk: &[u8]) {
+            unsafe { (self.api.feed)(self.state, chunk.as_ptr(), chunk.len()) };
+        }
+
+        fn merge(&mut self, other: &dyn Counter) {
+            if let Some(other) = (other as &dyn Any).downcast_ref::<PluginCounter>() {
+                unsafe { (self.api.merge)(self.state, other.state) };
+            }
+        }
+
+        fn report(&self) -> String {
+            unsafe { (self.api.report)(self.state) }.to_string()
+        }
+    }
+
+    impl Clone for PluginCounter {
+        fn clone(&self) -> Self {
+            Self { api: Arc::clone(&self.api), state: unsafe { (self.api.clone)(self.state) } }
+        }
+    }
+
+    impl Drop for PluginCounter {
+        fn drop(&mut self) {
+            unsafe { (self.api.free)(self.state) };
+        }
+    }
+
+    impl fmt::Debug for PluginCounter {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("PluginCounter").field("report", &self.report()).finish()
+        }
+    }
+}
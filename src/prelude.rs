@@ -0,0 +1,99 @@
+/*
+wc_clone as a library: use wc_clone::prelude::* and count a string or any reader the way the binary counts a file.
+What's here is the stable part, it only changes in a breaking way with a new major version:
+
+- count_str and count_reader
+- CountOptions, set up with its builder methods or from the same options the binary takes
+- FileStats and its accessors, for the counts that were asked for (the others can be 0 or counted anyway)
+
+Everything else public is listed where lib.rs re-exports it, and can still change between minor versions.
+tests/prelude.rs goes through all of the stable part, and examples/count.rs shows it in use...
+
+    let stats = count_str("one two\nthree\n", &CountOptions::new());
+    assert_eq!((stats.lines(), stats.words()), (2, 3));
+*/
+use std::io::{self, Read};
+
+use crate::i18n::tr;
+use crate::utf8::Utf8Mode;
+use crate::{stream, CommandOptions};
+
+pub use crate::FileStats;
+
+// what gets counted and how. new() is what wc counts without options: lines, words and bytes
+#[derive(Debug)]
+pub struct CountOptions {
+    inner: CommandOptions,
+}
+
+impl CountOptions {
+    pub fn new() -> Self {
+        let mut inner = CommandOptions::new();
+        inner.count_lines = true;
+        inner.count_words = true;
+        inner.count_bytes = true;
+        CountOptions { inner }
+    }
+
+    // from wc_clone's own options (["-lm", "--max-lines=100"]), without the program name and without files
+    pub fn parse<S: Into<String>>(args: impl IntoIterator<Item = S>) -> Result<Self, String> {
+        let args = std::iter::once(String::from("wc_clone")).chain(args.into_iter().map(Into::into));
+        let inner = CommandOptions::build(args)?;
+        if let Some(file) = inner.files.first() {
+            return Err(format!("CountOptions::parse takes options, not files: '{}'", file));
+        }
+        Ok(CountOptions { inner })
+    }
+
+    pub fn lines(mut self, count: bool) -> Self {
+        self.inner.count_lines = count;
+        self
+    }
+
+    pub fn words(mut self, count: bool) -> Self {
+        self.inner.count_words = count;
+        self
+    }
+
+    pub fn chars(mut self, count: bool) -> Self {
+        self.inner.count_chars = count;
+        self
+    }
+
+    pub fn bytes(mut self, count: bool) -> Self {
+        self.inner.count_bytes = count;
+        self
+    }
+
+    pub fn max_line_length(mut self, count: bool) -> Self {
+        self.inner.count_max_line_length = count;
+        self
+    }
+
+    // how many columns apart tab stops are for max_line_length (8 without it)
+    pub fn tab_width(mut self, columns: usize) -> Self {
+        self.inner.tab_width = columns.max(1);
+        self
+    }
+}
+
+impl Default for CountOptions {
+    fn default() -> Self {
+        CountOptions::new()
+    }
+}
+
+pub fn count_str(text: &str, options: &CountOptions) -> FileStats {
+    // reading a slice can't fail, and a str is valid UTF-8 for --strict
+    count_reader(text.as_bytes(), options).unwrap_or_default()
+}
+
+// everything the reader has (within --max-bytes and the like), input that isn't UTF-8 being an error with --strict
+pub fn count_reader(reader: impl Read + Send, options: &CountOptions) -> io::Result<FileStats> {
+    match stream::count_reader(reader, &options.inner, "")? {
+        (_, Some(offset)) if options.inner.utf8_mode == Utf8Mode::Strict => {
+            Err(io::Error::new(io::ErrorKind::InvalidData, tr!("invalid UTF-8 at byte {}", offset)))
+        },
+        (stats, _) => Ok(stats),
+    }
+}
@@ -0,0 +1,78 @@
+/*
+--idle, for a big scan that should only get the machine when nothing else wants it: the lowest CPU priority and the
+idle IO class, each the way the platform has it. On Linux that's nice 19 and ionice -c3 (the disk only serves us when
+nobody else is waiting on it), on macOS the background policy (PRIO_DARWIN_BG, which lowers both and throttles the
+IO), on Windows background mode (PROCESS_MODE_BACKGROUND_BEGIN, same idea) and on the BSDs nice 19 alone, they have no
+IO priority to set. Both are per thread on Linux and threads get them from the one that started them, so this has to
+happen before the pipeline starts any...
+*/
+use std::io;
+
+// ioprio_set(2), which libc has no wrapper for
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+
+// the lowest there is
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+const NICEST: libc::c_int = 19;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn idle() -> io::Result<()> {
+    nice()?;
+    // SAFETY: only changes the IO priority of the calling thread, no memory is involved
+    match unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT) } {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn idle() -> io::Result<()> {
+    // SAFETY: as above, the priority of this process and nothing else
+    match unsafe { libc::setpriority(libc::PRIO_DARWIN_PROCESS, 0, libc::PRIO_DARWIN_BG) } {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios"))))]
+pub fn idle() -> io::Result<()> {
+    nice()
+}
+
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+fn nice() -> io::Result<()> {
+    // SAFETY: as above
+    match unsafe { libc::setpriority(libc::PRIO_PROCESS as _, 0, NICEST) } {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(windows)]
+pub fn idle() -> io::Result<()> {
+    use std::ffi::c_void;
+
+    const PROCESS_MODE_BACKGROUND_BEGIN: u32 = 0x0010_0000;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn SetPriorityClass(process: *mut c_void, priority_class: u32) -> i32;
+    }
+
+    // SAFETY: GetCurrentProcess is a pseudo handle that needs no closing, and the call only changes our own priority
+    match unsafe { SetPriorityClass(GetCurrentProcess(), PROCESS_MODE_BACKGROUND_BEGIN) } {
+        0 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn idle() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "there's no priority to lower on this platform"))
+}
@@ -0,0 +1,89 @@
+/*
+dd-style progress snapshots: SIGUSR1 (or SIGINFO, Ctrl-T, on BSD/macOS) prints the files being counted, how many bytes
+have gone through the counters and the throughput so far to stderr. The signal handler only sets a flag since printing
+isn't safe in there, a small watcher thread notices it and does the printing...
+*/
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static SNAPSHOT_REQUESTED: AtomicBool = AtomicBool::new(false);
+static BYTES_PROCESSED: AtomicU64 = AtomicU64::new(0);
+static IN_PROGRESS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+// how often the watcher checks for a request, short enough to feel instant
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// marks a file as being counted until it's dropped
+pub struct Tracked {
+    path: String,
+}
+
+#[cfg(unix)]
+pub fn install() {
+    STARTED.get_or_init(Instant::now);
+
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_snapshot_signal as *const () as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART; // unlike Ctrl-C this shouldn't disturb any reads
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut());
+        #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly"))]
+        libc::sigaction(libc::SIGINFO, &action, std::ptr::null_mut());
+    }
+
+    thread::spawn(|| loop {
+        thread::sleep(POLL_INTERVAL);
+        if SNAPSHOT_REQUESTED.swap(false, Ordering::Relaxed) {
+            eprintln!("{}", snapshot());
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn install() {
+    STARTED.get_or_init(Instant::now);
+}
+
+#[cfg(unix)]
+extern "C" fn on_snapshot_signal(_signal: libc::c_int) {
+    SNAPSHOT_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+pub fn track(path: &str) -> Tracked {
+    if let Ok(mut in_progress) = IN_PROGRESS.lock() {
+        in_progress.push(path.to_string());
+    }
+    Tracked { path: path.to_string() }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        if let Ok(mut in_progress) = IN_PROGRESS.lock() {
+            if let Some(i) = in_progress.iter().position(|path| *path == self.path) {
+                in_progress.remove(i);
+            }
+        }
+    }
+}
+
+// called by the counters for every chunk they see
+pub fn add_bytes(bytes: usize) {
+    BYTES_PROCESSED.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+fn snapshot() -> String {
+    let bytes = BYTES_PROCESSED.load(Ordering::Relaxed);
+    let elapsed = STARTED.get().map_or(0.0, |started| started.elapsed().as_secs_f64());
+    let throughput = if elapsed > 0.0 { bytes as f64 / elapsed / 1_000_000.0 } else { 0.0 };
+    let current = match IN_PROGRESS.lock() {
+        Ok(in_progress) if !in_progress.is_empty() => in_progress.join(", "),
+        _ => String::from("(between files)"),
+    };
+
+    format!("wc_clone: counting {}: {} bytes processed in {:.1} s, {:.1} MB/s", current, bytes, elapsed, throughput)
+}
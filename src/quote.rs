@@ -0,0 +1,86 @@
+/*
+--quote-names, for names with a newline, a tab or some other control character in them, which would otherwise break a
+row in two or look like a different name. shell writes them the way GNU ls and wc do, so they can be pasted back
+into a shell: 'a b', 'it'\''s' and 'a'$'\n''b', names with nothing special in them staying as they are. c-style puts
+every name in double quotes with C escapes, "a\nb" and "\033[0m", for anything that's going to parse the output...
+*/
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteStyle {
+    Never,
+    Shell,
+    CStyle,
+}
+
+impl QuoteStyle {
+    pub fn parse(value: &str) -> Result<QuoteStyle, String> {
+        match value {
+            "never" => Ok(QuoteStyle::Never),
+            "shell" => Ok(QuoteStyle::Shell),
+            "c-style" => Ok(QuoteStyle::CStyle),
+            _ => Err(format!("invalid value for '--quote-names': '{}' (expected never, shell or c-style)", value)),
+        }
+    }
+
+    // stdin's empty name stays empty
+    pub fn quote(self, name: &str) -> Cow<'_, str> {
+        match self {
+            _ if name.is_empty() => Cow::Borrowed(name),
+            QuoteStyle::Never => Cow::Borrowed(name),
+            QuoteStyle::Shell if !name.chars().all(is_shell_safe) => Cow::Owned(shell(name)),
+            QuoteStyle::Shell => Cow::Borrowed(name),
+            QuoteStyle::CStyle => Cow::Owned(c_style(name)),
+        }
+    }
+}
+
+// what a shell leaves alone unquoted, non-ascii letters included
+fn is_shell_safe(c: char) -> bool {
+    c.is_alphanumeric() || "%+,-./:=@_^".contains(c)
+}
+
+fn shell(name: &str) -> String {
+    let mut quoted = String::from("'");
+    for c in name.chars() {
+        match c {
+            '\'' => quoted.push_str("'\\''"),
+            c if c.is_control() => quoted.push_str(&format!("'$'{}''", escape(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    // 'a'$'\n' rather than 'a'$'\n'''
+    match quoted.strip_suffix("''") {
+        Some(shorter) if !shorter.is_empty() => shorter.to_string(),
+        _ => quoted,
+    }
+}
+
+fn c_style(name: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in name.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            c if c.is_control() || c == '\\' => quoted.push_str(&escape(c)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// the escapes both styles share, and the UTF-8 bytes in octal for the controls without a letter of their own
+fn escape(c: char) -> String {
+    match c {
+        '\n' => String::from("\\n"),
+        '\t' => String::from("\\t"),
+        '\r' => String::from("\\r"),
+        '\x07' => String::from("\\a"),
+        '\x08' => String::from("\\b"),
+        '\x0b' => String::from("\\v"),
+        '\x0c' => String::from("\\f"),
+        '\\' => String::from("\\\\"),
+        c => c.to_string().bytes().map(|byte| format!("\\{:03o}", byte)).collect(),
+    }
+}
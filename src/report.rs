@@ -0,0 +1,501 @@
+/*
+Where the rows go. run_on_files and run_from_stdin hand each row to a Reporter along with the writer it should go to,
+so a caller can format rows its own way or capture them in a buffer, a file or a socket instead of stdout. The
+messages about inputs that couldn't be read go through the reporter too, to the same writer unless it says otherwise.
+--output-format picks one of the reporters here, and --output sends what it writes to a file (see ToFile)...
+*/
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+use crate::i18n::tr;
+use crate::summary::{self, Summary};
+use crate::{entropy, freq, snapshot, width, CommandOptions, FileStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+    Html,
+    Markdown,
+    #[cfg(feature = "arrow")]
+    Parquet,
+    #[cfg(feature = "arrow")]
+    Arrow,
+}
+
+impl OutputFormat {
+    pub fn parse(value: &str) -> Result<OutputFormat, String> {
+        match value {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "html" => Ok(OutputFormat::Html),
+            "md" | "markdown" => Ok(OutputFormat::Markdown),
+            #[cfg(feature = "arrow")]
+            "parquet" => Ok(OutputFormat::Parquet),
+            #[cfg(feature = "arrow")]
+            "arrow" => Ok(OutputFormat::Arrow),
+            #[cfg(not(feature = "arrow"))]
+            "parquet" | "arrow" => Err(format!("can't write {}: wc_clone was built without the arrow feature", value)),
+            _ => Err(format!("invalid value for '--output-format': '{}' (expected text, json, csv, html, md, parquet or arrow)", value)),
+        }
+    }
+
+    // not something a terminal can show
+    pub fn is_binary(self) -> bool {
+        match self {
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet | OutputFormat::Arrow => true,
+            _ => false,
+        }
+    }
+}
+
+// an input that couldn't be counted
+#[derive(Debug)]
+pub struct Failure {
+    pub file: String,
+    pub error: String,
+    pub kind: io::ErrorKind,
+    pub message: String, // the line the text report has for it
+    pub before: usize,   // how many rows came before it
+}
+
+// --run-info, for reports that get archived and looked at long after: what made them, with what, when and where
+#[derive(Debug)]
+pub struct RunInfo {
+    pub version: &'static str,
+    pub arguments: Vec<String>, // the options, not the inputs
+    pub started: SystemTime,
+    pub finished: SystemTime,
+    pub host: String,
+}
+
+impl RunInfo {
+    // for a run that's just done
+    pub fn finished(arguments: &[String], started: SystemTime) -> Self {
+        RunInfo { version: env!("CARGO_PKG_VERSION"), arguments: arguments.to_vec(), started, finished: SystemTime::now(), host: hostname() }
+    }
+}
+
+#[cfg(unix)]
+fn hostname() -> String {
+    let mut name = [0u8; 256];
+    // SAFETY: gethostname writes at most the length we give it into a buffer that big
+    if unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len()) } != 0 {
+        return String::new();
+    }
+    let len = name.iter().position(|&byte| byte == 0).unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
+}
+
+// 2026-10-14T09:30:00Z, in UTC since there's no time zone database to go by
+fn rfc3339(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let (days, of_day) = (seconds / 86400, seconds % 86400);
+
+    // days since 1970 to a date, Howard Hinnant's civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let of_era = z.rem_euclid(146_097);
+    let year_of_era = (of_era - of_era / 1460 + of_era / 36_524 - of_era / 146_096) / 365;
+    let day_of_year = of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, of_day / 3600, of_day % 3600 / 60, of_day % 60)
+}
+
+pub trait Reporter {
+    // a row of counts, for an input, a --group-by group or the total
+    fn row(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()>;
+
+    // the total's row, a row like any other for the formats that don't set it apart (a file can be called total)
+    fn total(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats) -> io::Result<()> {
+        self.row(out, options, stats, "total")
+    }
+
+    // whatever comes under an input's (or the total's) row, nothing unless the reporter has something to add
+    fn details(&mut self, _out: &mut impl Write, _options: &CommandOptions, _stats: &FileStats, _topic: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    // --summary, after the total. Only the text and markdown reports have one
+    fn summary(&mut self, _out: &mut impl Write, _options: &CommandOptions, _summary: &Summary) -> io::Result<()> {
+        Ok(())
+    }
+
+    // an input that couldn't be counted or was skipped, in between the rows
+    fn message(&mut self, out: &mut impl Write, text: &str) -> io::Result<()> {
+        writeln!(out, "{}", text)
+    }
+
+    // after the total, every input that couldn't be counted
+    fn errors(&mut self, out: &mut impl Write, failures: &[Failure]) -> io::Result<()> {
+        list_failures(self, out, failures)
+    }
+
+    // whether errors puts them in the report itself rather than passing them on as messages
+    fn keeps_errors(&self) -> bool {
+        false
+    }
+
+    // --run-info, after everything else. Only the json and csv reports have it
+    fn run_info(&mut self, _out: &mut impl Write, _info: &RunInfo) -> io::Result<()> {
+        Ok(())
+    }
+
+    // after the last row, for formats that have to close something
+    fn finish(&mut self, _out: &mut impl Write) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn list_failures(reporter: &mut (impl Reporter + ?Sized), out: &mut impl Write, failures: &[Failure]) -> io::Result<()> {
+    match failures.len() {
+        0 => return Ok(()),
+        1 => reporter.message(out, &tr!("wc_clone: 1 input couldn't be counted:"))?,
+        count => reporter.message(out, &tr!("wc_clone: {} inputs couldn't be counted:", count))?,
+    }
+    for failure in failures {
+        reporter.message(out, &failure.message)?;
+    }
+    Ok(())
+}
+
+/*
+The rows wc_clone prints, --compat and --posix included, with --show-longest and --byte-histogram under them. With
+--print0 every line ends in a NUL instead of a newline and loses the space in front of it, for xargs -0 and the like,
+and the messages go to stderr so nothing but rows is left to split.
+*/
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TextReporter {
+    print0: bool,
+}
+
+impl TextReporter {
+    pub fn print0() -> Self {
+        TextReporter { print0: true }
+    }
+
+    fn write_line(&self, out: &mut impl Write, line: &str) -> io::Result<()> {
+        match self.print0 {
+            true => write!(out, "{}\0", line),
+            false => writeln!(out, "{}", line),
+        }
+    }
+}
+
+impl Reporter for TextReporter {
+    fn row(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        let row = crate::format_run_results(options, stats, topic);
+        match (self.print0, topic.is_empty()) {
+            (true, true) => self.write_line(out, row.trim()), // stdin, no name after the space
+            (true, false) => self.write_line(out, row.trim_start()),
+            (false, _) => self.write_line(out, &row),
+        }
+    }
+
+    fn details(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        if let Some(longest) = longest_line(options, stats, topic) {
+            self.write_line(out, &longest)?;
+        }
+        if let Some(histogram) = histogram_line(options, stats, topic) {
+            self.write_line(out, &histogram)?;
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, out: &mut impl Write, text: &str) -> io::Result<()> {
+        match self.print0 {
+            true => writeln!(io::stderr(), "{}", text),
+            false => writeln!(out, "{}", text),
+        }
+    }
+
+    // a column for each count, as wide as its name or its widest value
+    fn summary(&mut self, out: &mut impl Write, _options: &CommandOptions, summary: &Summary) -> io::Result<()> {
+        let rows: Vec<(&str, Vec<String>)> = summary.rows().map(|(name, values)| (name, values.iter().map(|value| summary::format_value(*value)).collect())).collect();
+        let widths: Vec<usize> = summary.columns().iter().enumerate()
+            .map(|(i, name)| rows.iter().map(|(_, values)| values[i].len()).chain([name.len()]).max().unwrap_or(0))
+            .collect();
+
+        let plural = if summary.files() == 1 { "" } else { "s" };
+        self.write_line(out, &format!("summary of {} file{}", summary.files(), plural))?;
+        let header: String = summary.columns().iter().zip(&widths).map(|(name, width)| format!("  {:>width$}", name, width = width)).collect();
+        self.write_line(out, &format!("{:6}{}", "", header))?;
+        for (name, values) in &rows {
+            let line: String = values.iter().zip(&widths).map(|(value, width)| format!("  {:>width$}", value, width = width)).collect();
+            self.write_line(out, &format!("{:6}{}", name, line))?;
+        }
+        Ok(())
+    }
+}
+
+/*
+--show-longest, FILE:LINE like grep -n so editors and terminals can jump to it, then the start of the line with
+=content. Totals don't have a longest line of their own.
+*/
+fn longest_line(options: &CommandOptions, stats: &FileStats, topic: &str) -> Option<String> {
+    let line = stats.longest_line.filter(|_| options.show_longest)?;
+
+    match &stats.longest_text {
+        Some(text) => {
+            let text = text.trim();
+            let shown: String = text.chars().take(width::SHOWN_CHARS).collect();
+            let ellipsis = if shown.len() < text.len() { "..." } else { "" };
+            Some(format!("{}:{}: {}{}", topic, line, shown, ellipsis))
+        },
+        None => Some(format!("{}:{}", topic, line)),
+    }
+}
+
+fn histogram_line(options: &CommandOptions, stats: &FileStats, topic: &str) -> Option<String> {
+    match (options.byte_histogram, stats.byte_histogram.as_deref()) {
+        (Some(format), Some(histogram)) => Some(entropy::format_histogram(histogram, topic, format)),
+        _ => None,
+    }
+}
+
+/*
+An array with an object for each row, the file and then the counts under the same keys snapshots use. The inputs that
+couldn't be counted come after the rows as {"file", "error", "kind"} objects, kind being the io::ErrorKind in
+snake_case (not_found, permission_denied, invalid_data...), so they can't be mistaken for files with nothing in them.
+*/
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonReporter {
+    rows: usize,
+}
+
+impl JsonReporter {
+    fn write_object(&mut self, out: &mut impl Write, object: serde_json::Map<String, Value>) -> io::Result<()> {
+        let separator = if self.rows == 0 { "[\n" } else { ",\n" };
+        self.rows += 1;
+        write!(out, "{}  {}", separator, Value::Object(object))
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn row(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        let mut row = serde_json::Map::new();
+        row.insert(String::from("file"), if topic.is_empty() { Value::Null } else { Value::from(topic) }); // stdin
+        row.extend(snapshot::counts_object(&snapshot::column_keys(options), options, stats));
+        self.write_object(out, row)
+    }
+
+    fn errors(&mut self, out: &mut impl Write, failures: &[Failure]) -> io::Result<()> {
+        for failure in failures {
+            let mut record = serde_json::Map::new();
+            record.insert(String::from("file"), Value::from(failure.file.as_str()));
+            record.insert(String::from("error"), Value::from(failure.error.as_str()));
+            record.insert(String::from("kind"), Value::from(kind_name(failure.kind)));
+            self.write_object(out, record)?;
+        }
+        Ok(())
+    }
+
+    fn keeps_errors(&self) -> bool {
+        true
+    }
+
+    // {"run": {...}}, which no row has
+    fn run_info(&mut self, out: &mut impl Write, info: &RunInfo) -> io::Result<()> {
+        let mut run = serde_json::Map::new();
+        run.insert(String::from("version"), Value::from(info.version));
+        run.insert(String::from("options"), Value::from(info.arguments.clone()));
+        run.insert(String::from("started"), Value::from(rfc3339(info.started)));
+        run.insert(String::from("finished"), Value::from(rfc3339(info.finished)));
+        run.insert(String::from("host"), Value::from(info.host.as_str()));
+        let mut object = serde_json::Map::new();
+        object.insert(String::from("run"), Value::Object(run));
+        self.write_object(out, object)
+    }
+
+    // anything else in the output would make it something a JSON parser can't read
+    fn message(&mut self, _out: &mut impl Write, text: &str) -> io::Result<()> {
+        writeln!(io::stderr(), "{}", text)
+    }
+
+    fn finish(&mut self, out: &mut impl Write) -> io::Result<()> {
+        match self.rows {
+            0 => writeln!(out, "[]"),
+            _ => writeln!(out, "\n]"),
+        }
+    }
+}
+
+// a header with the same keys as JSON, then a line for each row
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CsvReporter {
+    header_written: bool,
+}
+
+impl Reporter for CsvReporter {
+    fn row(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        if !self.header_written {
+            writeln!(out, "file,{}", snapshot::column_keys(options).join(","))?;
+            self.header_written = true;
+        }
+        let counts: Vec<String> = crate::selected_counts(options, stats).iter().map(|count| freq::csv_field(count)).collect();
+        writeln!(out, "{},{}", freq::csv_field(topic), counts.join(","))
+    }
+
+    fn message(&mut self, _out: &mut impl Write, text: &str) -> io::Result<()> {
+        writeln!(io::stderr(), "{}", text)
+    }
+
+    // # comment lines, which pandas (comment='#'), DuckDB and the like can be told to skip
+    fn run_info(&mut self, out: &mut impl Write, info: &RunInfo) -> io::Result<()> {
+        writeln!(out, "# wc_clone {}", info.version)?;
+        writeln!(out, "# options: {}", info.arguments.join(" "))?;
+        writeln!(out, "# started: {}", rfc3339(info.started))?;
+        writeln!(out, "# finished: {}", rfc3339(info.finished))?;
+        writeln!(out, "# host: {}", info.host)
+    }
+}
+
+// NotFound as not_found
+fn kind_name(kind: io::ErrorKind) -> String {
+    let mut name = String::new();
+    for c in format!("{:?}", kind).chars() {
+        if c.is_ascii_uppercase() && !name.is_empty() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+// a GitHub flavored markdown table, to paste into a PR or an issue. The counts are right aligned, the total is bold
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MarkdownReporter {
+    header_written: bool,
+}
+
+impl MarkdownReporter {
+    fn write_row(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        if !self.header_written {
+            let names = crate::column_names(options);
+            writeln!(out, "| file | {} |", names.join(" | "))?;
+            writeln!(out, "| --- |{}", " ---: |".repeat(names.len()))?;
+            self.header_written = true;
+        }
+
+        let counts = crate::selected_counts(options, stats);
+        writeln!(out, "| {} | {} |", markdown_cell(topic), counts.iter().map(|count| markdown_cell(count)).collect::<Vec<_>>().join(" | "))
+    }
+}
+
+impl Reporter for MarkdownReporter {
+    fn row(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        let topic = match topic {
+            "" => String::from("(standard input)"),
+            _ => format!("`{}`", topic.replace('`', "'")),
+        };
+        self.write_row(out, options, stats, &topic)
+    }
+
+    fn total(&mut self, out: &mut impl Write, options: &CommandOptions, stats: &FileStats) -> io::Result<()> {
+        self.write_row(out, options, stats, "**total**")
+    }
+
+    fn summary(&mut self, out: &mut impl Write, _options: &CommandOptions, summary: &Summary) -> io::Result<()> {
+        writeln!(out, "\n| summary of {} files | {} |", summary.files(), summary.columns().join(" | "))?;
+        writeln!(out, "| --- |{}", " ---: |".repeat(summary.columns().len()))?;
+        for (name, values) in summary.rows() {
+            let values: Vec<String> = values.iter().map(|value| summary::format_value(*value)).collect();
+            writeln!(out, "| {} | {} |", name, values.join(" | "))?;
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, _out: &mut impl Write, text: &str) -> io::Result<()> {
+        writeln!(io::stderr(), "{}", text)
+    }
+}
+
+// a | would end the cell early, even inside `code`
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/*
+--output: the report goes to a file while the terminal gets what's worth seeing there, the messages about inputs that
+couldn't be counted and a line saying where it all went with the last row (the total, if there's more than one file).
+*/
+pub struct ToFile<R: Reporter> {
+    reporter: R,
+    file: BufWriter<File>,
+    path: String,
+    rows: usize,
+    last_row: Option<String>,
+}
+
+impl<R: Reporter> ToFile<R> {
+    pub fn create(reporter: R, path: &str) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        Ok(Self { reporter, file, path: path.to_string(), rows: 0, last_row: None })
+    }
+}
+
+impl<R: Reporter> Reporter for ToFile<R> {
+    fn row(&mut self, _out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        self.rows += 1;
+        self.last_row = Some(crate::format_run_results(options, stats, topic));
+        self.reporter.row(&mut self.file, options, stats, topic)
+    }
+
+    fn total(&mut self, _out: &mut impl Write, options: &CommandOptions, stats: &FileStats) -> io::Result<()> {
+        self.rows += 1;
+        self.last_row = Some(crate::format_run_results(options, stats, "total"));
+        self.reporter.total(&mut self.file, options, stats)
+    }
+
+    fn details(&mut self, _out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        self.reporter.details(&mut self.file, options, stats, topic)
+    }
+
+    fn summary(&mut self, _out: &mut impl Write, options: &CommandOptions, summary: &Summary) -> io::Result<()> {
+        self.reporter.summary(&mut self.file, options, summary)
+    }
+
+    fn message(&mut self, out: &mut impl Write, text: &str) -> io::Result<()> {
+        writeln!(out, "{}", text)
+    }
+
+    fn run_info(&mut self, _out: &mut impl Write, info: &RunInfo) -> io::Result<()> {
+        self.reporter.run_info(&mut self.file, info)
+    }
+
+    // the terminal gets the messages either way
+    fn errors(&mut self, out: &mut impl Write, failures: &[Failure]) -> io::Result<()> {
+        if self.reporter.keeps_errors() {
+            self.reporter.errors(&mut self.file, failures)?;
+        }
+        list_failures(self, out, failures)
+    }
+
+    fn finish(&mut self, out: &mut impl Write) -> io::Result<()> {
+        self.reporter.finish(&mut self.file)?;
+        self.file.flush()?;
+        if let Some(last_row) = &self.last_row {
+            writeln!(out, "{}", last_row)?;
+        }
+        let wrote = match self.rows {
+            1 => tr!("wc_clone: wrote {} row to {}", self.rows, self.path),
+            _ => tr!("wc_clone: wrote {} rows to {}", self.rows, self.path),
+        };
+        writeln!(out, "{}", wrote)
+    }
+}
@@ -0,0 +1,154 @@
+/*
+The selftest subcommand runs the system wc and wc_clone --compat=gnu side by side over a corpus and reports every line
+where they disagree, for chasing the last differences from GNU wc. Both get the same files, a couple of hundred at a
+time so totals are compared too, with each of the usual option sets and then each file again through stdin (where
+GNU pads differently). An option set the system wc doesn't know (-L on BSD) is skipped with a note. It exits with 1 if
+anything differed...
+
+    wc_clone selftest [--wc=PATH] [DIR]...
+    LC_ALL=C.UTF-8 wc_clone selftest ~/corpus
+*/
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::process::{self, Command, Stdio};
+
+use crate::i18n::tr;
+use crate::walk::{self, WalkOptions};
+
+const OPTION_SETS: &[&[&str]] = &[&[], &["-c"], &["-m"], &["-l"], &["-w"], &["-L"], &["-lwmcL"]];
+
+// files per run, enough for totals to matter without running into the argument limit
+const BATCH: usize = 200;
+
+// at most this many differences are printed, past that it's the same problem over and over
+const MAX_SHOWN: usize = 50;
+
+#[derive(Debug, Default)]
+struct Outcome {
+    compared: usize,
+    differences: usize,
+}
+
+impl Outcome {
+    // the two outputs a line at a time, along with whether they failed
+    fn compare(&mut self, what: &str, wc: &Output, ours: &Output) {
+        self.compared += 1;
+        let (wc_lines, our_lines): (Vec<&str>, Vec<&str>) = (wc.stdout.lines().collect(), ours.stdout.lines().collect());
+        for line in 0..wc_lines.len().max(our_lines.len()) {
+            let (expected, got) = (wc_lines.get(line).copied(), our_lines.get(line).copied());
+            if expected != got {
+                self.report(format!("{}: wc printed {:?} but wc_clone printed {:?}", what, expected.unwrap_or(""), got.unwrap_or("")));
+            }
+        }
+        if wc.success != ours.success {
+            self.report(format!("{}: wc {} but wc_clone {}", what, status(wc.success), status(ours.success)));
+        }
+    }
+
+    fn report(&mut self, difference: String) {
+        self.differences += 1;
+        if self.differences <= MAX_SHOWN {
+            println!("{}", difference);
+        }
+    }
+}
+
+struct Output {
+    stdout: String,
+    success: bool,
+}
+
+fn status(success: bool) -> &'static str {
+    if success { "succeeded" } else { "failed" }
+}
+
+// argv here is whatever came after "selftest"
+pub fn run(argv: impl Iterator<Item = String>) {
+    let mut system_wc = String::from("wc");
+    let mut dirs = Vec::new();
+    for arg in argv {
+        match arg.strip_prefix("--wc=") {
+            Some(path) => system_wc = path.to_string(),
+            None if arg.starts_with('-') => {
+                println!("{}", crate::suggest::unsupported(&arg, &["--wc"]));
+                return;
+            },
+            None => dirs.push(arg),
+        }
+    }
+    if dirs.is_empty() {
+        dirs.push(String::from("."));
+    }
+
+    let wc_clone = match env::current_exe() {
+        Ok(path) => path,
+        Err(err) => {
+            eprintln!("wc_clone: can't find the wc_clone binary to test: {}", err);
+            process::exit(2);
+        },
+    };
+
+    let (files, errors) = walk::expand_operands(&dirs, &WalkOptions::default());
+    for (dir, err) in errors {
+        println!("{}", tr!("Encounted error reading directory {}: {}", dir, err));
+    }
+    if files.is_empty() {
+        println!("{}", tr!("No files spcified..."));
+        return;
+    }
+
+    let mut outcome = Outcome::default();
+    for options in OPTION_SETS {
+        let name = if options.is_empty() { String::from("(no options)") } else { options.join(" ") };
+        if !execute(&system_wc, options, &[String::from("/dev/null")], None).is_some_and(|output| output.success) {
+            println!("selftest: skipped {}, {} doesn't take it", name, system_wc);
+            continue;
+        }
+        for batch in files.chunks(BATCH) {
+            let (wc, ours) = (execute(&system_wc, options, batch, None), execute(&wc_clone, &with_compat(options), batch, None));
+            match (wc, ours) {
+                (Some(wc), Some(ours)) => outcome.compare(&name, &wc, &ours),
+                _ => unrunnable(&system_wc),
+            }
+        }
+    }
+
+    for file in &files {
+        let (Ok(input), Ok(our_input)) = (fs::File::open(file), fs::File::open(file)) else {
+            continue;
+        };
+        let (wc, ours) = (execute(&system_wc, &[], &[], Some(input)), execute(&wc_clone, &with_compat(&[]), &[], Some(our_input)));
+        match (wc, ours) {
+            (Some(wc), Some(ours)) => outcome.compare(&format!("stdin < {}", file), &wc, &ours),
+            _ => unrunnable(&system_wc),
+        }
+    }
+
+    if outcome.differences > MAX_SHOWN {
+        println!("...and {} more", outcome.differences - MAX_SHOWN);
+    }
+    println!("selftest: {} files, {} comparisons, {} differences", files.len(), outcome.compared, outcome.differences);
+    if outcome.differences > 0 {
+        process::exit(1);
+    }
+}
+
+fn with_compat<'a>(options: &[&'a str]) -> Vec<&'a str> {
+    let mut args = vec!["--compat=gnu"];
+    args.extend_from_slice(options);
+    args
+}
+
+fn execute(program: impl AsRef<OsStr>, options: &[&str], files: &[String], stdin: Option<fs::File>) -> Option<Output> {
+    let mut command = Command::new(program);
+    command.args(options).arg("--").args(files).stderr(Stdio::null());
+    command.stdin(stdin.map_or_else(Stdio::null, Stdio::from));
+    let output = command.output().ok()?;
+    Some(Output { stdout: String::from_utf8_lossy(&output.stdout).into_owned(), success: output.status.success() })
+}
+
+fn unrunnable(system_wc: &str) -> ! {
+    eprintln!("wc_clone: couldn't run {}", system_wc);
+    process::exit(2);
+}
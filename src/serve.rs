@@ -0,0 +1,200 @@
+/*
+The serve subcommand runs wc_clone as a small HTTP service: POST a body to /count and get its row back, counted with
+the options serve was started with. GET /metrics has what an operator needs to watch it in Prometheus like any other
+service: bytes and inputs counted, errors, and how long requests took. It's plain HTTP/1.1 with a Content-Length
+body and one request per connection, meant to sit behind whatever does TLS and auth...
+
+    wc_clone serve [--listen=ADDR] [OPTION]...
+    curl --data-binary @file.txt localhost:8080/count
+*/
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::i18n::tr;
+use crate::{interrupt, logging, stream, CommandOptions};
+
+const DEFAULT_LISTEN: &str = "127.0.0.1:8080";
+
+// request latency buckets in seconds, the usual Prometheus defaults
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+// nobody sends headers this long, and it keeps a slow client from holding on to memory
+const MAX_HEADER_BYTES: u64 = 64 * 1024;
+
+#[derive(Default)]
+struct Metrics {
+    bytes: AtomicU64,
+    inputs: AtomicU64,
+    errors: AtomicU64,
+    latency: Mutex<Latency>,
+}
+
+#[derive(Default)]
+struct Latency {
+    buckets: [u64; BUCKETS.len()], // requests that took at most each bucket's bound
+    count: u64,
+    sum: f64,
+}
+
+impl Metrics {
+    fn observe(&self, took: Duration) {
+        let seconds = took.as_secs_f64();
+        let mut latency = self.latency.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (bucket, bound) in latency.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        latency.count += 1;
+        latency.sum += seconds;
+    }
+
+    // the Prometheus text format
+    fn render(&self) -> String {
+        let mut text = String::new();
+        let counters = [
+            ("wc_clone_bytes_processed_total", "Bytes counted.", &self.bytes),
+            ("wc_clone_inputs_counted_total", "Request bodies counted.", &self.inputs),
+            ("wc_clone_errors_total", "Requests that failed.", &self.errors),
+        ];
+        for (name, help, value) in counters {
+            text.push_str(&format!("# HELP {} {}\n# TYPE {} counter\n{} {}\n", name, help, name, name, value.load(Ordering::Relaxed)));
+        }
+
+        let latency = self.latency.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let name = "wc_clone_request_duration_seconds";
+        text.push_str(&format!("# HELP {} Time taken to answer a request.\n# TYPE {} histogram\n", name, name));
+        for (bucket, bound) in latency.buckets.iter().zip(BUCKETS) {
+            text.push_str(&format!("{}_bucket{{le=\"{}\"}} {}\n", name, bound, bucket));
+        }
+        text.push_str(&format!("{}_bucket{{le=\"+Inf\"}} {}\n", name, latency.count));
+        text.push_str(&format!("{}_sum {}\n{}_count {}\n", name, latency.sum, name, latency.count));
+        text
+    }
+}
+
+// argv here is whatever came after "serve"
+pub fn run(mut argv: impl Iterator<Item = String>) {
+    let mut listen = String::from(DEFAULT_LISTEN);
+    let mut counting_args = vec![String::from("wc_clone")]; // CommandOptions::build skips over the exec path
+    while let Some(arg) = argv.next() {
+        if let Some(addr) = arg.strip_prefix("--listen=") {
+            listen = addr.to_string();
+        } else if arg == "--listen" {
+            match argv.next() {
+                Some(addr) => listen = addr,
+                None => {
+                    println!("{}", tr!("option '--listen' requires an argument"));
+                    return;
+                },
+            }
+        } else {
+            counting_args.push(arg);
+        }
+    }
+
+    let command_options = match CommandOptions::build(counting_args.into_iter()) {
+        Ok(command_options) => {
+            logging::init(&command_options);
+            Arc::new(command_options)
+        },
+        Err(err) => {
+            println!("{}", err);
+            return;
+        },
+    };
+    let listener = match TcpListener::bind(&listen) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("wc_clone: can't listen on {}: {}", listen, err);
+            std::process::exit(1);
+        },
+    };
+    eprintln!("wc_clone: listening on {}", listen);
+
+    let metrics = Arc::new(Metrics::default());
+    for connection in listener.incoming() {
+        // Ctrl-C interrupts the accept, there's nothing to wind down
+        if interrupt::is_interrupted() {
+            std::process::exit(interrupt::EXIT_CODE);
+        }
+        let Ok(connection) = connection else { continue };
+        let (command_options, metrics) = (Arc::clone(&command_options), Arc::clone(&metrics));
+        thread::spawn(move || {
+            let started = Instant::now();
+            let failed = handle(connection, &command_options, &metrics).is_err();
+            if failed {
+                metrics.errors.fetch_add(1, Ordering::Relaxed);
+            }
+            metrics.observe(started.elapsed());
+            tracing::debug!(failed, elapsed_ms = started.elapsed().as_secs_f64() * 1000.0, "request answered");
+        });
+    }
+}
+
+// an Err is a request that failed, whoever's fault it was
+fn handle(connection: TcpStream, command_options: &CommandOptions, metrics: &Metrics) -> Result<(), ()> {
+    let mut reader = BufReader::new(connection.try_clone().map_err(|_| ())?);
+    let mut connection = connection;
+
+    let Some((method, path, content_length)) = read_head(&mut reader.by_ref().take(MAX_HEADER_BYTES)) else {
+        return respond(&mut connection, "400 Bad Request", "bad request\n").and(Err(()));
+    };
+    match (method.as_str(), path.as_str()) {
+        ("GET", "/metrics") => respond(&mut connection, "200 OK", &metrics.render()),
+        ("POST", "/count") => {
+            let Some(length) = content_length else {
+                return respond(&mut connection, "411 Length Required", "a Content-Length is needed\n").and(Err(()));
+            };
+            let body = reader.take(length);
+            match stream::count_reader(body, command_options, "") {
+                Ok((stats, _)) => {
+                    metrics.bytes.fetch_add(stats.bytes(), Ordering::Relaxed);
+                    metrics.inputs.fetch_add(1, Ordering::Relaxed);
+                    respond(&mut connection, "200 OK", &format!("{}\n", crate::format_run_results(command_options, &stats, "")))
+                },
+                Err(err) => respond(&mut connection, "400 Bad Request", &format!("{}\n", err)).and(Err(())),
+            }
+        },
+        (_, "/metrics" | "/count") => respond(&mut connection, "405 Method Not Allowed", "method not allowed\n").and(Err(())),
+        _ => respond(&mut connection, "404 Not Found", "not found\n").and(Err(())),
+    }
+}
+
+// the method, the path and the Content-Length if there was one
+fn read_head(reader: &mut impl BufRead) -> Option<(String, String, Option<u64>)> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut request = line.split_whitespace();
+    let (method, path) = (request.next()?.to_string(), request.next()?.to_string());
+
+    let mut content_length = None;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break; // or MAX_HEADER_BYTES ran out, and the request is cut short
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse().ok()?);
+            }
+        }
+    }
+    Some((method, path, content_length))
+}
+
+fn respond(connection: &mut TcpStream, status: &str, body: &str) -> Result<(), ()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    connection.write_all(response.as_bytes()).and_then(|_| connection.flush()).map_err(|_: io::Error| ())
+}
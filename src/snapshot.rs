@@ -0,0 +1,372 @@
+/*
+The snapshot and delta subcommands, for keeping an eye on how a codebase or a document set grows: snapshot saves the
+counts of every input to a JSON baseline, and delta counts again later and prints what changed per file and in total.
+Both take the same counting options as a normal run. delta counts the files listed in the baseline unless it's given
+files of its own, which is how new files show up (`-r src` on both sides, say)...
+
+    wc_clone snapshot [-o FILE] [OPTION]... FILE...
+    wc_clone delta [--format=text|json] BASELINE [OPTION]... [FILE]...
+*/
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::process;
+
+use serde_json::{json, Map, Number, Value};
+
+use crate::i18n::tr;
+use crate::{interrupt, logging, pipeline, walk, CommandOptions, FileStats};
+
+// would only change if the layout of the baseline did
+const SNAPSHOT_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+// argv here is whatever came after "snapshot"
+pub fn run_snapshot(mut argv: impl Iterator<Item = String>) {
+    let mut output: Option<String> = None;
+    let mut counting_args = vec![String::from("wc_clone")]; // CommandOptions::build skips over the exec path
+    while let Some(arg) = argv.next() {
+        if arg == "-o" || arg == "--output" {
+            match argv.next() {
+                Some(path) => output = Some(path),
+                None => {
+                    println!("{}", tr!("option '{}' requires an argument", arg));
+                    return;
+                },
+            }
+        } else if let Some(path) = arg.strip_prefix("--output=") {
+            output = Some(path.to_string());
+        } else {
+            counting_args.push(arg);
+        }
+    }
+
+    let mut command_options = match CommandOptions::build(counting_args.into_iter()) {
+        Ok(command_options) => command_options,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        },
+    };
+    logging::init(&command_options);
+    if command_options.files.is_empty() {
+        println!("{}", tr!("No files spcified..."));
+        return;
+    }
+
+    let columns = column_keys(&command_options);
+    let (counted, failed) = count_inputs(&mut command_options);
+    let mut total = FileStats::new();
+    let mut files: Vec<Value> = Vec::new();
+    for (file, stats) in &counted {
+        total += stats;
+        let mut row = Map::new();
+        row.insert(String::from("file"), Value::from(file.as_str()));
+        row.extend(counts_object(&columns, &command_options, stats));
+        files.push(Value::Object(row));
+    }
+
+    let snapshot = json!({
+        "version": SNAPSHOT_VERSION,
+        "columns": columns,
+        "files": files,
+        "total": counts_object(&columns, &command_options, &total),
+    });
+    let text = format!("{}\n", serde_json::to_string_pretty(&snapshot).unwrap_or_default());
+    match output {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, text) {
+                println!("{}", tr!("Encounted error writing file {}: {}", path, err));
+                process::exit(1);
+            }
+        },
+        None => print!("{}", text),
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+// argv here is whatever came after "delta"
+pub fn run_delta(mut argv: impl Iterator<Item = String>) {
+    let mut format = Format::Text;
+    let mut baseline_path: Option<String> = None;
+    let mut counting_args = vec![String::from("wc_clone")];
+    while let Some(arg) = argv.next() {
+        let value = if arg == "--format" { argv.next() } else { arg.strip_prefix("--format=").map(String::from) };
+        match value.as_deref() {
+            Some("text") => format = Format::Text,
+            Some("json") => format = Format::Json,
+            Some(value) => {
+                println!("invalid value for '--format': '{}' (expected text or json)", value);
+                return;
+            },
+            None if arg == "--format" => {
+                println!("{}", tr!("option '--format' requires an argument"));
+                return;
+            },
+            None if baseline_path.is_none() && !arg.starts_with('-') => baseline_path = Some(arg),
+            None => counting_args.push(arg),
+        }
+    }
+
+    let Some(baseline_path) = baseline_path else {
+        println!("{}", tr!("No baseline spcified..."));
+        return;
+    };
+    let baseline = match read_baseline(&baseline_path) {
+        Ok(baseline) => baseline,
+        Err(err) => {
+            println!("{}", tr!("Encounted error reading file {}: {}", baseline_path, err));
+            process::exit(1);
+        }
+    };
+
+    let mut command_options = match CommandOptions::build(counting_args.into_iter()) {
+        Ok(command_options) => command_options,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        },
+    };
+    logging::init(&command_options);
+
+    // only what both sides have can be compared, a baseline of -l against a -lw run just compares lines
+    let baseline_columns: Vec<&str> = baseline["columns"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+    let columns: Vec<String> =
+        column_keys(&command_options).into_iter().filter(|column| baseline_columns.contains(&column.as_str())).collect();
+    if columns.is_empty() {
+        println!("{} has none of the counts asked for, it has: {}", baseline_path, baseline_columns.join(", "));
+        process::exit(1);
+    }
+
+    let baseline_files: Vec<&Map<String, Value>> = baseline["files"].as_array().into_iter().flatten().filter_map(Value::as_object).collect();
+    let file_name = |row: &Map<String, Value>| row.get("file").and_then(Value::as_str).unwrap_or("").to_string();
+    if command_options.files.is_empty() {
+        command_options.files = baseline_files.iter().map(|row| file_name(row)).collect();
+    }
+
+    let (counted, failed) = count_inputs(&mut command_options);
+    let before: HashMap<String, &Map<String, Value>> = baseline_files.iter().map(|row| (file_name(row), *row)).collect();
+    let mut total = FileStats::new();
+    let mut rows: Vec<Row> = Vec::new();
+    for (file, stats) in &counted {
+        total += stats;
+        rows.push(Row::new(file, &columns, before.get(file).copied(), Some(&counts_object(&columns, &command_options, stats))));
+    }
+    // whatever isn't there anymore (or wasn't asked about this time)
+    let counted_files: HashSet<&str> = counted.iter().map(|(file, _)| file.as_str()).collect();
+    for row in &baseline_files {
+        let file = file_name(row);
+        if !counted_files.contains(file.as_str()) {
+            rows.push(Row::new(&file, &columns, Some(row), None));
+        }
+    }
+
+    let total = Row::new("total", &columns, baseline["total"].as_object(), Some(&counts_object(&columns, &command_options, &total)));
+    match format {
+        Format::Text => print_text(&rows, &total, command_options.verbosity > 0),
+        Format::Json => print_json(&rows, &total),
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn read_baseline(path: &str) -> Result<Value, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let baseline: Value = serde_json::from_str(&contents).map_err(|err| format!("not a wc_clone snapshot ({})", err))?;
+    match baseline["version"].as_u64() {
+        Some(SNAPSHOT_VERSION) => Ok(baseline),
+        Some(version) => Err(format!("snapshot version {} isn't supported, only {}", version, SNAPSHOT_VERSION)),
+        None => Err(String::from("not a wc_clone snapshot (no version)")),
+    }
+}
+
+// the JSON keys for the counts the options ask for, in output order
+pub fn column_keys(command_options: &CommandOptions) -> Vec<String> {
+    crate::column_names(command_options)
+        .into_iter()
+        .map(|name| name.replace(' ', "_")) // "max line length" -> max_line_length
+        .collect()
+}
+
+// numbers as JSON numbers, hashes as strings, and the "-" a total has for a hash left out
+pub fn counts_object(columns: &[String], command_options: &CommandOptions, stats: &FileStats) -> Map<String, Value> {
+    let mut counts = Map::new();
+    for (column, count) in columns.iter().zip(crate::selected_counts(command_options, stats)) {
+        let value = if let Ok(integer) = count.parse::<i64>() {
+            Value::from(integer)
+        } else if let Some(number) = count.parse::<f64>().ok().and_then(Number::from_f64) {
+            Value::Number(number)
+        } else if count == "-" {
+            continue;
+        } else {
+            Value::from(count)
+        };
+        counts.insert(column.to_string(), value);
+    }
+    counts
+}
+
+/*
+Counts the inputs the way run_on_files does (walking directories with -r, in parallel with -j), reporting whatever
+can't be counted as it goes. The bool is whether anything couldn't be.
+*/
+fn count_inputs(command_options: &mut CommandOptions) -> (Vec<(String, FileStats)>, bool) {
+    let mut failed = false;
+    if command_options.recursive {
+        let (files, errors) = walk::expand_operands(&command_options.files, &command_options.walk_options);
+        for (dir, err) in errors {
+            println!("{}", tr!("Encounted error reading directory {}: {}", dir, err));
+            failed = true;
+        }
+        command_options.files = files;
+    }
+    if let Some(sort) = command_options.sort {
+        sort.sort(&mut command_options.files);
+    }
+
+    let results = pipeline::count_all(&command_options.files, command_options, command_options.jobs);
+    let mut counted = Vec::new();
+    for (file, result) in command_options.files.iter().zip(results) {
+        match crate::counted_stats(result, file, command_options) {
+            Ok(stats) => counted.push((file.clone(), stats)),
+            Err(err) => {
+                println!("{}", tr!("Encounted error reading file {}: {}", file, err));
+                failed = true;
+            },
+        }
+    }
+
+    if interrupt::is_interrupted() {
+        eprintln!("{}", tr!("wc_clone: interrupted before every file was counted"));
+        process::exit(interrupt::EXIT_CODE);
+    }
+    (counted, failed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    New,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+impl Status {
+    fn name(&self) -> &'static str {
+        match self {
+            Status::New => "new",
+            Status::Removed => "removed",
+            Status::Changed => "changed",
+            Status::Unchanged => "unchanged",
+        }
+    }
+}
+
+// one file's before and after, a missing side is a file that was added or removed
+struct Row {
+    file: String,
+    status: Status,
+    columns: Vec<(String, Value, Value, Value)>, // name, before, after and the difference
+}
+
+impl Row {
+    fn new(file: &str, columns: &[String], before: Option<&Map<String, Value>>, after: Option<&Map<String, Value>>) -> Self {
+        let columns: Vec<(String, Value, Value, Value)> = columns
+            .iter()
+            .map(|column| {
+                let before_value = before.and_then(|before| before.get(column)).cloned().unwrap_or(Value::Null);
+                let after_value = after.and_then(|after| after.get(column)).cloned().unwrap_or(Value::Null);
+                let difference = difference(&before_value, &after_value);
+                (column.clone(), before_value, after_value, difference)
+            })
+            .collect();
+
+        let status = match (before, after) {
+            (None, _) => Status::New,
+            (_, None) => Status::Removed,
+            _ if columns.iter().all(|(_, before, after, _)| before == after) => Status::Unchanged,
+            _ => Status::Changed,
+        };
+        Row { file: file.to_string(), status, columns }
+    }
+}
+
+/*
+after - before, a side that's missing counts as 0 so added and removed files show their whole size. Hashes have no
+difference, they're either the same or not.
+*/
+fn difference(before: &Value, after: &Value) -> Value {
+    let zero = Value::from(0);
+    let (before, after) = match (before, after) {
+        (Value::Null, Value::Null) => return Value::Null,
+        (Value::Null, after) => (&zero, after),
+        (before, Value::Null) => (before, &zero),
+        sides => sides,
+    };
+
+    match (before.as_i64(), after.as_i64(), before.as_f64(), after.as_f64()) {
+        (Some(before), Some(after), _, _) => Value::from(after - before),
+        (_, _, Some(before), Some(after)) => Number::from_f64(after - before).map_or(Value::Null, Value::Number),
+        _ => Value::Null,
+    }
+}
+
+// signed so growth and shrinkage are easy to tell apart, hashes just say whether they changed
+fn format_difference(before: &Value, after: &Value, difference: &Value) -> String {
+    if let Some(difference) = difference.as_i64() {
+        format!("{:+}", difference)
+    } else if let Some(difference) = difference.as_f64() {
+        format!("{:+.2}", difference)
+    } else if before == after {
+        String::from("same")
+    } else {
+        String::from("changed")
+    }
+}
+
+// unchanged files are only listed with -v, the total always is
+fn print_text(rows: &[Row], total: &Row, verbose: bool) {
+    for row in rows.iter().filter(|row| verbose || row.status != Status::Unchanged) {
+        println!("{}", format_row(row));
+    }
+    println!("{}", format_row(total));
+}
+
+fn format_row(row: &Row) -> String {
+    let mut line = String::new();
+    for (_, before, after, difference) in &row.columns {
+        line.push_str(&format!(" {}", format_difference(before, after, difference)));
+    }
+    line.push_str(&format!(" {}", row.file));
+    if matches!(row.status, Status::New | Status::Removed) {
+        line.push_str(&format!(" ({})", row.status.name()));
+    }
+    line
+}
+
+fn print_json(rows: &[Row], total: &Row) {
+    let row_json = |row: &Row| {
+        let mut object = Map::new();
+        object.insert(String::from("file"), Value::from(row.file.as_str()));
+        object.insert(String::from("status"), Value::from(row.status.name()));
+        for (column, before, after, difference) in &row.columns {
+            object.insert(column.to_string(), json!({ "before": before, "after": after, "delta": difference }));
+        }
+        Value::Object(object)
+    };
+
+    let delta = json!({
+        "files": rows.iter().map(row_json).collect::<Vec<Value>>(),
+        "total": row_json(total),
+    });
+    println!("{}", serde_json::to_string_pretty(&delta).unwrap_or_default());
+}
@@ -0,0 +1,66 @@
+/*
+--sort=name puts the inputs in natural order before they're counted: runs of digits compare by their value, so
+file2 comes before file10 and ch9.md before ch10.md, instead of the byte order -r walks directories in. Paths are
+compared a component at a time so a directory's files stay together, and anything natural order calls equal (file01
+and file1) falls back to byte order, so the result is the same whatever the platform, the locale or -j...
+*/
+use std::cmp::Ordering;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+}
+
+impl SortKey {
+    pub fn parse(value: &str) -> Result<SortKey, String> {
+        match value {
+            "name" => Ok(SortKey::Name),
+            _ => Err(format!("invalid value for '--sort': '{}' (expected name)", value)),
+        }
+    }
+
+    pub fn sort(self, files: &mut [String]) {
+        match self {
+            SortKey::Name => files.sort_by(|a, b| natural_path_cmp(a, b)),
+        }
+    }
+}
+
+fn natural_path_cmp(a: &str, b: &str) -> Ordering {
+    let components = |path: &str| Path::new(path).components().map(|component| component.as_os_str().to_string_lossy().into_owned()).collect::<Vec<_>>();
+    let (a_components, b_components) = (components(a), components(b));
+    let natural = a_components.iter().zip(&b_components).map(|(a, b)| natural_cmp(a, b)).find(|order| order.is_ne());
+    natural.unwrap_or_else(|| a_components.len().cmp(&b_components.len())).then_with(|| a.cmp(b))
+}
+
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    while let (Some(&a_first), Some(&b_first)) = (a.first(), b.first()) {
+        let order = if a_first.is_ascii_digit() && b_first.is_ascii_digit() {
+            let (a_digits, a_rest) = split_digits(a);
+            let (b_digits, b_rest) = split_digits(b);
+            (a, b) = (a_rest, b_rest);
+            compare_numbers(a_digits, b_digits)
+        } else {
+            (a, b) = (&a[1..], &b[1..]);
+            a_first.cmp(&b_first)
+        };
+        if order.is_ne() {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn split_digits(bytes: &[u8]) -> (&[u8], &[u8]) {
+    let end = bytes.iter().position(|byte| !byte.is_ascii_digit()).unwrap_or(bytes.len());
+    bytes.split_at(end)
+}
+
+// by value however long they are, leading zeros aside
+fn compare_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    let trim = |digits: &[u8]| -> usize { digits.iter().position(|digit| *digit != b'0').unwrap_or(digits.len()) };
+    let (a, b) = (&a[trim(a)..], &b[trim(b)..]);
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
@@ -0,0 +1,113 @@
+/*
+--output sqlite:FILE (with the sqlite feature) appends the run to a SQLite database instead of writing a report, so
+the counts of a codebase can be followed over months with nothing but sqlite3. Every row of the run, the total
+included (as file 'total'), goes into the counts table with the run's ID and start time (seconds since the epoch):
+
+    CREATE TABLE counts (run_id TEXT NOT NULL, timestamp INTEGER NOT NULL, file TEXT, lines, words, bytes...)
+
+The count columns have the names snapshots use and are added as runs ask for new ones, older rows just have NULL
+there. Nothing is written unless the run gets to the end.
+
+    wc_clone -r -lw --output sqlite:stats.db src/
+    sqlite3 stats.db "SELECT datetime(timestamp, 'unixepoch'), lines FROM counts WHERE file = 'total'"
+*/
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::types::Value;
+use rusqlite::Connection;
+
+use crate::{snapshot, CommandOptions, FileStats, Reporter};
+
+pub struct SqliteReporter {
+    connection: Connection,
+    path: String,
+    run_id: String,
+    timestamp: i64,
+    columns: Option<Vec<String>>, // once the table has them
+    rows: usize,
+    last_row: Option<String>,
+}
+
+impl SqliteReporter {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let error = |err: rusqlite::Error| format!("can't write {}: {}", path, err);
+        let connection = Connection::open(path).map_err(error)?;
+        connection
+            .execute_batch("CREATE TABLE IF NOT EXISTS counts (run_id TEXT NOT NULL, timestamp INTEGER NOT NULL, file TEXT); BEGIN;")
+            .map_err(error)?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let run_id = format!("{:x}-{:x}", now.as_nanos(), process::id()); // unique enough without a random source
+        Ok(Self { connection, path: path.to_string(), run_id, timestamp: now.as_secs() as i64, columns: None, rows: 0, last_row: None })
+    }
+
+    // the columns this run counts, added to the table if an earlier run didn't have them
+    fn prepare_columns(&mut self, options: &CommandOptions) -> rusqlite::Result<Vec<String>> {
+        let mut statement = self.connection.prepare("SELECT name FROM pragma_table_info('counts')")?;
+        let existing: HashSet<String> = statement.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        drop(statement);
+
+        let columns = snapshot::column_keys(options);
+        for column in columns.iter().filter(|column| !existing.contains(*column)) {
+            self.connection.execute(&format!("ALTER TABLE counts ADD COLUMN {}", quote(column)), [])?;
+        }
+        Ok(columns)
+    }
+
+    fn insert(&mut self, options: &CommandOptions, stats: &FileStats, topic: &str) -> rusqlite::Result<()> {
+        let columns = match self.columns.take() {
+            Some(columns) => columns,
+            None => self.prepare_columns(options)?,
+        };
+
+        let names: String = columns.iter().map(|column| format!(", {}", quote(column))).collect();
+        let placeholders = ", ?".repeat(columns.len());
+        let sql = format!("INSERT INTO counts (run_id, timestamp, file{}) VALUES (?, ?, ?{})", names, placeholders);
+
+        let mut values = vec![Value::from(self.run_id.clone()), Value::from(self.timestamp), Value::from(topic.to_string())];
+        values.extend(crate::selected_counts(options, stats).into_iter().map(sql_value));
+        self.connection.prepare_cached(&sql)?.execute(rusqlite::params_from_iter(values))?;
+
+        self.columns = Some(columns);
+        Ok(())
+    }
+}
+
+impl Reporter for SqliteReporter {
+    fn row(&mut self, _out: &mut impl Write, options: &CommandOptions, stats: &FileStats, topic: &str) -> io::Result<()> {
+        self.insert(options, stats, topic).map_err(io::Error::other)?;
+        self.rows += 1;
+        self.last_row = Some(crate::format_run_results(options, stats, topic));
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut impl Write) -> io::Result<()> {
+        self.connection.execute_batch("COMMIT").map_err(io::Error::other)?;
+        if let Some(last_row) = &self.last_row {
+            writeln!(out, "{}", last_row)?;
+        }
+        let plural = if self.rows == 1 { "" } else { "s" };
+        writeln!(out, "wc_clone: added {} row{} to {} as run {}", self.rows, plural, self.path, self.run_id)
+    }
+}
+
+// the counts as numbers where they are, "-" (nothing to show) as NULL
+fn sql_value(count: String) -> Value {
+    if let Ok(integer) = count.parse::<i64>() {
+        Value::Integer(integer)
+    } else if let Ok(real) = count.parse::<f64>() {
+        Value::Real(real)
+    } else if count == "-" {
+        Value::Null
+    } else {
+        Value::Text(count)
+    }
+}
+
+// column names come from --count-char and registered counters too, so they can be anything
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
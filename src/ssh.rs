@@ -0,0 +1,85 @@
+/*
+Files on other machines: --remote=[USER@]HOST:PATH (scp's syntax) and ssh://[USER@]HOST[:PORT]/PATH operands are cat'ed
+over ssh and counted here, so checking a log's size across a fleet doesn't mean copying it around first. ssh runs in
+batch mode, it has to get in with a key or an agent since there's nobody to type a password, and everything else
+(users, ports, jump hosts) comes from ~/.ssh/config as usual. A relative PATH (or ~/PATH) is from the home directory,
+same as scp. Plain HOST:PATH operands without --remote are local files, colons are allowed in file names...
+
+    wc_clone -l --remote=web1:/var/log/nginx/access.log --remote=web2:/var/log/nginx/access.log
+*/
+use std::io;
+use std::process::Command;
+
+use crate::i18n::tr;
+use crate::{child, decompress, stream, CommandOptions, ReadResult};
+
+#[derive(Debug, PartialEq, Eq)]
+struct Remote<'a> {
+    destination: String, // [USER@]HOST, without the brackets around an IPv6 address
+    port: Option<&'a str>,
+    path: &'a str,
+}
+
+// [USER@]HOST:PATH, for --remote
+pub fn check(value: &str) -> Result<(), String> {
+    match parse_scp(value) {
+        Some(_) => Ok(()),
+        None => Err(format!("invalid value for '--remote': '{}' (expected [USER@]HOST:PATH)", value)),
+    }
+}
+
+pub fn is_uri(operand: &str) -> bool {
+    operand.starts_with("ssh://")
+}
+
+fn parse_scp(value: &str) -> Option<Remote<'_>> {
+    // [::1]:path, the colons in the brackets are the address's
+    let host_end = match value.strip_prefix('[').or_else(|| value.split_once("@[").map(|(_, rest)| rest)) {
+        Some(_) => value.find("]:")? + 1,
+        None => value.find(':')?,
+    };
+    let (destination, path) = (&value[..host_end], &value[host_end + 1..]);
+    valid(Remote { destination: destination.replace(['[', ']'], ""), port: None, path })
+}
+
+fn parse_uri(uri: &str) -> Option<Remote<'_>> {
+    let rest = uri.strip_prefix("ssh://")?;
+    let (authority, path) = rest.split_at(rest.find('/')?);
+    let (destination, port) = match authority.rsplit_once(':') {
+        Some((destination, port)) if !port.contains(']') => (destination, Some(port)),
+        _ => (authority, None),
+    };
+    let path = path.strip_prefix("/~/").unwrap_or(path);
+    valid(Remote { destination: destination.replace(['[', ']'], ""), port, path })
+}
+
+// a destination starting with a dash would be taken for one of ssh's options
+fn valid(remote: Remote) -> Option<Remote> {
+    let usable = !remote.destination.is_empty() && !remote.destination.starts_with('-') && !remote.path.is_empty();
+    usable.then_some(remote)
+}
+
+pub fn read(operand: &str, command_options: &CommandOptions) -> ReadResult {
+    let Some(remote) = parse_uri(operand).or_else(|| parse_scp(operand)) else {
+        return crate::read_result(Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("not a remote file: {}", operand))));
+    };
+
+    let mut command = Command::new("ssh");
+    command.args(["-o", "BatchMode=yes", "-T"]);
+    if let Some(port) = remote.port {
+        command.args(["-p", port]);
+    }
+    if let Some(timeout) = command_options.timeout {
+        command.arg("-o").arg(format!("ConnectTimeout={}", timeout.as_secs().max(1)));
+    }
+    command.arg("--").arg(&remote.destination).arg(format!("cat -- {}", quote(remote.path.strip_prefix("~/").unwrap_or(remote.path))));
+
+    let body = child::spawn(command, command_options).map_err(|err| io::Error::new(err.kind(), tr!("couldn't run ssh: {}", err)));
+    let body = body.and_then(|body| decompress::wrap(body, command_options));
+    crate::read_result(body.and_then(|body| stream::count_reader(body, command_options, remote.path)))
+}
+
+// for the remote shell, which gets the command as one string
+fn quote(path: &str) -> String {
+    format!("'{}'", path.replace('\'', r"'\''"))
+}
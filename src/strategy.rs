@@ -0,0 +1,173 @@
+/*
+Picks how each input gets read: streaming it through a buffer, mapping it into memory, or not reading it at all when the
+file size is the only thing asked for. Every front end counts through here so they all get the same choice, and
+--io=read|mmap overrides the heuristics when they get it wrong...
+*/
+use std::fs;
+use std::io;
+
+#[cfg(feature = "mmap")]
+use crate::cache;
+use crate::{decompress, stream, timeout};
+use crate::{CommandOptions, FileStats, ReadLimits};
+
+// below this reading is at least as fast as setting up a mapping, and it's a lot less fragile
+#[cfg(feature = "mmap")]
+const MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoMode {
+    Auto,
+    Read,
+    #[cfg_attr(not(feature = "mmap"), allow(dead_code))] // parse refuses mmap then
+    Mmap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Metadata, // the byte count is the file size
+    Read,
+    Mmap,
+}
+
+impl IoMode {
+    pub fn parse(value: &str) -> Result<IoMode, String> {
+        match value {
+            "auto" => Ok(IoMode::Auto),
+            "read" => Ok(IoMode::Read),
+            #[cfg(feature = "mmap")]
+            "mmap" => Ok(IoMode::Mmap),
+            #[cfg(not(feature = "mmap"))]
+            "mmap" => Err(String::from("--io=mmap isn't available, wc_clone was built without the mmap feature")),
+            _ => Err(format!("invalid value for '--io': '{}' (expected read, mmap or auto)", value)),
+        }
+    }
+}
+
+/*
+Only regular files with a known size can be mapped or sized from metadata, anything else (pipes, devices, procfs) is
+always streamed. A forced mode is taken literally and never skips the read, except that a file bigger than --max-memory
+is streamed even with --io=mmap, a mapping that size would end up all in memory.
+*/
+pub fn choose(metadata: &fs::Metadata, path: &str, mode: IoMode, bytes_only: bool, read_limits: &ReadLimits, max_memory: Option<usize>) -> Strategy {
+    if !metadata.is_file() || metadata.len() == 0 {
+        return Strategy::Read;
+    }
+    let too_big_to_map = max_memory.is_some_and(|max_memory| metadata.len() > max_memory as u64);
+
+    match mode {
+        IoMode::Read => Strategy::Read,
+        IoMode::Mmap if too_big_to_map => Strategy::Read,
+        IoMode::Mmap => Strategy::Mmap,
+        IoMode::Auto => {
+            if bytes_only && read_limits.max_lines.is_none() {
+                Strategy::Metadata
+            } else if metadata.len() >= mmap_threshold() && !too_big_to_map && !is_network_fs(path) {
+                Strategy::Mmap
+            } else {
+                Strategy::Read
+            }
+        }
+    }
+}
+
+pub fn count_file(path: &str, metadata: &fs::Metadata, strategy: Strategy, command_options: &CommandOptions) -> io::Result<(FileStats, Option<u64>)> {
+    let read_limits = &command_options.read_limits;
+
+    match strategy {
+        Strategy::Metadata => {
+            let mut stats = FileStats::new();
+            let len = metadata.len().saturating_sub(read_limits.offset);
+            stats.byte_count = read_limits.byte_cap().map_or(len, |cap| cap.min(len));
+            Ok((stats, None))
+        },
+        Strategy::Mmap => count_mapped(path, command_options),
+        Strategy::Read => {
+            let file = timeout::open(path, command_options)?;
+            match metadata.is_file() {
+                true => stream::count_file(file, command_options, path),
+                false => stream::count_reader(decompress::wrap(file, command_options)?, command_options, path), // a FIFO or a device
+            }
+        },
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn mmap_threshold() -> u64 {
+    MMAP_THRESHOLD
+}
+
+#[cfg(not(feature = "mmap"))]
+fn mmap_threshold() -> u64 {
+    u64::MAX
+}
+
+#[cfg(feature = "mmap")]
+fn count_mapped(path: &str, command_options: &CommandOptions) -> io::Result<(FileStats, Option<u64>)> {
+    let read_limits = &command_options.read_limits;
+    let file = fs::File::open(path)?;
+    // SAFETY: the mapping is read only and dropped before we return. Another process truncating the file while we
+    // count is the one thing that can still go wrong, which is why network filesystems never get mapped in auto mode.
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    if command_options.no_cache_pollution {
+        let _ = map.advise(memmap2::Advice::Sequential);
+    }
+
+    let start = (read_limits.offset as usize).min(map.len());
+    let content = &map[start..];
+    let len = read_limits.prefix_len(content);
+
+    // fed in chunks anyway so progress snapshots keep moving on huge files
+    let mut counter = stream::StreamCounter::new(command_options, path);
+    for chunk in content[..len].chunks(command_options.buffer_size) {
+        counter.feed(chunk);
+    }
+    if len < content.len() {
+        counter.drop_partial_char();
+    }
+
+    drop(map);
+    if command_options.no_cache_pollution {
+        // unmapping only drops our view of the pages, the page cache needs telling separately
+        cache::drop_cached(&file, 0, 0);
+    }
+    Ok(counter.finish())
+}
+
+#[cfg(not(feature = "mmap"))]
+fn count_mapped(path: &str, command_options: &CommandOptions) -> io::Result<(FileStats, Option<u64>)> {
+    let file = timeout::open(path, command_options)?;
+    stream::count_file(file, command_options, path)
+}
+
+/*
+Mapping a file on NFS/SMB/FUSE means a SIGBUS if it shrinks under us and usually no speedup since every page fault is a
+round trip anyway, so those stick to plain reads.
+*/
+#[cfg(target_os = "linux")]
+fn is_network_fs(path: &str) -> bool {
+    use std::ffi::CString;
+
+    const NFS_SUPER_MAGIC: u32 = 0x6969;
+    const SMB_SUPER_MAGIC: u32 = 0x517B;
+    const CIFS_SUPER_MAGIC: u32 = 0xFF53_4D42;
+    const SMB2_SUPER_MAGIC: u32 = 0xFE53_4D42;
+    const FUSE_SUPER_MAGIC: u32 = 0x6573_5546;
+
+    let Ok(c_path) = CString::new(path) else { return true };
+    let mut fs_stats: libc::statfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is nul terminated and fs_stats is a properly sized out parameter
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut fs_stats) } != 0 {
+        return true; // can't tell, so play it safe
+    }
+
+    matches!(
+        fs_stats.f_type as u32, // the magic numbers are 32 bit whatever the width of f_type
+        NFS_SUPER_MAGIC | SMB_SUPER_MAGIC | CIFS_SUPER_MAGIC | SMB2_SUPER_MAGIC | FUSE_SUPER_MAGIC
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_network_fs(_path: &str) -> bool {
+    false
+}
@@ -0,0 +1,669 @@
+/*
+Streaming reader for inputs we can't (or shouldn't) load in one go: pipes, devices and procfs entries that report a
+size of 0. The content is counted chunk by chunk as it arrives and never read twice, which matters since reading a
+FIFO again after a failed utf8 read just hangs or comes back empty...
+*/
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use crate::classes::CharClasses;
+use crate::code::{self, LineClassifier};
+use crate::compat::{Compat, GnuCounter};
+use crate::distinct::Distinct;
+use crate::emoji::EmojiCounter;
+use crate::eol::{Eol, LineEnds};
+use crate::filetype::Sniffer;
+use crate::hash::Hasher;
+use crate::kernel::{self, CharCount, Counts};
+use crate::numbers::{self, NumberCounter};
+use crate::lineclass::LineClasses;
+use crate::occurrences::Occurrences;
+use crate::whitespace::WhitespaceReport;
+use crate::normalize::Normalizer;
+use crate::utf8::{Utf8Decoder, Utf8Mode};
+use crate::width::LineWidth;
+use crate::{entropy, interrupt, progress, CommandOptions, FileStats, ReadLimits};
+
+// default for --buffer-size
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+// granularity of the ascii fast path, is_ascii checks a word at a time so this is cheap to scan
+const ASCII_BLOCK: usize = 4 * 1024;
+
+pub static DEFAULT_SEPARATORS: Separators = Separators::new(b" \t\r\n", &[]);
+pub static POSIX_SEPARATORS: Separators = Separators::new(b" \t\r\n\x0b\x0c", &[]); // isspace() in the C locale
+
+/*
+The bytes that end a word, as a lookup table since it gets checked for every byte of the input. Marks are non-ascii
+characters that end words too, only --word-policy has any and the byte loop only looks for them then.
+*/
+pub struct Separators {
+    table: [bool; 256],
+    marks: &'static [&'static str],
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum MarkMatch {
+    No,
+    Partial, // the start of one, it depends on the bytes to come
+    Full,
+}
+
+impl Separators {
+    pub const fn new(bytes: &[u8], marks: &'static [&'static str]) -> Self {
+        let mut table = [false; 256];
+        let mut i = 0;
+        while i < bytes.len() {
+            table[bytes[i] as usize] = true;
+            i += 1;
+        }
+        Self { table, marks }
+    }
+
+    pub fn contains(&self, byte: u8) -> bool {
+        self.table[byte as usize]
+    }
+
+    pub fn has_marks(&self) -> bool {
+        !self.marks.is_empty()
+    }
+
+    // for the places a character at a time goes by
+    pub fn ends_word(&self, c: char) -> bool {
+        if c.is_ascii() {
+            self.contains(c as u8)
+        } else {
+            self.marks.iter().any(|mark| mark.starts_with(c))
+        }
+    }
+
+    // the words in a run of bytes that has no separators from the table, split on the marks
+    pub fn split_marks<'a>(&self, bytes: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut words = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            match self.marks.iter().find(|mark| bytes[i..].starts_with(mark.as_bytes())) {
+                Some(mark) => {
+                    words.push(&bytes[start..i]);
+                    i += mark.len();
+                    start = i;
+                },
+                None => i += 1,
+            }
+        }
+        words.push(&bytes[start..]);
+        words.retain(|word| !word.is_empty());
+        words
+    }
+
+    fn mark_match(&self, bytes: &[u8]) -> MarkMatch {
+        let mut partial = false;
+        for mark in self.marks {
+            if mark.as_bytes() == bytes {
+                return MarkMatch::Full;
+            }
+            partial |= mark.as_bytes().starts_with(bytes);
+        }
+        if partial { MarkMatch::Partial } else { MarkMatch::No }
+    }
+}
+
+pub struct StreamCounter {
+    stats: FileStats,
+    in_word: bool,
+    mark: [u8; 4], // the start of what may be one of the separators' marks
+    mark_len: usize,
+    separators: &'static Separators,
+    decoder: Option<Utf8Decoder>, // only for --strict/--lossy, the fast path never decodes anything
+    line_width: Option<LineWidth>, // only for -L
+    normalizer: Option<Normalizer>, // only for --normalize, the char count comes from here then
+    sample: Option<Vec<u8>>,        // only for --compress-ratio, the start of the input
+    classifier: Option<LineClassifier>, // only for --code
+    hasher: Option<Hasher>,             // only for --hash
+    sniffer: Option<Sniffer>,           // only for --detect-type
+    lines: Option<Distinct>,            // only for --unique-lines/--duplicate-lines
+    words: Option<Distinct>,            // only for --unique-words
+    numbers: Option<(NumberCounter, numbers::Mode)>, // only for --numbers
+    char_classes: Option<CharClasses>,  // only for --char-classes
+    emoji: Option<EmojiCounter>,        // only for --non-ascii/--emoji
+    whitespace: Option<WhitespaceReport>, // only for --whitespace-report
+    occurrences: Option<Occurrences>,   // only for --count-char
+    line_classes: Option<LineClasses>,  // only for --blank-lines and the like
+    line_ends: Option<LineEnds>,        // only for --eol=crlf or any, the line count comes from here then
+    gnu: Option<GnuCounter>,            // only for --compat=gnu, the words and characters come from here then
+    ends_mid_char: bool,          // whether the last chunk stopped in the middle of a character
+    #[cfg(debug_assertions)]
+    reference: Option<kernel::Kernel>, // debug builds count everything again with the kernel, see kernel.rs
+}
+
+impl StreamCounter {
+    // the path (empty for stdin) is only looked at for the --code comment syntax
+    pub fn new(command_options: &CommandOptions, path: &str) -> Self {
+        let mut counter = Self {
+            stats: FileStats::new(),
+            in_word: false,
+            mark: [0; 4],
+            mark_len: 0,
+            separators: command_options.separators(),
+            decoder: (command_options.utf8_mode != Utf8Mode::Fast).then(Utf8Decoder::new),
+            line_width: command_options.count_max_line_length.then(|| LineWidth::new(command_options.tab_width, command_options.show_longest_text)),
+            normalizer: command_options.normalize.map(Normalizer::new),
+            sample: command_options.count_compress_ratio.then(Vec::new),
+            classifier: command_options.count_code.then(|| LineClassifier::new(code::syntax_for(path))),
+            hasher: command_options.hash.map(Hasher::new),
+            sniffer: command_options.detect_type.then(|| Sniffer::new(path)),
+            lines: command_options.distinct_lines(path),
+            words: command_options.distinct_words(path),
+            numbers: command_options.numbers.map(|mode| (NumberCounter::new(command_options.separators()), mode)),
+            char_classes: command_options.count_char_classes.then(CharClasses::new),
+            emoji: command_options.counts_emoji().then(EmojiCounter::new),
+            whitespace: command_options.count_whitespace.then(WhitespaceReport::new),
+            occurrences: (!command_options.count_chars_of.is_empty()).then(|| Occurrences::new(&command_options.count_chars_of)),
+            line_classes: (!command_options.count_lines_of.is_empty()).then(|| LineClasses::new(&command_options.count_lines_of)),
+            line_ends: (command_options.eol != Eol::Lf).then(|| LineEnds::new(command_options.eol)),
+            gnu: (command_options.compat == Compat::Gnu).then(|| GnuCounter::new(command_options.separators())),
+            ends_mid_char: false,
+            // the kernel only knows \n as a line end, and ascii separators
+            #[cfg(debug_assertions)]
+            reference: (!command_options.separators().has_marks() && command_options.eol == Eol::Lf && command_options.compat != Compat::Gnu)
+                .then(|| kernel::Kernel::with_separators(command_options.separators())),
+        };
+        if command_options.needs_histogram() {
+            counter.stats.byte_histogram = Some(Box::new([0; 256]));
+        }
+        counter.stats.counters = command_options.registry.start();
+        counter
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.stats.byte_count += chunk.len() as u64;
+        progress::add_bytes(chunk.len());
+        #[cfg(debug_assertions)]
+        if let Some(reference) = self.reference.as_mut() {
+            reference.feed(chunk);
+        }
+
+        // separators are all ascii and never show up inside a multi-byte character, so words and lines
+        // can be counted on the raw bytes whether or not the input turns out to be utf8
+        if self.separators.has_marks() {
+            self.count_words_with_marks(chunk);
+        } else {
+            for byte in chunk {
+                if *byte == b'\n' {
+                    self.stats.line_count += 1;
+                    self.end_word();
+                } else if self.separators.contains(*byte) {
+                    self.end_word();
+                } else {
+                    self.in_word = true;
+                }
+            }
+        }
+
+        if let Some(line_width) = self.line_width.as_mut() {
+            line_width.feed(chunk);
+        }
+        if let Some(histogram) = self.stats.byte_histogram.as_mut() {
+            for byte in chunk {
+                histogram[*byte as usize] += 1;
+            }
+        }
+        if let Some(classifier) = self.classifier.as_mut() {
+            classifier.feed(chunk);
+        }
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(chunk);
+        }
+        if let Some(sniffer) = self.sniffer.as_mut() {
+            sniffer.feed(chunk);
+        }
+        if let Some(lines) = self.lines.as_mut() {
+            lines.feed(chunk);
+        }
+        if let Some(words) = self.words.as_mut() {
+            words.feed(chunk);
+        }
+        if let Some((numbers, _)) = self.numbers.as_mut() {
+            numbers.feed(chunk);
+        }
+        if let Some(char_classes) = self.char_classes.as_mut() {
+            char_classes.feed(chunk);
+        }
+        if let Some(emoji) = self.emoji.as_mut() {
+            emoji.feed(chunk);
+        }
+        if let Some(whitespace) = self.whitespace.as_mut() {
+            whitespace.feed(chunk);
+        }
+        if let Some(occurrences) = self.occurrences.as_mut() {
+            occurrences.feed(chunk);
+        }
+        if let Some(line_classes) = self.line_classes.as_mut() {
+            line_classes.feed(chunk);
+        }
+        if let Some(line_ends) = self.line_ends.as_mut() {
+            line_ends.feed(chunk);
+        }
+        if let Some(gnu) = self.gnu.as_mut() {
+            gnu.feed(chunk);
+        }
+        for counter in self.stats.counters.iter_mut() {
+            counter.feed(chunk);
+        }
+        if let Some(sample) = self.sample.as_mut() {
+            let wanted = (entropy::SAMPLE_LEN - sample.len()).min(chunk.len());
+            sample.extend_from_slice(&chunk[..wanted]);
+        }
+
+        if let Some(normalizer) = self.normalizer.as_mut() {
+            normalizer.feed(chunk);
+        }
+
+        if let Some(decoder) = self.decoder.as_mut() {
+            decoder.feed(chunk);
+            return;
+        }
+        if self.normalizer.is_some() {
+            return;
+        }
+
+        // no decoding needed to count characters, every byte that doesn't continue one (10xxxxxx) starts one. Pure
+        // ascii blocks (most of them, usually) are just their length
+        for block in chunk.chunks(ASCII_BLOCK) {
+            let chars = if block.is_ascii() { block.len() } else { count_char_starts(block) };
+            self.stats.char_count += chars as u64;
+        }
+        if !chunk.is_empty() {
+            self.ends_mid_char = ends_mid_char(chunk);
+        }
+    }
+
+    /*
+    Returns the stats and, when the input was decoded, the offset of the first invalid utf8 in it. Only --strict and
+    --lossy decode, so the fast path always comes back with None.
+    */
+    pub fn finish(mut self) -> (FileStats, Option<u64>) {
+        let char_count = match (self.normalizer.is_some(), self.decoder.is_some()) {
+            (true, _) => CharCount::Normalized,
+            (false, true) => CharCount::Decoded { valid: false }, // until the decoder says otherwise below
+            (false, false) => CharCount::Kernel,
+        };
+        if self.mark_len > 0 {
+            self.in_word = true; // it never turned into one
+        }
+        self.end_word();
+        if let Some(line_width) = self.line_width.take() {
+            self.stats.set_longest(line_width.finish());
+        }
+
+        let invalid_utf8_at = match self.decoder.as_mut() {
+            Some(decoder) => {
+                decoder.finish(|_| {});
+                self.stats.char_count = decoder.chars();
+                decoder.first_error()
+            },
+            None => None,
+        };
+        if let Some(gnu) = self.gnu.take() {
+            (self.stats.word_count, self.stats.char_count) = gnu.finish();
+        }
+        if let Some(normalizer) = self.normalizer.take() {
+            self.stats.char_count = normalizer.finish();
+        }
+        if let Some(classifier) = self.classifier.take() {
+            self.stats.set_code_lines(classifier.finish());
+        }
+        if let Some(lines) = self.lines.take() {
+            self.stats.set_distinct_lines(lines.finish());
+        }
+        if let Some(words) = self.words.take() {
+            self.stats.set_distinct_words(words.finish());
+        }
+        if let Some(char_classes) = self.char_classes.take() {
+            self.stats.set_char_classes(char_classes.finish());
+        }
+        if let Some(emoji) = self.emoji.take() {
+            self.stats.set_emoji(emoji.finish());
+        }
+        if let Some(whitespace) = self.whitespace.take() {
+            self.stats.set_whitespace(whitespace.finish());
+        }
+        if let Some(line_classes) = self.line_classes.take() {
+            self.stats.set_line_classes(line_classes.finish());
+        }
+        if let Some(line_ends) = self.line_ends.take() {
+            self.stats.line_count = line_ends.finish();
+        }
+        if let Some(occurrences) = self.occurrences.take() {
+            self.stats.set_char_occurrences(occurrences.finish());
+        }
+        if let Some(hasher) = self.hasher.take() {
+            self.stats.hash = Some(hasher.finish());
+        }
+        if let Some(sniffer) = self.sniffer.take() {
+            self.stats.set_file_type(sniffer.finish());
+        }
+        if let Some(sample) = self.sample.take() {
+            self.stats.sample_len = sample.len() as u64;
+            self.stats.sample_compressed_len = entropy::compressed_len(&sample);
+        }
+
+        let char_count = match char_count {
+            CharCount::Decoded { .. } => CharCount::Decoded { valid: invalid_utf8_at.is_none() },
+            other => other,
+        };
+        #[cfg(debug_assertions)]
+        let reference = self.reference.take().map(kernel::Kernel::finish);
+        #[cfg(not(debug_assertions))]
+        let reference = None;
+        kernel::debug_validate(Counts::from(&self.stats), reference, char_count);
+        // after the check, --numbers=exclude takes words out of what the kernel counted
+        if let Some((numbers, mode)) = self.numbers.take() {
+            self.stats.set_numbers(numbers.finish(), mode);
+        }
+
+        (self.stats, invalid_utf8_at)
+    }
+
+    /*
+    Forgets a character cut in half at the end of the input. Used when a byte limit did the cutting, the bytes are still
+    counted but the partial character isn't and the input isn't treated as invalid because of it.
+    */
+    pub fn drop_partial_char(&mut self) {
+        #[cfg(debug_assertions)]
+        {
+            self.reference = None; // it has no idea a character was let go of
+        }
+        if let Some(normalizer) = self.normalizer.as_mut() {
+            normalizer.drop_partial_char();
+        }
+        if let Some(char_classes) = self.char_classes.as_mut() {
+            char_classes.drop_partial_char();
+        }
+        if let Some(emoji) = self.emoji.as_mut() {
+            emoji.drop_partial_char();
+        }
+        match self.decoder.as_mut() {
+            Some(decoder) => decoder.drop_partial_char(),
+            None if self.ends_mid_char => {
+                self.stats.char_count -= 1; // its leading byte was already counted as a character
+                self.ends_mid_char = false;
+            },
+            None => {},
+        }
+    }
+
+    /*
+    The same as the plain loop, only the bytes that might be a mark are held back until it's clear whether they are
+    one. If not they were part of a word, none of the marks' bytes are ascii so they can't have been anything else.
+    */
+    fn count_words_with_marks(&mut self, chunk: &[u8]) {
+        for byte in chunk {
+            if self.mark_len > 0 {
+                self.mark[self.mark_len] = *byte;
+                self.mark_len += 1;
+                match self.separators.mark_match(&self.mark[..self.mark_len]) {
+                    MarkMatch::Partial => continue,
+                    MarkMatch::Full => {
+                        self.mark_len = 0;
+                        self.end_word();
+                        continue;
+                    },
+                    MarkMatch::No => {
+                        self.mark_len = 0;
+                        self.in_word = true; // and the byte gets another look on its own
+                    },
+                }
+            }
+
+            if *byte == b'\n' {
+                self.stats.line_count += 1;
+                self.end_word();
+            } else if self.separators.contains(*byte) {
+                self.end_word();
+            } else if self.separators.mark_match(&[*byte]) == MarkMatch::Partial {
+                self.mark[0] = *byte;
+                self.mark_len = 1;
+            } else {
+                self.in_word = true;
+            }
+        }
+    }
+
+    fn end_word(&mut self) {
+        if self.in_word {
+            self.stats.word_count += 1;
+            self.in_word = false;
+        }
+    }
+}
+
+// whether the chunk stops before the end of the multi-byte character its last leading byte started
+fn ends_mid_char(chunk: &[u8]) -> bool {
+    let continuations = chunk.iter().rev().take(3).take_while(|byte| **byte & 0xC0 == 0x80).count();
+    let Some(leading) = chunk.len().checked_sub(continuations + 1).map(|i| chunk[i]) else { return false };
+
+    let needed = match leading {
+        0xC0..=0xDF => 1,
+        0xE0..=0xEF => 2,
+        0xF0..=0xF7 => 3,
+        _ => 0,
+    };
+    continuations < needed
+}
+
+// branch free so it vectorizes, a continuation byte is -64..-128 as an i8
+fn count_char_starts(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|byte| (**byte as i8) >= -0x40).count()
+}
+
+/*
+StreamCounter plus the --max-lines cut, a byte limit is already taken care of by reading through limited_reader.
+*/
+pub struct LimitedCounter {
+    counter: StreamCounter,
+    lines_left: Option<usize>,
+}
+
+impl LimitedCounter {
+    pub fn new(command_options: &CommandOptions, path: &str) -> Self {
+        Self {
+            counter: StreamCounter::new(command_options, path),
+            lines_left: command_options.read_limits.max_lines,
+        }
+    }
+
+    // counts as much of the chunk as the line limit allows, returns false once there's no point reading more
+    pub fn feed(&mut self, chunk: &[u8]) -> bool {
+        if self.lines_left == Some(0) {
+            return false;
+        }
+
+        let mut len = chunk.len();
+        if let Some(lines) = self.lines_left.as_mut() {
+            // cut the chunk right after the last line we're allowed
+            for (i, byte) in chunk.iter().enumerate() {
+                if *byte == b'\n' {
+                    *lines -= 1;
+                    if *lines == 0 {
+                        len = i + 1;
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.counter.feed(&chunk[..len]);
+        self.lines_left != Some(0)
+    }
+
+    // cut_by_bytes is whether the byte limit ended the input, see StreamCounter::drop_partial_char
+    pub fn finish(mut self, cut_by_bytes: bool) -> (FileStats, Option<u64>) {
+        if cut_by_bytes {
+            self.counter.drop_partial_char();
+        }
+        self.counter.finish()
+    }
+}
+
+/*
+Skips to the offset and caps the reader at the byte limits. The offset is skipped by reading since most of what comes
+through here can't seek, regular files go through limited_file instead.
+*/
+pub fn limited_reader<R: Read>(mut reader: R, read_limits: &ReadLimits) -> io::Result<io::Take<R>> {
+    io::copy(&mut (&mut reader).take(read_limits.offset), &mut io::sink())?;
+    Ok(capped(reader, read_limits))
+}
+
+// seeks to the offset rather than reading (and with --throttle, waiting) through everything before it
+pub fn limited_file<R: Read + Seek>(file: R, read_limits: &ReadLimits) -> io::Result<io::Take<R>> {
+    Ok(capped(seek_offset(file, read_limits)?, read_limits))
+}
+
+pub fn seek_offset<R: Seek>(mut file: R, read_limits: &ReadLimits) -> io::Result<R> {
+    if read_limits.offset > 0 {
+        file.seek(SeekFrom::Start(read_limits.offset))?;
+    }
+    Ok(file)
+}
+
+pub fn capped<R: Read>(reader: R, read_limits: &ReadLimits) -> io::Take<R> {
+    reader.take(read_limits.byte_cap().unwrap_or(u64::MAX))
+}
+
+// whether reading stopped because the byte limit was reached rather than at the end of the input
+pub fn was_cut(reader: &io::Take<impl Read>, read_limits: &ReadLimits) -> bool {
+    read_limits.byte_cap().is_some() && reader.limit() == 0
+}
+
+/*
+Counts everything the reader produces within the limits. Past the first couple of chunks the reading moves to a
+prefetch thread with two buffers going back and forth, so the next chunk is being read while the current one is
+counted. That's what keeps high latency filesystems (NFS and co) from leaving the CPU idle on every read...
+*/
+pub fn count_reader(reader: impl Read + Send, command_options: &CommandOptions, path: &str) -> io::Result<(FileStats, Option<u64>)> {
+    count_limited(limited_reader(reader, &command_options.read_limits)?, command_options, path)
+}
+
+// count_reader for a regular file, which seeks to the --offset
+pub fn count_file(file: impl Read + Seek + Send, command_options: &CommandOptions, path: &str) -> io::Result<(FileStats, Option<u64>)> {
+    count_limited(limited_file(file, &command_options.read_limits)?, command_options, path)
+}
+
+fn count_limited(mut reader: io::Take<impl Read + Send>, command_options: &CommandOptions, path: &str) -> io::Result<(FileStats, Option<u64>)> {
+    let read_limits = &command_options.read_limits;
+    let mut counter = LimitedCounter::new(command_options, path);
+    let mut buffer = vec![0; command_options.buffer_size];
+
+    // small inputs are done within a read or two, they don't need a thread
+    for _ in 0..2 {
+        match read_chunk(&mut reader, &mut buffer)? {
+            0 => return Ok(counter.finish(was_cut(&reader, read_limits))),
+            read => {
+                if !counter.feed(&buffer[..read]) {
+                    return Ok(counter.finish(false));
+                }
+            }
+        }
+    }
+
+    if count_prefetched(&mut reader, &mut counter, buffer)? {
+        return Ok(counter.finish(false));
+    }
+    Ok(counter.finish(was_cut(&reader, read_limits)))
+}
+
+/*
+Reads once, retrying interrupted reads unless the interruption was a Ctrl-C. How long it took is logged with -vv, a slow
+disk or network mount shows up there.
+*/
+pub fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let started = Instant::now();
+    loop {
+        // registered before the flag is checked, a Ctrl-C in between still wakes the read
+        let reading = interrupt::Reading::start();
+        if interrupt::is_interrupted() {
+            return Err(interrupt::interrupted_error());
+        }
+
+        let result = reader.read(buffer);
+        drop(reading);
+        match result {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            result => {
+                if let Ok(read) = result {
+                    tracing::debug!(bytes = read, elapsed_us = started.elapsed().as_micros() as u64, "read chunk");
+                }
+                return result;
+            },
+        }
+    }
+}
+
+// the first len bytes of reader, all of it if it's shorter, read with read_chunk so a Ctrl-C gets through to the sniffing
+pub fn read_start(reader: &mut impl Read, len: usize) -> io::Result<Vec<u8>> {
+    let mut start = vec![0; len];
+    let mut filled = 0;
+    while filled < len {
+        match read_chunk(reader, &mut start[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    start.truncate(filled);
+    Ok(start)
+}
+
+/*
+The double buffered part of count_reader, returns true when the counter stopped early (line limit) rather than the
+input running out.
+*/
+fn count_prefetched(reader: &mut (impl Read + Send), counter: &mut LimitedCounter, first_buffer: Vec<u8>) -> io::Result<bool> {
+    let buffer_size = first_buffer.len();
+    let (filled_sender, filled) = mpsc::sync_channel::<io::Result<Vec<u8>>>(1);
+    let (empty_sender, empty) = mpsc::channel::<Vec<u8>>();
+    let _ = empty_sender.send(first_buffer);
+    let _ = empty_sender.send(vec![0; buffer_size]);
+
+    let span = tracing::Span::current(); // so the reads are logged under the file they're for
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            let _span = span.entered();
+            // stops when the counter hangs up on us, or after passing on the end of the input / an error
+            while let Ok(mut buffer) = empty.recv() {
+                buffer.resize(buffer_size, 0);
+                let chunk = read_chunk(reader, &mut buffer).map(|read| {
+                    buffer.truncate(read);
+                    buffer
+                });
+
+                let is_last = !matches!(&chunk, Ok(buffer) if !buffer.is_empty());
+                if filled_sender.send(chunk).is_err() || is_last {
+                    return;
+                }
+            }
+        });
+
+        let result = loop {
+            match filled.recv() {
+                Ok(Ok(buffer)) if buffer.is_empty() => break Ok(false),
+                Ok(Ok(buffer)) => {
+                    if !counter.feed(&buffer) {
+                        break Ok(true);
+                    }
+                    let _ = empty_sender.send(buffer);
+                },
+                Ok(Err(err)) => break Err(err),
+                Err(_) => break Ok(false),
+            }
+        };
+
+        // hang up so the prefetch thread stops if it's still going
+        drop(filled);
+        drop(empty_sender);
+        result
+    })
+}
@@ -0,0 +1,62 @@
+/*
+"Did you mean" for options that don't exist: --line gets pointed at --lines, --wrods at --words and -lines at
+--lines. Typos are measured in edits (a swap of two neighbours being one), and a long enough option only needs enough
+of its start to pick it out, --unique-w for --unique-words. Nothing is suggested when nothing is close, a guess that's
+way off is worse than none...
+*/
+use crate::i18n::tr;
+
+// the unknown option's error message, with a suggestion if there's a good one
+pub fn unsupported(arg: &str, options: &[&str]) -> String {
+    match closest(arg, options) {
+        Some(option) => tr!("Recieved unsupported option: {} (did you mean '{}'?)", arg, option),
+        None => tr!("Recieved unsupported option: {}", arg),
+    }
+}
+
+fn closest<'a>(arg: &str, options: &[&'a str]) -> Option<&'a str> {
+    let flag = arg.split('=').next().unwrap_or(arg);
+    if !flag.starts_with("--") {
+        // -lines for --lines, a single letter has too many neighbours to guess at
+        let long = format!("-{}", flag);
+        return options.iter().copied().find(|option| *option == long);
+    }
+
+    let prefixed: Vec<&str> = options.iter().copied().filter(|option| option.starts_with(flag)).collect();
+    if let [option] = prefixed[..] {
+        return Some(option);
+    }
+
+    let allowed = (flag.len() - 2).div_ceil(3).max(1);
+    options
+        .iter()
+        .copied()
+        .map(|option| (distance(flag, option), option))
+        .filter(|(distance, _)| *distance <= allowed)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, option)| option)
+}
+
+// edits between two strings, insertions, deletions, substitutions and swapping two neighbours each counting one
+fn distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
@@ -0,0 +1,79 @@
+/*
+--summary: what the counted files look like as a whole, the smallest, largest, mean, median and 95th percentile of
+each column, for "how big is a typical file here" without a spreadsheet. Only the files count, not the groups or the
+total, and a column that isn't a number (a hash) has nothing to summarize. The percentiles are nearest rank ones, so
+they're always a value some file has...
+*/
+use crate::{CommandOptions, FileStats};
+
+pub const STATISTICS: [&str; 5] = ["min", "max", "mean", "median", "p95"];
+
+#[derive(Debug, Clone)]
+pub struct Summary {
+    files: usize,
+    columns: Vec<String>,
+    values: [Vec<Option<f64>>; 5], // in STATISTICS order, one for each column
+}
+
+impl Summary {
+    pub fn of<'a>(options: &CommandOptions, files: impl IntoIterator<Item = &'a FileStats>) -> Summary {
+        let columns = crate::column_names(options);
+        let mut by_column: Vec<Vec<f64>> = vec![Vec::new(); columns.len()];
+        let mut count = 0;
+        for stats in files {
+            count += 1;
+            for (column, value) in by_column.iter_mut().zip(crate::selected_counts(options, stats)) {
+                if let Ok(value) = value.parse::<f64>() {
+                    column.push(value);
+                }
+            }
+        }
+
+        let mut values: [Vec<Option<f64>>; 5] = Default::default();
+        for mut column in by_column {
+            column.sort_by(f64::total_cmp);
+            let mean = (!column.is_empty()).then(|| column.iter().sum::<f64>() / column.len() as f64);
+            values[0].push(column.first().copied());
+            values[1].push(column.last().copied());
+            values[2].push(mean);
+            values[3].push(median(&column));
+            values[4].push(percentile(&column, 95));
+        }
+        Summary { files: count, columns, values }
+    }
+
+    pub fn files(&self) -> usize {
+        self.files
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    // each of STATISTICS with its value for every column, None for a column with nothing to go on
+    pub fn rows(&self) -> impl Iterator<Item = (&'static str, &[Option<f64>])> {
+        STATISTICS.iter().copied().zip(self.values.iter().map(Vec::as_slice))
+    }
+}
+
+fn median(sorted: &[f64]) -> Option<f64> {
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(sorted[len / 2]),
+        len => Some((sorted[len / 2 - 1] + sorted[len / 2]) / 2.0),
+    }
+}
+
+fn percentile(sorted: &[f64], percent: usize) -> Option<f64> {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+// whole numbers stay whole, the mean and the like get two decimals
+pub fn format_value(value: Option<f64>) -> String {
+    match value {
+        None => String::from("-"),
+        Some(value) if value.fract() == 0.0 => format!("{}", value),
+        Some(value) => format!("{:.2}", value),
+    }
+}
@@ -0,0 +1,46 @@
+/*
+--throttle=RATE: a cap on how fast inputs are read, so a bulk count over a production NFS mount or a spinning disk
+leaves some of it for the services that actually live there. The files' reads pay for themselves: each one books the
+time its bytes take at RATE after the ones before it, and waits until then. That's one booking for the whole run,
+with -j the jobs share RATE instead of getting it each. Nothing is saved up while the run isn't reading, so there's
+no burst after a pause either. Mmap reads can't be paced, throttled files are read instead, and stdin, remote inputs
+and what archives and documents read of their files aren't throttled...
+
+    wc_clone -r --throttle=20 /mnt/nfs/logs
+*/
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// when the bytes read so far are paid for
+static PAID_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+
+// megabytes a second, or a size with a unit like --buffer-size's: 20, 20/s, 512K, 1G/s
+pub fn parse(value: &str) -> Result<u64, String> {
+    let rate = value.strip_suffix("/s").unwrap_or(value);
+    let bytes_per_second = if !rate.is_empty() && rate.bytes().all(|byte| byte.is_ascii_digit()) {
+        crate::parse_count("--throttle", rate)?.checked_mul(1_000_000).ok_or(format!("size for '--throttle' is too large: '{}'", value))?
+    } else {
+        crate::parse_size("--throttle", rate)?
+    };
+    match bytes_per_second {
+        0 => Err(format!("invalid value for '--throttle': '{}' (expected more than nothing a second)", value)),
+        bytes_per_second => Ok(bytes_per_second as u64),
+    }
+}
+
+// called after every read of a throttled file, with what it read
+pub fn pace(bytes: usize, bytes_per_second: u64) {
+    if bytes == 0 {
+        return;
+    }
+    let now = Instant::now();
+    let paid_until = {
+        let mut paid_until = PAID_UNTIL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let from = paid_until.filter(|paid_until| *paid_until > now).unwrap_or(now);
+        let until = from + Duration::from_secs_f64(bytes as f64 / bytes_per_second as f64);
+        *paid_until = Some(until);
+        until
+    };
+    thread::sleep(paid_until - now);
+}
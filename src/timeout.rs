@@ -0,0 +1,161 @@
+/*
+--timeout=SECONDS: how long an input gets to be read before it's given up on, reported as an error like any other
+read error, and the next one started. The clock starts when the input is opened, and every read waits for at most
+what's left of it: pipes, FIFOs, sockets and terminals get a poll() for the rest of the time before each read (and
+FIFOs are opened non-blocking, so one nobody ever writes to doesn't hang in open either), regular files can only be
+checked between reads. A read that blocks on a hard NFS mount that stopped answering still blocks, the kernel gives
+no way out of those...
+
+    wc_clone --timeout=5 -r /mnt/slow /tmp/feed.fifo
+*/
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::{Duration, Instant};
+
+use crate::cache::{self, FileReader};
+use crate::child::ChildOutput;
+use crate::i18n::tr;
+use crate::CommandOptions;
+
+pub fn parse(value: &str) -> Result<Duration, String> {
+    match value.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(Duration::from_secs_f64(seconds)),
+        _ => Err(format!("invalid value for '--timeout': '{}' (expected a number of seconds)", value)),
+    }
+}
+
+pub struct Deadline<R> {
+    inner: R,
+    limit: Option<(Instant, Duration)>, // when reading has to be done by, and the --timeout it came from
+    #[cfg(unix)]
+    pollable: Option<libc::c_int>, // the descriptor, when poll() can tell us whether a read would wait
+}
+
+pub fn open(path: &str, command_options: &CommandOptions) -> io::Result<Deadline<FileReader>> {
+    let Some(timeout) = command_options.timeout else {
+        return Ok(Deadline::unlimited(cache::open(path, command_options.no_cache_pollution)?.throttled(command_options.throttle)));
+    };
+
+    let file = open_nonblocking(path)?;
+    Ok(Deadline {
+        #[cfg(unix)]
+        pollable: pollable(std::os::unix::io::AsRawFd::as_raw_fd(&file)),
+        inner: cache::reader(file, command_options.no_cache_pollution).throttled(command_options.throttle),
+        limit: Some((Instant::now() + timeout, timeout)),
+    })
+}
+
+pub fn stdin(command_options: &CommandOptions) -> Deadline<io::Stdin> {
+    let Some(timeout) = command_options.timeout else {
+        return Deadline::unlimited(io::stdin());
+    };
+
+    // stdin is shared with whoever started us, so it's only polled, never switched to non-blocking
+    Deadline {
+        #[cfg(unix)]
+        pollable: pollable(libc::STDIN_FILENO),
+        inner: io::stdin(),
+        limit: Some((Instant::now() + timeout, timeout)),
+    }
+}
+
+// anything else, which can only be checked between reads
+pub fn limit<R: Read>(inner: R, command_options: &CommandOptions) -> Deadline<R> {
+    let mut deadline = Deadline::unlimited(inner);
+    deadline.limit = command_options.timeout.map(|timeout| (Instant::now() + timeout, timeout));
+    deadline
+}
+
+// a child's stdout, a pipe poll() can wait on like any other
+pub fn pipe(inner: ChildOutput, command_options: &CommandOptions) -> Deadline<ChildOutput> {
+    let mut deadline = limit(inner, command_options);
+    #[cfg(unix)]
+    if deadline.limit.is_some() {
+        deadline.pollable = Some(std::os::unix::io::AsRawFd::as_raw_fd(&deadline.inner));
+    }
+    deadline
+}
+
+impl<R> Deadline<R> {
+    fn unlimited(inner: R) -> Self {
+        Deadline {
+            inner,
+            limit: None,
+            #[cfg(unix)]
+            pollable: None,
+        }
+    }
+}
+
+impl<R: Read> Read for Deadline<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let Some((ends, timeout)) = self.limit else {
+            return self.inner.read(buffer);
+        };
+
+        loop {
+            let left = ends.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Err(timed_out(timeout));
+            }
+            #[cfg(unix)]
+            if let Some(fd) = self.pollable {
+                if !wait_readable(fd, left)? {
+                    return Err(timed_out(timeout));
+                }
+            }
+
+            match self.inner.read(buffer) {
+                // a non-blocking FIFO that poll() woke us up for without anything to read yet
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<R: Seek> Seek for Deadline<R> {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(position)
+    }
+}
+
+fn timed_out(timeout: Duration) -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, tr!("timed out after {}s", timeout.as_secs_f64()))
+}
+
+#[cfg(unix)]
+fn open_nonblocking(path: &str) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    // O_NONBLOCK does nothing for regular files, and for a FIFO it's what keeps open from waiting for a writer
+    File::options().read(true).custom_flags(libc::O_NONBLOCK).open(path)
+}
+
+#[cfg(not(unix))]
+fn open_nonblocking(path: &str) -> io::Result<File> {
+    File::open(path)
+}
+
+// pipes, FIFOs, sockets and terminals, the descriptors reads can wait on forever
+#[cfg(unix)]
+fn pollable(fd: libc::c_int) -> Option<libc::c_int> {
+    // SAFETY: fstat only writes into the stat we hand it, and a bad descriptor is an error, not UB
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+        return None;
+    }
+    matches!(stat.st_mode & libc::S_IFMT, libc::S_IFIFO | libc::S_IFSOCK | libc::S_IFCHR).then_some(fd)
+}
+
+// false when nothing showed up before left ran out
+#[cfg(unix)]
+fn wait_readable(fd: libc::c_int, left: Duration) -> io::Result<bool> {
+    let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+    let millis = left.as_millis().clamp(1, libc::c_int::MAX as u128) as libc::c_int;
+    // SAFETY: one pollfd that outlives the call
+    match unsafe { libc::poll(&mut pollfd, 1, millis) } {
+        -1 => Err(io::Error::last_os_error()), // EINTR included, read_chunk checks for Ctrl-C and tries again
+        0 => Ok(false),
+        _ => Ok(true),
+    }
+}
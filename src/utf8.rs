@@ -0,0 +1,195 @@
+/*
+Incremental utf8 decoder for --strict, --lossy and --normalize. It only keeps a few bytes of state, so a character split
+across two chunks decodes the same as one that isn't. Counts follow the same "maximal subpart" rule as
+String::from_utf8_lossy: every invalid sequence becomes exactly one U+FFFD, which is what --lossy reports as a character.
+The offset --strict reports is where the bad sequence starts, even when that was in an earlier chunk than the byte
+that showed it up, so the error doesn't move with --buffer-size either...
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8Mode {
+    Fast,   // count leading bytes, no validation at all
+    Strict, // invalid utf8 is an error
+    Lossy,  // count invalid sequences as U+FFFD
+}
+
+pub struct Utf8Decoder {
+    chars: u64, // complete characters, U+FFFD replacements included
+    first_error: Option<u64>,
+    position: u64,
+    started: u64, // where the current character began, which can be a chunk or two back
+    needed: u8, // continuation bytes still expected for the current character
+    code: u32,  // the bits of it seen so far
+    seen: u8,
+    lower: u8, // allowed range of the next continuation byte, narrower after E0/ED/F0/F4 to
+    upper: u8, // rule out overlongs, surrogates and anything past U+10FFFF
+}
+
+impl Utf8Decoder {
+    pub fn new() -> Self {
+        Self {
+            chars: 0,
+            first_error: None,
+            position: 0,
+            started: 0,
+            needed: 0,
+            code: 0,
+            seen: 0,
+            lower: 0x80,
+            upper: 0xBF,
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        // nothing pending and nothing but ascii ahead is by far the common case
+        if self.needed == 0 && bytes.is_ascii() {
+            self.chars += bytes.len() as u64;
+            self.position += bytes.len() as u64;
+            return;
+        }
+
+        self.decode(bytes, |_| {});
+    }
+
+    // same as feed but hands every character to emit as well, an invalid sequence as None
+    pub fn decode(&mut self, bytes: &[u8], mut emit: impl FnMut(Option<char>)) {
+        for byte in bytes {
+            self.feed_byte(*byte, &mut emit);
+            self.position += 1;
+        }
+    }
+
+    // a character left unfinished at the end of the input is one more invalid sequence
+    pub fn finish(&mut self, mut emit: impl FnMut(Option<char>)) {
+        if self.needed != 0 {
+            self.reset();
+            self.invalid_at(self.started, &mut emit);
+        }
+    }
+
+    pub fn chars(&self) -> u64 {
+        self.chars
+    }
+
+    // byte offset of the first invalid sequence, for --strict errors
+    pub fn first_error(&self) -> Option<u64> {
+        self.first_error
+    }
+
+    // a character that was still incomplete is forgotten, as if the input had ended before it started
+    pub fn drop_partial_char(&mut self) {
+        self.reset();
+    }
+
+    fn feed_byte(&mut self, byte: u8, emit: &mut impl FnMut(Option<char>)) {
+        if self.needed == 0 {
+            self.started = self.position;
+            match byte {
+                0x00..=0x7F => {
+                    self.chars += 1;
+                    emit(Some(byte as char));
+                },
+                0xC2..=0xDF => {
+                    self.code = (byte & 0x1F) as u32;
+                    self.needed = 1;
+                },
+                0xE0..=0xEF => {
+                    self.code = (byte & 0x0F) as u32;
+                    if byte == 0xE0 {
+                        self.lower = 0xA0;
+                    } else if byte == 0xED {
+                        self.upper = 0x9F;
+                    }
+                    self.needed = 2;
+                },
+                0xF0..=0xF4 => {
+                    self.code = (byte & 0x07) as u32;
+                    if byte == 0xF0 {
+                        self.lower = 0x90;
+                    } else if byte == 0xF4 {
+                        self.upper = 0x8F;
+                    }
+                    self.needed = 3;
+                },
+                _ => self.invalid_at(self.position, emit),
+            }
+            return;
+        }
+
+        if byte < self.lower || byte > self.upper {
+            // the sequence so far is one replacement, and this byte starts over on its own
+            self.reset();
+            self.invalid_at(self.started, emit);
+            self.feed_byte(byte, emit);
+            return;
+        }
+
+        self.lower = 0x80;
+        self.upper = 0xBF;
+        self.code = (self.code << 6) | (byte & 0x3F) as u32;
+        self.seen += 1;
+        if self.seen == self.needed {
+            self.reset();
+            self.chars += 1;
+            // the byte ranges above already rule out anything that isn't a char
+            emit(char::from_u32(self.code));
+        }
+    }
+
+    // an invalid sequence is reported where it starts, not at the byte that gave it away
+    fn invalid_at(&mut self, start: u64, emit: &mut impl FnMut(Option<char>)) {
+        emit(None);
+        self.chars += 1;
+        self.first_error.get_or_insert(start);
+    }
+
+    fn reset(&mut self) {
+        self.needed = 0;
+        self.seen = 0;
+        self.lower = 0x80;
+        self.upper = 0xBF;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(chunks: &[&[u8]]) -> (u64, Option<u64>) {
+        let mut decoder = Utf8Decoder::new();
+        for chunk in chunks {
+            decoder.feed(chunk);
+        }
+        decoder.finish(|_| {});
+        (decoder.chars(), decoder.first_error())
+    }
+
+    #[test]
+    fn a_character_split_across_chunks_decodes_whole() {
+        assert_eq!(decode(&[b"ab\xe2\x82", b"\xac"]), (3, None));
+        assert_eq!(decode(&[b"\xf0", b"\x9f\x98", b"\x80!"]), (2, None));
+    }
+
+    #[test]
+    fn an_invalid_sequence_split_across_chunks_is_reported_where_it_starts() {
+        // cut short by an ascii byte in the next chunk
+        assert_eq!(decode(&[b"ab\xe2\x82", b"x"]), (4, Some(2)));
+        // a continuation byte out of range for F0, two chunks after the sequence started
+        assert_eq!(decode(&[b"abc\xf0", b"\x9f", b"\xc0zz"]), (7, Some(3)));
+        // still unfinished when the input ends
+        assert_eq!(decode(&[b"abc\xe2", b"\x82"]), (4, Some(3)));
+    }
+
+    #[test]
+    fn the_offset_does_not_move_with_the_chunk_size() {
+        let inputs: [&[u8]; 4] = [b"caf\xc3\xa9 \xe2\x82 euro", b"ok \xf0\x9f\x98\x80 \xf0\x9f\x98", b"\xed\xa0\x80", b"plain"];
+        for input in inputs {
+            let expected = std::str::from_utf8(input).err().map(|err| err.valid_up_to() as u64);
+            let (whole_chars, _) = decode(&[input]);
+            for split in 0..=input.len() {
+                let (first, second) = input.split_at(split);
+                assert_eq!(decode(&[first, second]), (whole_chars, expected), "for {:?} split at {}", input, split);
+            }
+        }
+    }
+}
@@ -0,0 +1,425 @@
+/*
+Directory walking for -r/--recursive. Entries are visited in name order so results come out the same on every run.
+Symlinks are left alone unless --follow-symlinks, and when they are followed every directory is remembered by device
+and inode: a link pointing back up the tree is reported instead of sending us around in circles, and a directory
+reached a second way isn't counted twice. Hidden entries (a name starting with a dot) are skipped unless --hidden,
+.wcignore files leave out what they list (see ignore.rs) and --include/--exclude/--max-depth narrow it down further,
+as do --newer-than/--older-than going by when each file was last modified and --min-size/--max-size going by its
+size, both from the metadata the walk has anyway so a skipped file is never opened...
+*/
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::ignore::{self, IgnoreFile};
+use crate::winpath;
+
+// what gets walked into, operands themselves are always taken as given
+#[derive(Debug, Default)]
+pub struct WalkOptions {
+    pub include: Vec<String>, // globs, a file has to match one of them when there are any
+    pub exclude: Vec<String>, // globs, matching files are skipped and matching directories not walked at all
+    pub max_depth: Option<usize>, // 1 is the files directly inside an operand
+    pub newer_than: Option<SystemTime>, // files have to be modified after it
+    pub older_than: Option<SystemTime>, // or before it
+    pub min_size: Option<u64>, // bytes, both ends included
+    pub max_size: Option<u64>,
+    pub hidden: bool,
+    pub follow_symlinks: bool,
+}
+
+// identifies a directory however it was reached
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+struct Walker<'a> {
+    walk_options: &'a WalkOptions,
+    root: PathBuf,
+    // only kept when following symlinks, nothing else can lead back into a directory
+    visited: HashSet<DirId>,
+    ancestors: Vec<DirId>, // the directories we're inside right now, coming back to one of them is a loop
+    ignore_files: Vec<IgnoreFile>, // the .wcignores of the directories we're inside
+    files: Vec<String>,
+    errors: Vec<(String, io::Error)>,
+}
+
+/*
+Replaces every directory operand with the regular files found under it, anything else is passed through untouched so
+missing files still get reported by the normal read. Directories we fail to list are returned with their error.
+*/
+pub fn expand_operands(operands: &[String], walk_options: &WalkOptions) -> (Vec<String>, Vec<(String, io::Error)>) {
+    let mut walker = Walker {
+        walk_options,
+        root: PathBuf::new(),
+        visited: HashSet::new(),
+        ancestors: Vec::new(),
+        ignore_files: Vec::new(),
+        files: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for operand in operands {
+        match fs::metadata(operand) {
+            Ok(metadata) if metadata.is_dir() => {
+                walker.root = PathBuf::from(operand);
+                walker.walk_dir(Path::new(operand), &metadata, 1);
+            },
+            _ => walker.files.push(operand.clone()),
+        }
+    }
+
+    (walker.files, walker.errors)
+}
+
+impl Walker<'_> {
+    fn walk_dir(&mut self, dir: &Path, metadata: &fs::Metadata, depth: usize) {
+        if self.walk_options.max_depth.is_some_and(|max_depth| depth > max_depth) {
+            return;
+        }
+        if !self.walk_options.follow_symlinks {
+            self.walk_entries(dir, depth);
+            return;
+        }
+
+        let id = dir_id(dir, metadata);
+        if self.ancestors.contains(&id) {
+            let loop_error = io::Error::other("filesystem loop, this link leads back to a directory above it");
+            self.errors.push((dir.to_string_lossy().into_owned(), loop_error));
+            return;
+        }
+        #[allow(clippy::clone_on_copy)] // a PathBuf off unix
+        if !self.visited.insert(id.clone()) {
+            return; // already counted through another link
+        }
+
+        self.ancestors.push(id);
+        self.walk_entries(dir, depth);
+        self.ancestors.pop();
+    }
+
+    fn walk_entries(&mut self, dir: &Path, depth: usize) {
+        let mut entries: Vec<fs::DirEntry> = match fs::read_dir(dir) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok()).collect(),
+            Err(err) => {
+                self.errors.push((dir.to_string_lossy().into_owned(), err));
+                return;
+            }
+        };
+        entries.sort_by_key(|entry| entry.file_name());
+
+        let ignore_file = match ignore::load(dir) {
+            Ok(ignore_file) => ignore_file,
+            Err(err) => {
+                self.errors.push((dir.join(ignore::FILE_NAME).to_string_lossy().into_owned(), err));
+                None
+            },
+        };
+        let has_ignore_file = ignore_file.is_some();
+        self.ignore_files.extend(ignore_file);
+
+        for entry in entries {
+            let path = entry.path();
+            if !self.walk_options.hidden && entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            // globs with a slash in them are matched against the path below the operand, others just the name
+            let relative = winpath::slashed(path.strip_prefix(&self.root).unwrap_or(&path));
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let matches = |pattern: &String| glob_matches(pattern, if pattern.contains('/') { &relative } else { &name });
+            if self.walk_options.exclude.iter().any(matches) {
+                continue;
+            }
+            let included = self.walk_options.include.is_empty() || self.walk_options.include.iter().any(matches);
+
+            // entry metadata doesn't follow symlinks, so without --follow-symlinks links are neither files nor dirs
+            let metadata = match entry.metadata() {
+                Ok(metadata) if metadata.is_symlink() && self.walk_options.follow_symlinks => match fs::metadata(&path) {
+                    Ok(target) => target,
+                    Err(_) => {
+                        // dangling link, let the normal read report it
+                        self.files.push(path.to_string_lossy().into_owned());
+                        continue;
+                    }
+                },
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if ignore::is_ignored(&self.ignore_files, &path, metadata.is_dir()) {
+                continue;
+            }
+
+            if metadata.is_dir() {
+                self.walk_dir(&path, &metadata, depth + 1);
+            } else if metadata.is_file() && included && self.walk_options.wants(&metadata) {
+                self.files.push(path.to_string_lossy().into_owned());
+            }
+        }
+
+        if has_ignore_file {
+            self.ignore_files.pop();
+        }
+    }
+}
+
+impl WalkOptions {
+    // whether a file found by the walk gets past the size and age filters
+    fn wants(&self, metadata: &fs::Metadata) -> bool {
+        let size = metadata.len();
+        if self.min_size.is_some_and(|min_size| size < min_size) || self.max_size.is_some_and(|max_size| size > max_size) {
+            return false;
+        }
+        if self.newer_than.is_none() && self.older_than.is_none() {
+            return true;
+        }
+        // a file whose modification time can't be had doesn't pass
+        metadata.modified().is_ok_and(|modified| {
+            self.newer_than.is_none_or(|newer_than| modified > newer_than) && self.older_than.is_none_or(|older_than| modified < older_than)
+        })
+    }
+}
+
+/*
+How far back --newer-than and --older-than go: a number with s, m, h, d or w after it (seconds if there's nothing),
+fractions are fine, 24h, 1.5d, 90m...
+*/
+pub fn parse_age(flag: &str, value: &str) -> Result<SystemTime, String> {
+    let invalid = || format!("invalid value for '{}': '{}' (expected a number with s, m, h, d or w after it)", flag, value);
+    let number_end = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(number_end);
+    let seconds: f64 = match unit {
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 60.0 * 60.0,
+        "d" => 24.0 * 60.0 * 60.0,
+        "w" => 7.0 * 24.0 * 60.0 * 60.0,
+        _ => return Err(invalid()),
+    };
+    let age = number.parse::<f64>().ok().and_then(|number| Duration::try_from_secs_f64(number * seconds).ok()).ok_or_else(invalid)?;
+    // further back than the clock goes is just everything
+    Ok(SystemTime::now().checked_sub(age).unwrap_or(SystemTime::UNIX_EPOCH))
+}
+
+// --newer-than-file, newer than the last change to path
+pub fn modified(path: &str) -> Result<SystemTime, String> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).map_err(|err| format!("can't use '--newer-than-file': {}: {}", path, err))
+}
+
+#[cfg(unix)]
+fn dir_id(_dir: &Path, metadata: &fs::Metadata) -> DirId {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.dev(), metadata.ino())
+}
+
+// no inodes to go by, the canonical path is the next best thing
+#[cfg(not(unix))]
+fn dir_id(dir: &Path, _metadata: &fs::Metadata) -> DirId {
+    fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
+
+/*
+Shell style globs: * is anything but a slash, ** anything at all, ? one character and [abc]/[a-z]/[!abc] a set of them.
+*/
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_matches_at(&pattern, &text)
+}
+
+fn glob_matches_at(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // "**/" also matches no directory at all
+            let rest_after_slash = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=text.len()).any(|i| glob_matches_at(rest, &text[i..]) || glob_matches_at(rest_after_slash, &text[i..]))
+        },
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if glob_matches_at(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        },
+        Some('?') => !text.is_empty() && text[0] != '/' && glob_matches_at(&pattern[1..], &text[1..]),
+        Some('[') => match (pattern.iter().skip(2).position(|c| *c == ']'), text.first()) {
+            (Some(close), Some(c)) => {
+                let set = &pattern[1..close + 2];
+                let (negated, set) = match set.first() {
+                    Some('!') | Some('^') => (true, &set[1..]),
+                    _ => (false, set),
+                };
+                let mut in_set = false;
+                let mut i = 0;
+                while i < set.len() {
+                    if i + 2 < set.len() && set[i + 1] == '-' {
+                        in_set |= set[i] <= *c && *c <= set[i + 2];
+                        i += 3;
+                    } else {
+                        in_set |= set[i] == *c;
+                        i += 1;
+                    }
+                }
+                in_set != negated && glob_matches_at(&pattern[close + 3..], &text[1..])
+            },
+            (None, Some(c)) => *c == '[' && glob_matches_at(&pattern[1..], &text[1..]), // no closing bracket, literal
+            (_, None) => false,
+        },
+        Some(p) => text.first() == Some(p) && glob_matches_at(&pattern[1..], &text[1..]),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Ext,
+    Dir,
+}
+
+impl GroupBy {
+    pub fn parse(value: &str) -> Result<GroupBy, String> {
+        match value {
+            "ext" => Ok(GroupBy::Ext),
+            "dir" => Ok(GroupBy::Dir),
+            _ => Err(format!("invalid value for '--group-by': '{}' (expected ext or dir)", value)),
+        }
+    }
+}
+
+/*
+Which --group-by group a file belongs to: "*.rs" style for extensions, and for directories the operand it was found
+under plus the first directory below that, so `-r src` gives one group per directory directly inside src (and one for
+the files sitting in src itself).
+*/
+pub fn group_key(path: &str, operands: &[String], group_by: GroupBy) -> String {
+    match group_by {
+        GroupBy::Ext => match Path::new(path).extension() {
+            Some(extension) => format!("*.{}", extension.to_string_lossy().to_lowercase()),
+            None => String::from("(no extension)"),
+        },
+        GroupBy::Dir => {
+            let path = Path::new(path);
+            for operand in operands {
+                let Ok(relative) = path.strip_prefix(operand) else { continue };
+                let mut components = relative.components();
+                return match (components.next(), components.next()) {
+                    (Some(top), Some(_)) => Path::new(operand).join(top).to_string_lossy().into_owned(),
+                    _ if path == Path::new(operand) => parent_dir(path),
+                    _ => operand.clone(),
+                };
+            }
+            parent_dir(path)
+        },
+    }
+}
+
+fn parent_dir(path: &Path) -> String {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().into_owned(),
+        _ => String::from("."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh directory under the system temp dir with the given files in it, a name ending in / is an empty directory
+    fn scratch(test: &str, files: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("wc_clone-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            match file.strip_suffix('/') {
+                Some(_) => fs::create_dir_all(&path).unwrap(),
+                None => {
+                    fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    fs::write(&path, "x\n").unwrap();
+                },
+            }
+        }
+        root
+    }
+
+    // what the walk finds under root, relative to it
+    fn walk(root: &Path, walk_options: &WalkOptions) -> Vec<String> {
+        let (files, errors) = expand_operands(&[root.to_string_lossy().into_owned()], walk_options);
+        assert!(errors.is_empty(), "{:?}", errors);
+        files.iter().map(|file| winpath::slashed(Path::new(file).strip_prefix(root).unwrap())).collect()
+    }
+
+    #[test]
+    fn globs_match_like_the_shell() {
+        let table = [
+            ("*.rs", "main.rs", true),
+            ("*.rs", "main.rs.bak", false),
+            ("*.rs", "src/main.rs", false), // * stops at a slash
+            ("*", "", true),
+            ("?.txt", "a.txt", true),
+            ("?.txt", "ab.txt", false),
+            ("?", "/", false),
+            ("**/*.rs", "main.rs", true), // no directory at all
+            ("**/*.rs", "src/bin/main.rs", true),
+            ("src/**", "src/bin/main.rs", true),
+            ("src/**/test", "src/test", true),
+            ("src/**/test", "lib/src/test", false), // anchored at the start
+            ("src/*.rs", "src/main.rs", true),
+            ("src/*.rs", "src/bin/main.rs", false),
+            ("[ab]*", "b.txt", true),
+            ("[a-c]x", "dx", false),
+            ("[!a-c]x", "dx", true),
+            ("[x", "[x", true), // no closing bracket, a literal
+        ];
+        for (pattern, text, expected) in table {
+            assert_eq!(glob_matches(pattern, text), expected, "{} against {}", pattern, text);
+        }
+    }
+
+    #[test]
+    fn include_exclude_and_max_depth_leave_out_what_they_should() {
+        let root = scratch("walk", &["a.txt", "b.rs", ".hidden.txt", "sub/c.txt", "sub/deep/d.txt", "target/e.txt"]);
+
+        let everything = WalkOptions::default();
+        assert_eq!(walk(&root, &everything), ["a.txt", "b.rs", "sub/c.txt", "sub/deep/d.txt", "target/e.txt"]);
+
+        let hidden = WalkOptions { hidden: true, ..WalkOptions::default() };
+        assert_eq!(walk(&root, &hidden)[0], ".hidden.txt");
+
+        let shallow = WalkOptions { max_depth: Some(1), ..WalkOptions::default() };
+        assert_eq!(walk(&root, &shallow), ["a.txt", "b.rs"]);
+        let two_deep = WalkOptions { max_depth: Some(2), ..WalkOptions::default() };
+        assert_eq!(walk(&root, &two_deep), ["a.txt", "b.rs", "sub/c.txt", "target/e.txt"]);
+
+        // without a slash the name is matched at any depth, with one the path below the operand
+        let txt = WalkOptions { include: vec![String::from("*.txt")], ..WalkOptions::default() };
+        assert_eq!(walk(&root, &txt), ["a.txt", "sub/c.txt", "sub/deep/d.txt", "target/e.txt"]);
+        let sub_txt = WalkOptions { include: vec![String::from("sub/*.txt")], ..WalkOptions::default() };
+        assert_eq!(walk(&root, &sub_txt), ["sub/c.txt"]);
+        let no_target = WalkOptions { exclude: vec![String::from("target"), String::from("*.rs")], ..WalkOptions::default() };
+        assert_eq!(walk(&root, &no_target), ["a.txt", "sub/c.txt", "sub/deep/d.txt"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn wcignore_files_leave_out_what_they_list() {
+        let files = ["a.txt", "a.min.js", "keep.min.js", "vendor/v.txt", "sub/vendor", "sub/b.txt", "sub/build/c.txt", "build/d.txt", "sub/e.log"];
+        let root = scratch("wcignore", &files);
+        // vendor/ is only directories, build/ anchored to the top, *.min.js at any depth with one taken back
+        fs::write(root.join(ignore::FILE_NAME), "# generated\nvendor/\n/build\n*.min.js\n!keep.min.js\n").unwrap();
+        // a deeper one comes after it
+        fs::write(root.join("sub").join(ignore::FILE_NAME), "*.log\n").unwrap();
+
+        assert_eq!(walk(&root, &WalkOptions::default()), ["a.txt", "keep.min.js", "sub/b.txt", "sub/build/c.txt", "sub/vendor"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,83 @@
+/*
+--whitespace-report, the hygiene check a linter would do, minus the linter: how many lines end in spaces or tabs,
+and how many are indented with tabs, with spaces, or with both mixed together. A line that's only whitespace counts as
+trailing whitespace but isn't indented, there's nothing there to indent. The \r of a CRLF line ending is part of the
+line ending and not trailing whitespace...
+*/
+
+// the order the columns are printed in
+pub const TRAILING: usize = 0;
+pub const TABS: usize = 1;
+pub const SPACES: usize = 2;
+pub const MIXED: usize = 3;
+
+pub struct WhitespaceReport {
+    counts: [u64; 4],
+    in_indent: bool, // nothing but spaces and tabs so far on this line
+    indent_tabs: bool,
+    indent_spaces: bool,
+    ends_blank: bool, // the last byte before a possible \r was a space or a tab
+    line_empty: bool,
+}
+
+impl WhitespaceReport {
+    pub fn new() -> Self {
+        Self {
+            counts: [0; 4],
+            in_indent: true,
+            indent_tabs: false,
+            indent_spaces: false,
+            ends_blank: false,
+            line_empty: true,
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            match byte {
+                b'\n' => self.end_line(),
+                b'\r' => {},
+                b' ' | b'\t' => {
+                    if self.in_indent {
+                        self.indent_tabs |= *byte == b'\t';
+                        self.indent_spaces |= *byte == b' ';
+                    }
+                    self.ends_blank = true;
+                    self.line_empty = false;
+                },
+                _ => {
+                    if self.in_indent {
+                        self.in_indent = false;
+                        self.count_indent();
+                    }
+                    self.ends_blank = false;
+                    self.line_empty = false;
+                },
+            }
+        }
+    }
+
+    // a last line without a newline still counts
+    pub fn finish(mut self) -> [u64; 4] {
+        if !self.line_empty {
+            self.end_line();
+        }
+        self.counts
+    }
+
+    fn count_indent(&mut self) {
+        match (self.indent_tabs, self.indent_spaces) {
+            (true, true) => self.counts[MIXED] += 1,
+            (true, false) => self.counts[TABS] += 1,
+            (false, true) => self.counts[SPACES] += 1,
+            (false, false) => {},
+        }
+    }
+
+    fn end_line(&mut self) {
+        if self.ends_blank {
+            self.counts[TRAILING] += 1;
+        }
+        *self = Self { counts: self.counts, ..Self::new() };
+    }
+}
@@ -0,0 +1,124 @@
+/*
+Display width of the longest line for -L, measured the way GNU wc does it: tabs go to the next tab stop, CJK and emoji
+take two columns, combining marks and control characters none, and a carriage return or form feed starts over at
+column 0. Invalid utf8 takes no room at all. For --show-longest it also remembers which line that was, and with
+--show-longest=content the start of it...
+*/
+use unicode_width::UnicodeWidthChar;
+
+// default for --tab-width
+pub const TAB_WIDTH: usize = 8;
+
+// how much of the longest line --show-longest=content keeps, it's for recognizing the line and not reading it
+pub const SHOWN_CHARS: usize = 80;
+
+pub struct Longest {
+    pub width: u64,
+    pub line: u64,            // 1 is the first line
+    pub text: Option<String>, // only kept when asked for
+}
+
+pub struct LineWidth {
+    tab_width: u64,
+    column: u64,
+    longest: u64,
+    line: u64,
+    longest_line: u64,
+    text: Option<String>, // the start of the current line, enough bytes for SHOWN_CHARS once indentation is trimmed
+    longest_text: Option<String>,
+    pending: [u8; 4], // a multi-byte character split across chunks
+    pending_len: usize,
+    pending_needed: usize,
+}
+
+impl LineWidth {
+    pub fn new(tab_width: usize, keep_text: bool) -> Self {
+        Self {
+            tab_width: tab_width.max(1) as u64,
+            column: 0,
+            longest: 0,
+            line: 1,
+            longest_line: 0,
+            text: keep_text.then(String::new),
+            longest_text: None,
+            pending: [0; 4],
+            pending_len: 0,
+            pending_needed: 0,
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.feed_byte(*byte);
+        }
+    }
+
+    pub fn feed_char(&mut self, c: char) {
+        if let Some(text) = self.text.as_mut() {
+            if !matches!(c, '\n' | '\r' | '\x0c') && text.len() < SHOWN_CHARS * 8 {
+                text.push(c);
+            }
+        }
+
+        match c {
+            '\n' => {
+                self.end_line();
+                self.line += 1;
+                if let Some(text) = self.text.as_mut() {
+                    text.clear();
+                }
+            },
+            '\r' | '\x0c' => self.end_line(),
+            '\t' => self.column += self.tab_width - self.column % self.tab_width,
+            _ => self.column += c.width().unwrap_or(0) as u64,
+        }
+    }
+
+    // a last line without a newline still counts
+    pub fn finish(mut self) -> Longest {
+        self.end_line();
+        Longest { width: self.longest, line: self.longest_line, text: self.longest_text }
+    }
+
+    fn feed_byte(&mut self, byte: u8) {
+        if self.pending_len > 0 {
+            if byte & 0xC0 == 0x80 {
+                self.pending[self.pending_len] = byte;
+                self.pending_len += 1;
+                if self.pending_len == self.pending_needed {
+                    let decoded = std::str::from_utf8(&self.pending[..self.pending_len]).ok().and_then(|c| c.chars().next());
+                    if let Some(c) = decoded {
+                        self.feed_char(c);
+                    }
+                    self.pending_len = 0;
+                }
+                return;
+            }
+            self.pending_len = 0; // cut short, forget it and look at this byte on its own
+        }
+
+        let needed = match byte {
+            0x00..=0x7F => {
+                self.feed_char(byte as char);
+                return;
+            },
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => return, // stray continuation or never valid
+        };
+        self.pending[0] = byte;
+        self.pending_len = 1;
+        self.pending_needed = needed;
+    }
+
+    // the first of the longest lines is the one that's shown
+    fn end_line(&mut self) {
+        if self.column > self.longest || self.longest_line == 0 {
+            self.longest = self.column;
+            self.longest_line = self.line;
+            self.longest_text = self.text.clone();
+        }
+        self.column = 0;
+    }
+}
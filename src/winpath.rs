@@ -0,0 +1,35 @@
+/*
+Windows paths. Opening and listing anything past MAX_PATH (deep node_modules trees) is taken care of by std, which
+puts the \\?\ prefix in front of long paths itself, so what's left is what the prefix changes: a \\?\C:\... or
+\\?\UNC\server\share\... path is used exactly as written, with no / to \ conversion, so a path someone wrote with a
+slash somewhere in it (or that --files-from read as one) can't be opened. Those get their slashes turned around here.
+Plain UNC paths (\\server\share\dir) already work like any other.
+
+The paths found by -r have \ between their directories too, and the globs of --include/--exclude and .wcignore files
+are written with /, so they're matched against the path with its separators turned into slashes...
+*/
+use std::path::Path;
+
+#[cfg(windows)]
+pub fn operand(path: String) -> String {
+    match path.strip_prefix(r"\\?\") {
+        Some(rest) if rest.contains('/') => format!(r"\\?\{}", rest.replace('/', r"\")),
+        _ => path,
+    }
+}
+
+#[cfg(not(windows))]
+pub fn operand(path: String) -> String {
+    path
+}
+
+// path as the globs see it
+#[cfg(windows)]
+pub fn slashed(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(not(windows))]
+pub fn slashed(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
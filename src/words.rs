@@ -0,0 +1,55 @@
+/*
+--word-policy, for matching a word processor's count. By default a word is anything between whitespace, so "don't"
+and "well-known" are one word each, the way wc and Word count them. Some tools split them apart instead, and with
+split-apostrophes, split-hyphens or split (both) so does this. The typographic ’ and the unicode hyphens ‐ and ‑ are
+split on along with the ascii ' and -, text from a word processor is mostly the former...
+*/
+use crate::stream::{Separators, DEFAULT_SEPARATORS, POSIX_SEPARATORS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordPolicy {
+    Joined,
+    SplitApostrophes,
+    SplitHyphens,
+    Split,
+}
+
+impl WordPolicy {
+    pub fn parse(value: &str) -> Result<WordPolicy, String> {
+        match value {
+            "joined" => Ok(WordPolicy::Joined),
+            "split-apostrophes" => Ok(WordPolicy::SplitApostrophes),
+            "split-hyphens" => Ok(WordPolicy::SplitHyphens),
+            "split" => Ok(WordPolicy::Split),
+            _ => Err(format!(
+                "invalid value for '--word-policy': '{}' (expected joined, split-apostrophes, split-hyphens or split)",
+                value
+            )),
+        }
+    }
+
+    // posix is whether the vertical tab and form feed separate words too, like isspace()
+    pub fn separators(self, posix: bool) -> &'static Separators {
+        match (self, posix) {
+            (WordPolicy::Joined, false) => &DEFAULT_SEPARATORS,
+            (WordPolicy::Joined, true) => &POSIX_SEPARATORS,
+            (WordPolicy::SplitApostrophes, false) => &APOSTROPHE_SEPARATORS,
+            (WordPolicy::SplitApostrophes, true) => &POSIX_APOSTROPHE_SEPARATORS,
+            (WordPolicy::SplitHyphens, false) => &HYPHEN_SEPARATORS,
+            (WordPolicy::SplitHyphens, true) => &POSIX_HYPHEN_SEPARATORS,
+            (WordPolicy::Split, false) => &SPLIT_SEPARATORS,
+            (WordPolicy::Split, true) => &POSIX_SPLIT_SEPARATORS,
+        }
+    }
+}
+
+const APOSTROPHES: &[&str] = &["\u{2019}"]; // ’
+const HYPHENS: &[&str] = &["\u{2010}", "\u{2011}"]; // hyphen and non-breaking hyphen
+const BOTH: &[&str] = &["\u{2019}", "\u{2010}", "\u{2011}"];
+
+static APOSTROPHE_SEPARATORS: Separators = Separators::new(b" \t\r\n'", APOSTROPHES);
+static POSIX_APOSTROPHE_SEPARATORS: Separators = Separators::new(b" \t\r\n\x0b\x0c'", APOSTROPHES);
+static HYPHEN_SEPARATORS: Separators = Separators::new(b" \t\r\n-", HYPHENS);
+static POSIX_HYPHEN_SEPARATORS: Separators = Separators::new(b" \t\r\n\x0b\x0c-", HYPHENS);
+static SPLIT_SEPARATORS: Separators = Separators::new(b" \t\r\n'-", BOTH);
+static POSIX_SPLIT_SEPARATORS: Separators = Separators::new(b" \t\r\n\x0b\x0c'-", BOTH);
@@ -0,0 +1,128 @@
+// the stable part of the library, see src/prelude.rs: what's tested here only changes with a new major version
+use wc_clone::prelude::*;
+
+#[test]
+fn count_str_counts_lines_words_and_bytes() {
+    let stats = count_str("one two\nthree\n", &CountOptions::new());
+    assert_eq!((stats.lines(), stats.words(), stats.bytes()), (2, 3, 14));
+}
+
+#[test]
+fn count_str_counts_nothing_in_nothing() {
+    let stats = count_str("", &CountOptions::new().chars(true).max_line_length(true));
+    assert_eq!((stats.lines(), stats.words(), stats.chars(), stats.bytes(), stats.max_line_length()), (0, 0, 0, 0, 0));
+}
+
+#[test]
+fn count_str_counts_characters_not_bytes() {
+    let stats = count_str("h\u{e9}llo w\u{f6}rld", &CountOptions::new().chars(true));
+    assert_eq!((stats.words(), stats.chars(), stats.bytes()), (2, 11, 13));
+}
+
+#[test]
+fn count_reader_agrees_with_count_str() {
+    let text = "a line\n\tanother, longer line\nno newline at the end";
+    let options = CountOptions::new().chars(true).max_line_length(true);
+    let from_str = count_str(text, &options);
+    let from_reader = count_reader(text.as_bytes(), &options).unwrap();
+    assert_eq!(from_reader.lines(), from_str.lines());
+    assert_eq!(from_reader.words(), from_str.words());
+    assert_eq!(from_reader.chars(), from_str.chars());
+    assert_eq!(from_reader.bytes(), from_str.bytes());
+    assert_eq!(from_reader.max_line_length(), from_str.max_line_length());
+}
+
+#[test]
+fn count_reader_fails_on_invalid_utf8_with_strict() {
+    let strict = CountOptions::parse(["-m", "--strict"]).unwrap();
+    let err = count_reader(&b"caf\xe9"[..], &strict).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    // and counts it without
+    assert_eq!(count_reader(&b"caf\xe9"[..], &CountOptions::new()).unwrap().bytes(), 4);
+}
+
+#[test]
+fn builder_methods_choose_what_is_counted() {
+    let lines_only = CountOptions::default().words(false).bytes(false);
+    let stats = count_str("one two\nthree\n", &lines_only);
+    assert_eq!(stats.lines(), 2);
+
+    let tabbed = CountOptions::new().max_line_length(true);
+    assert_eq!(count_str("\tx", &tabbed).max_line_length(), 9);
+    assert_eq!(count_str("\tx", &tabbed.tab_width(4)).max_line_length(), 5);
+    assert_eq!(count_str("\tx", &CountOptions::new().max_line_length(true).tab_width(0)).max_line_length(), 2);
+}
+
+#[test]
+fn parse_takes_the_binarys_options() {
+    let first_line = CountOptions::parse(["-l", "--max-lines=1"]).unwrap().words(true);
+    let stats = count_str("one two\nthree\n", &first_line);
+    assert_eq!((stats.lines(), stats.words()), (1, 2));
+
+    let width = CountOptions::parse(["-L", "--tab-width=2"]).unwrap();
+    assert_eq!(count_str("\tx", &width).max_line_length(), 3);
+
+    let words = CountOptions::parse(["-w", "--numbers=exclude"]).unwrap();
+    assert_eq!(count_str("one 2 three 4.5\n", &words).words(), 2);
+}
+
+#[test]
+fn parse_refuses_files_and_unknown_options() {
+    assert!(CountOptions::parse(["notes.txt"]).is_err());
+    assert!(CountOptions::parse(["-l", "notes.txt"]).is_err());
+    assert!(CountOptions::parse(["--no-such-option"]).is_err());
+}
+
+#[test]
+fn file_stats_add_up() {
+    let options = CountOptions::new().chars(true).max_line_length(true);
+    let a = count_str("one two\n", &options);
+    let b = count_str("three four five\n", &options);
+
+    let total = a.clone() + b.clone();
+    assert_eq!((total.lines(), total.words(), total.chars(), total.bytes()), (2, 5, 24, 24));
+    assert_eq!(total.max_line_length(), 15); // the longest of them, not the sum
+
+    let mut total_by_assign = FileStats::new();
+    total_by_assign += &a;
+    total_by_assign += b.clone();
+    assert_eq!(total_by_assign.words(), total.words());
+
+    let summed: FileStats = [a, b].into_iter().sum();
+    assert_eq!(summed.bytes(), total.bytes());
+}
+
+#[test]
+fn file_stats_start_out_empty() {
+    let stats = FileStats::new();
+    assert_eq!((stats.lines(), stats.words(), stats.chars(), stats.bytes()), (0, 0, 0, 0));
+    assert_eq!(stats.longest_line(), None);
+    assert_eq!(FileStats::default().bytes(), 0);
+}
+
+#[test]
+fn file_stats_display_as_lines_words_and_bytes() {
+    let stats = count_str("one two\nthree\n", &CountOptions::new());
+    assert_eq!(stats.to_string(), "2 3 14");
+}
+
+// what GNU wc 9.1 prints for these with -wm under C.UTF-8: invalid bytes are neither characters nor part of a word
+#[test]
+fn compat_gnu_counts_invalid_utf8_like_gnu_wc() {
+    let gnu = CountOptions::parse(["--compat=gnu", "-wm"]).unwrap();
+    let cases: [(&[u8], (u64, u64)); 8] = [
+        (b"caf\xc3\xa9 ok", (2, 7)),
+        (b"a\xffb c", (2, 4)),
+        (b"ab\x80\x80 \xc3", (1, 3)),
+        (b"x\xe2\x82 y", (2, 3)),
+        (b"\xf0\x9f\x98\x80\xf0\x9f\x98", (1, 1)),
+        // overlong forms and a surrogate
+        (b"\xc0\xaf x", (1, 2)),
+        (b"\xe0\x80\xaf y z", (2, 4)),
+        (b"\xed\xa0\x80 w", (1, 2)),
+    ];
+    for (input, expected) in cases {
+        let stats = count_reader(input, &gnu).unwrap();
+        assert_eq!((stats.words(), stats.chars()), expected, "for {:?}", input);
+    }
+}